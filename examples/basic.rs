@@ -49,6 +49,10 @@ async fn main() {
                         println!("\n❌ Progress was cancelled!");
                         break;
                     }
+                    State::Failed => {
+                        println!("\n❌ Progress failed!");
+                        break;
+                    }
                     _ => {}
                 }
             }
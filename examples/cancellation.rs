@@ -22,7 +22,7 @@ async fn main() {
             if i == 30 {
                 println!("\n⏸️  Pausing task at 30%...");
                 updater.pause();
-                tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 println!("▶️  Resuming task...");
             }
 
@@ -70,6 +70,10 @@ async fn main() {
                         println!("\n❌ Progress was cancelled!");
                         break;
                     }
+                    State::Failed => {
+                        println!("\n❌ Progress failed!");
+                        break;
+                    }
                 }
             }
         } => {}
@@ -54,6 +54,9 @@ async fn main() {
             State::Cancelled => {
                 println!("\n❌ Progress was cancelled!");
             }
+            State::Failed => {
+                println!("\n❌ Progress failed!");
+            }
         }
     })
     .await;
@@ -0,0 +1,174 @@
+//! Procedural macros for [progressor](https://docs.rs/progressor).
+//!
+//! Use them through the `macros` feature of `progressor` rather than depending on this
+//! crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Expr, Fields, Ident, ItemFn, Member, ReturnType, Token, parse::Parse,
+    parse::ParseStream, parse_macro_input, parse_quote,
+};
+
+/// Arguments of `#[tracked(total = ..., updater = ...)]`.
+struct TrackedArgs {
+    total: Expr,
+    updater: Ident,
+}
+
+impl Parse for TrackedArgs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut total = None;
+        let mut updater = None;
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if key == "total" {
+                total = Some(input.parse()?);
+            } else if key == "updater" {
+                updater = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(key.span(), "expected `total` or `updater`"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self {
+            total: total.unwrap_or_else(|| parse_quote!(0)),
+            updater: updater.unwrap_or_else(|| parse_quote!(updater)),
+        })
+    }
+}
+
+/// Turns an `async fn` into a function returning `impl Progress`.
+///
+/// The body runs as a task created by `progressor::progress`, with a mutable
+/// `ProgressUpdater` named `updater` in scope. Arguments:
+///
+/// - `total = expr`: The total to track against, which may refer to the function's
+///   parameters. Defaults to zero.
+/// - `updater = name`: Binds the updater to another name.
+#[proc_macro_attribute]
+pub fn tracked(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as TrackedArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    expand(args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(args: TrackedArgs, item: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = item;
+    let Some(asyncness) = sig.asyncness.take() else {
+        return Err(syn::Error::new_spanned(
+            sig.fn_token,
+            "`#[tracked]` can only be applied to an `async fn`",
+        ));
+    };
+    let output = match &sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => quote!(#ty),
+    };
+    sig.output = parse_quote!(-> impl ::progressor::Progress<Output = #output>);
+
+    let TrackedArgs { total, updater } = args;
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            ::progressor::progress(
+                #total,
+                move |#[allow(unused_mut, unused_variables)] mut #updater: ::progressor::ProgressUpdater| {
+                    #asyncness move #block
+                },
+            )
+        }
+    })
+}
+
+/// Implements `Progress` for a wrapper by forwarding to one of its fields.
+///
+/// The field is the only field of the struct, or the one marked `#[progress]`. The
+/// wrapper must implement `Future` itself.
+#[proc_macro_derive(Progress, attributes(progress))]
+pub fn derive_progress(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_derive(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`#[derive(Progress)]` only supports structs",
+        ));
+    };
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+    let marked: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            field
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("progress"))
+        })
+        .collect();
+    let (index, field) = match (marked.as_slice(), fields.as_slice()) {
+        ([(index, field)], _) => (*index, **field),
+        ([], [field]) => (0, *field),
+        ([_, second, ..], _) => {
+            return Err(syn::Error::new_spanned(
+                second.1,
+                "only one field can be marked `#[progress]`",
+            ));
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "mark the field that reports progress with `#[progress]`",
+            ));
+        }
+    };
+    let member = field
+        .ident
+        .clone()
+        .map_or_else(|| Member::from(index), Member::from);
+    let ty = &field.ty;
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#ty: ::progressor::Progress));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::progressor::Progress for #ident #ty_generics #where_clause {
+            fn progress(
+                &self,
+            ) -> impl ::progressor::__private::Stream<Item = ::progressor::ProgressUpdate>
+                   + ::core::marker::Unpin
+                   + ::core::marker::Send
+                   + 'static {
+                ::progressor::Progress::progress(&self.#member)
+            }
+
+            fn transitions(&self) -> ::progressor::TransitionLog {
+                ::progressor::Progress::transitions(&self.#member)
+            }
+        }
+    })
+}
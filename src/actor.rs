@@ -0,0 +1,216 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use std::sync::{Arc, Mutex};
+
+use async_broadcast::InactiveReceiver;
+use futures_core::Stream;
+
+use crate::{
+    Progress, ProgressController, ProgressUpdate, ProgressUpdater, sync::lock, updater::detached,
+};
+
+/// A change to a job's progress, sent as a message to the actor that owns the job.
+///
+/// Workers that talk to an actor through its mailbox send these, and the actor applies
+/// them with [`ProgressReporter::apply`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressMessage {
+    /// Sets the current value.
+    Update(u64),
+    /// Advances the current value by the given amount.
+    Advance(u64),
+    /// Sets the current value and attaches a message.
+    UpdateWithMessage(u64, String),
+    /// Changes the total.
+    SetTotal(u64),
+    /// Pauses the job.
+    Pause,
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    output: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// The handle an actor owns to report the progress of a job and deliver its output.
+///
+/// Created by [`actor_job`] together with the [`ActorJob`] that the rest of the system
+/// awaits. Dropping the reporter without calling [`finish`](Self::finish) reports the
+/// job as cancelled.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct ProgressReporter<T> {
+    updater: ProgressUpdater,
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> ProgressReporter<T> {
+    /// Returns the updater for reporting progress directly.
+    pub const fn updater(&mut self) -> &mut ProgressUpdater {
+        &mut self.updater
+    }
+
+    /// Applies a progress message received in the actor's mailbox.
+    pub fn apply(&mut self, message: ProgressMessage) {
+        match message {
+            ProgressMessage::Update(current) => self.updater.update(current),
+            ProgressMessage::Advance(amount) => {
+                let current = self.updater.current().saturating_add(amount);
+                self.updater.update(current);
+            }
+            ProgressMessage::UpdateWithMessage(current, message) => {
+                self.updater.update_with_message(current, message);
+            }
+            ProgressMessage::SetTotal(total) => self.updater.set_total(total),
+            ProgressMessage::Pause => self.updater.pause(),
+        }
+    }
+
+    /// Completes the job, resolving the [`ActorJob`] to `output`.
+    pub fn finish(mut self, output: T) {
+        lock(&self.slot).output = Some(output);
+        self.updater.complete();
+    }
+}
+
+impl<T> Drop for ProgressReporter<T> {
+    fn drop(&mut self) {
+        let mut slot = lock(&self.slot);
+        slot.closed = true;
+        let waker = slot.waker.take();
+        drop(slot);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A job run by an actor, seen from the rest of the system.
+///
+/// It implements [`Progress`], and resolves to the output passed to
+/// [`ProgressReporter::finish`], or to `None` if the reporter was dropped without
+/// finishing.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct ActorJob<T> {
+    receiver: InactiveReceiver<ProgressUpdate>,
+    controller: ProgressController,
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> ActorJob<T> {
+    /// Returns a controller for sending feedback, such as cancellation, to the actor.
+    ///
+    /// The actor sees it through its reporter's [updater](ProgressReporter::updater).
+    #[must_use]
+    pub fn controller(&self) -> ProgressController {
+        self.controller.clone()
+    }
+}
+
+impl<T> Future for ActorJob<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = lock(&self.slot);
+        if let Some(output) = slot.output.take() {
+            return Poll::Ready(Some(output));
+        }
+        if slot.closed {
+            return Poll::Ready(None);
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Progress for ActorJob<T> {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.receiver.activate_cloned()
+    }
+
+    fn controller(&self) -> Option<ProgressController> {
+        Some(self.controller.clone())
+    }
+}
+
+/// Creates a job for an actor: the reporter the actor owns, and the job others await.
+///
+/// Actor frameworks and hand-written actors built on channels keep long-running work in
+/// the actor's state rather than in a future. Hand the [`ProgressReporter`] to the actor
+/// and expose the [`ActorJob`] to the rest of the system, which can observe it like any
+/// other [`Progress`].
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{ProgressExt, ProgressMessage, actor_job};
+///
+/// # async fn example() {
+/// let (mut reporter, job) = actor_job::<&str>(3);
+///
+/// // The actor applies messages from its mailbox and finishes the job.
+/// std::thread::spawn(move || {
+///     for _ in 0..3 {
+///         reporter.apply(ProgressMessage::Advance(1));
+///     }
+///     reporter.finish("indexed");
+/// });
+///
+/// let output = job.observe(|update| println!("{}/3", update.current())).await;
+/// assert_eq!(output, Some("indexed"));
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[must_use]
+pub fn actor_job<T>(total: u64) -> (ProgressReporter<T>, ActorJob<T>) {
+    let (updater, receiver) = detached(total);
+    let slot = Arc::new(Mutex::new(Slot {
+        output: None,
+        closed: false,
+        waker: None,
+    }));
+    let job = ActorJob {
+        receiver,
+        controller: updater.controller(),
+        slot: slot.clone(),
+    };
+    (ProgressReporter { updater, slot }, job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_actor_job() {
+        let (mut reporter, job) = actor_job(10);
+        let updates = job.progress();
+        tokio::spawn(async move {
+            reporter.apply(ProgressMessage::Update(4));
+            reporter.apply(ProgressMessage::Advance(6));
+            reporter.finish(42);
+        });
+        assert_eq!(job.await, Some(42));
+
+        let currents: Vec<_> = updates.map(|update| update.current()).collect().await;
+        assert_eq!(currents, [4, 10, 10]);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_reporter() {
+        let (reporter, job) = actor_job::<()>(1);
+        let updates = job.progress();
+        drop(reporter);
+        assert_eq!(job.await, None);
+        let updates: Vec<_> = updates.collect().await;
+        assert!(updates[0].is_cancelled());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::ProgressUpdate;
+
+/// Detects throughput staying under a threshold for a whole window.
+///
+/// Backs [`ProgressExt::alarm_if_rate_below`](crate::ProgressExt::alarm_if_rate_below).
+/// The alarm trips once when the rate over the last window falls under the threshold
+/// and re-arms when it recovers. Time spent paused does not count.
+#[derive(Debug)]
+pub struct RateAlarm {
+    threshold: f64,
+    window: Duration,
+    since: Instant,
+    samples: VecDeque<(Instant, u64)>,
+    current: u64,
+    paused: bool,
+    tripped: bool,
+}
+
+impl RateAlarm {
+    pub fn new(threshold: f64, window: Duration, now: Instant) -> Self {
+        Self {
+            threshold,
+            window,
+            since: now,
+            samples: VecDeque::from([(now, 0)]),
+            current: 0,
+            paused: false,
+            tripped: false,
+        }
+    }
+
+    pub fn record(&mut self, update: &ProgressUpdate, now: Instant) {
+        self.current = update.current();
+        let paused = !update.is_working();
+        if paused || self.paused {
+            // Measure a fresh window after every pause.
+            self.since = now;
+            self.samples.clear();
+            self.tripped = false;
+        }
+        self.paused = paused;
+        self.samples.push_back((now, self.current));
+    }
+
+    /// Returns the rate, in units per second, if it just fell under the threshold.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn check(&mut self, now: Instant) -> Option<f64> {
+        if self.paused || now.saturating_duration_since(self.since) < self.window {
+            return None;
+        }
+        let start = now.checked_sub(self.window)?;
+        while self.samples.len() > 1 && self.samples[1].0 <= start {
+            self.samples.pop_front();
+        }
+        let &(at, baseline) = self.samples.front()?;
+        let elapsed = now.saturating_duration_since(at).as_secs_f64();
+        let rate = self.current.saturating_sub(baseline) as f64 / elapsed;
+        if rate >= self.threshold {
+            self.tripped = false;
+            None
+        } else if self.tripped {
+            None
+        } else {
+            self.tripped = true;
+            Some(rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+
+    #[test]
+    fn test_alarm_trips_once_per_slowdown() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let working = |current| ProgressUpdate::new(1000, current, State::Working, None);
+        let mut alarm = RateAlarm::new(10.0, Duration::from_secs(10), start);
+
+        // A full window has not passed yet.
+        alarm.record(&working(50), at(5));
+        assert_eq!(alarm.check(at(9)), None);
+
+        // 200 units in 10 seconds, then a stall.
+        alarm.record(&working(200), at(10));
+        assert_eq!(alarm.check(at(10)), None);
+        assert_eq!(alarm.check(at(19)), None);
+        assert_eq!(alarm.check(at(25)), Some(0.0));
+        assert_eq!(alarm.check(at(26)), None);
+
+        // The rate recovers and falls again.
+        alarm.record(&working(400), at(30));
+        assert_eq!(alarm.check(at(30)), None);
+        assert_eq!(alarm.check(at(45)), Some(0.0));
+
+        // Pauses are not slowdowns.
+        alarm.record(&ProgressUpdate::new(1000, 400, State::Paused, None), at(46));
+        assert_eq!(alarm.check(at(100)), None);
+        alarm.record(&working(400), at(100));
+        assert_eq!(alarm.check(at(105)), None);
+    }
+}
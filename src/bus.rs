@@ -0,0 +1,196 @@
+use std::sync::{Arc, Mutex};
+
+use async_broadcast::{Sender, broadcast};
+use futures_core::Stream;
+
+use crate::{ProgressUpdate, ProgressUpdater, Transport, sync::lock, updater::detached};
+
+/// Number of updates buffered for each subscriber before the oldest are dropped.
+const CAPACITY: usize = 256;
+
+/// A tagged progress update published on a [`ProgressBus`].
+pub type TaggedUpdate = (Arc<str>, ProgressUpdate);
+
+/// A subscriber's pattern and the sending end of its channel.
+type Subscription = (String, Sender<TaggedUpdate>);
+
+/// A channel for progress updates that is independent of the futures reporting them.
+///
+/// In actor systems and services, the code that reports progress is often not the code
+/// that awaits completion. Producers publish updates under a tag, either directly with
+/// [`publish`](Self::publish) or through an [`updater`](Self::updater), and consumers
+/// subscribe to the tags matching a pattern. Patterns may contain `*`, which matches
+/// any sequence of characters, so `"backup/*"` matches `"backup/photos"`.
+///
+/// The bus is cheap to clone; all clones share the same subscribers. A subscriber that
+/// falls behind loses its oldest buffered updates.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::ProgressBus;
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let bus = ProgressBus::new();
+/// let mut backups = bus.subscribe("backup/*");
+///
+/// let mut updater = bus.updater("backup/photos", 100);
+/// tokio::spawn(async move {
+///     for i in 1..=100 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+///
+/// while let Some((tag, update)) = backups.next().await {
+///     println!("{tag}: {}/{}", update.current(), update.total());
+///     if update.is_completed() {
+///         break;
+///     }
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Default)]
+pub struct ProgressBus {
+    subscribers: Arc<Mutex<Vec<Subscription>>>,
+}
+
+impl ProgressBus {
+    /// Creates a bus without subscribers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publishes `update` under `tag` to every subscriber whose pattern matches it.
+    pub fn publish(&self, tag: &str, update: &ProgressUpdate) {
+        let tag: Arc<str> = Arc::from(tag);
+        lock(&self.subscribers).retain(|(pattern, sender)| {
+            if matches(pattern, &tag) {
+                let _ = sender.try_broadcast((tag.clone(), update.clone()));
+            }
+            sender.receiver_count() > 0
+        });
+    }
+
+    /// Returns an updater whose updates are published under `tag`.
+    ///
+    /// Like the updater of a [`progress`](crate::progress) task, it reports the operation
+    /// as cancelled when dropped without completing.
+    #[must_use]
+    pub fn updater(&self, tag: impl Into<String>, total: u64) -> ProgressUpdater {
+        let (updater, _) = detached(total);
+        let bus = self.clone();
+        let tag = tag.into();
+        updater.forward(move |update| {
+            bus.publish(&tag, update);
+            true
+        });
+        updater
+    }
+
+    /// Returns a [`Transport`] that publishes a task's updates under `tag`.
+    ///
+    /// Attach it with [`ProgressFuture::with_transport`](crate::ProgressFuture::with_transport)
+    /// to make an existing task visible on the bus.
+    #[must_use]
+    pub fn transport(&self, tag: impl Into<String>) -> impl Transport {
+        Publisher {
+            bus: self.clone(),
+            tag: tag.into(),
+        }
+    }
+
+    /// Returns a stream of the updates published under tags matching `pattern`.
+    pub fn subscribe(
+        &self,
+        pattern: impl Into<String>,
+    ) -> impl Stream<Item = TaggedUpdate> + Unpin + Send + 'static {
+        let (mut sender, receiver) = broadcast(CAPACITY);
+        sender.set_overflow(true);
+        lock(&self.subscribers).push((pattern.into(), sender));
+        receiver
+    }
+}
+
+/// Publishes the updates of a task on a bus under a fixed tag.
+struct Publisher {
+    bus: ProgressBus,
+    tag: String,
+}
+
+impl Transport for Publisher {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        self.bus.publish(&self.tag, update);
+        true
+    }
+}
+
+/// Returns `true` if `tag` matches `pattern`, where `*` matches any sequence of characters.
+fn matches(pattern: &str, tag: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return true;
+    };
+    let Some(mut rest) = tag.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn test_pattern_matching() {
+        assert!(matches("backup/photos", "backup/photos"));
+        assert!(!matches("backup/photos", "backup/photos2"));
+        assert!(matches("backup/*", "backup/photos"));
+        assert!(matches("*", "anything"));
+        assert!(matches("*/photos", "backup/photos"));
+        assert!(matches("a*c*e", "abcde"));
+        assert!(!matches("a*c*e", "abcdef"));
+        assert!(!matches("backup/*", "restore/photos"));
+    }
+
+    #[tokio::test]
+    async fn test_bus_routes_by_tag() {
+        let bus = ProgressBus::new();
+        let backups = bus.subscribe("backup/*");
+        let everything = bus.subscribe("*");
+
+        let mut photos = bus.updater("backup/photos", 2);
+        photos.update(1);
+        photos.complete();
+        drop(photos);
+        bus.publish(
+            "sync",
+            &ProgressUpdate::new(1, 1, crate::State::Completed, None),
+        );
+        drop(bus);
+
+        let backups: Vec<_> = backups
+            .map(|(tag, update)| (tag, update.current()))
+            .collect()
+            .await;
+        assert_eq!(
+            backups,
+            [("backup/photos".into(), 1), ("backup/photos".into(), 1)]
+        );
+        assert_eq!(everything.count().await, 3);
+    }
+}
@@ -0,0 +1,211 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::sync::{Arc, Mutex};
+
+use async_broadcast::{Receiver, Sender};
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::{ProgressUpdate, sync::lock};
+
+/// A progress update that only moved the current value.
+///
+/// Sent in place of a full [`ProgressUpdate`] on streams from
+/// [`ProgressFuture::progress_compact`](crate::ProgressFuture::progress_compact) when
+/// nothing else changed since the last full update.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CounterUpdate {
+    current: u64,
+    sequence: u64,
+}
+
+impl CounterUpdate {
+    /// Returns the current progress value.
+    #[must_use]
+    pub const fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Returns the sequence number of the update, see [`ProgressUpdate::sequence`].
+    #[must_use]
+    pub const fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Applies this counter to the last full update, returning the update it stands for.
+    #[must_use]
+    pub fn merge(&self, metadata: &ProgressUpdate) -> ProgressUpdate {
+        let mut update = metadata.clone().with_sequence(self.sequence);
+        update.current = self.current;
+        update
+    }
+}
+
+/// An item of a compact progress stream.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CompactUpdate {
+    /// An update that changed more than the current value. Its metadata, such as the
+    /// total, state and message, applies to the counters that follow.
+    Full(Arc<ProgressUpdate>),
+    /// An update that only moved the current value.
+    Counter(CounterUpdate),
+}
+
+/// Returns `true` if the updates differ at most in their current value and sequence.
+fn same_metadata(update: &ProgressUpdate, other: &ProgressUpdate) -> bool {
+    // Destructured so that new fields cannot be forgotten here.
+    let ProgressUpdate {
+        current: _,
+        sequence: _,
+        total,
+        state,
+        message,
+        priority,
+        stable_fraction,
+        outcomes,
+        extra_state,
+        keyed_message,
+        speed,
+        cancel_reason,
+        active_items,
+        ratio,
+        secondary,
+        estimated_total,
+        unit_cost,
+        event,
+        last_progress_age,
+        queue_wait,
+        // Updates from different call sites still describe the same metadata.
+        callsite: _callsite,
+    } = update;
+    *total == other.total
+        && *state == other.state
+        && *message == other.message
+        && *priority == other.priority
+        && *stable_fraction == other.stable_fraction
+        && *outcomes == other.outcomes
+        && *extra_state == other.extra_state
+        && *keyed_message == other.keyed_message
+        && *speed == other.speed
+        && *cancel_reason == other.cancel_reason
+        && *active_items == other.active_items
+        && *ratio == other.ratio
+        && *secondary == other.secondary
+        && *estimated_total == other.estimated_total
+        && *unit_cost == other.unit_cost
+        && *event == other.event
+        && *last_progress_age == other.last_progress_age
+        && *queue_wait == other.queue_wait
+}
+
+/// Returns a forward that sends updates to `sender`, as counters where possible.
+pub fn forward(
+    sender: Sender<CompactUpdate>,
+) -> impl Fn(&ProgressUpdate) -> bool + Send + Sync + 'static {
+    let last = Mutex::new(None::<Arc<ProgressUpdate>>);
+    move |update| {
+        let mut last = lock(&last);
+        let message = match &*last {
+            Some(metadata) if same_metadata(update, metadata) => {
+                CompactUpdate::Counter(CounterUpdate {
+                    current: update.current,
+                    sequence: update.sequence,
+                })
+            }
+            _ => {
+                let full = Arc::new(update.clone());
+                *last = Some(full.clone());
+                CompactUpdate::Full(full)
+            }
+        };
+        if sender.try_broadcast(message).is_err() {
+            // The subscriber may have missed new metadata: resend it with the next update.
+            *last = None;
+        }
+        drop(last);
+        sender.receiver_count() > 0
+    }
+}
+
+/// A stream of compact progress updates.
+///
+/// Created by [`ProgressFuture::progress_compact`](crate::ProgressFuture::progress_compact).
+/// Use [`expand`](Self::expand) to turn it back into full updates.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct CompactProgress {
+    receiver: Receiver<CompactUpdate>,
+}
+
+impl CompactProgress {
+    pub(crate) const fn new(receiver: Receiver<CompactUpdate>) -> Self {
+        Self { receiver }
+    }
+
+    /// Merges every counter with the metadata of the last full update.
+    pub fn expand(self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.scan(None::<Arc<ProgressUpdate>>, |metadata, item| {
+            let update = match item {
+                CompactUpdate::Full(full) => {
+                    let update = (*full).clone();
+                    *metadata = Some(full);
+                    Some(update)
+                }
+                CompactUpdate::Counter(counter) => metadata.as_deref().map(|m| counter.merge(m)),
+            };
+            core::future::ready(Some(update))
+        })
+        .filter_map(core::future::ready)
+    }
+}
+
+impl Stream for CompactProgress {
+    type Item = CompactUpdate;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress;
+
+    #[tokio::test]
+    async fn test_counters_between_full_updates() {
+        let task = progress(10, |mut updater| async move {
+            updater.update_with_message(1, "start");
+            updater.update_with_message(2, "start");
+            updater.update_with_message(3, "start");
+            updater.set_total(20);
+            updater.complete();
+        });
+        let compact = task.progress_compact();
+        let expanded = task.progress_compact().expand();
+        task.await;
+
+        let items: Vec<_> = compact.collect().await;
+        assert!(matches!(items[0], CompactUpdate::Full(_)));
+        assert_eq!(
+            items[1],
+            CompactUpdate::Counter(CounterUpdate {
+                current: 2,
+                sequence: 1
+            })
+        );
+        assert!(matches!(items[2], CompactUpdate::Counter(_)));
+        assert!(matches!(items[3], CompactUpdate::Full(_)));
+
+        let updates: Vec<_> = expanded.collect().await;
+        assert_eq!(updates.len(), 5);
+        assert_eq!(updates[2].current(), 3);
+        assert_eq!(updates[2].message(), Some("start"));
+        assert_eq!(updates[3].total(), 20);
+    }
+}
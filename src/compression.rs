@@ -0,0 +1,203 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+use std::{
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures_io::{AsyncBufRead, AsyncRead};
+use pin_project_lite::pin_project;
+
+use crate::ProgressUpdater;
+
+pin_project! {
+    /// A reader that counts the bytes consumed from it.
+    ///
+    /// [`CompressionProgress`] hands this to the encoder or decoder it wraps, so it can
+    /// report how much of the input has been processed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[derive(Debug)]
+    pub struct CountingReader<R> {
+        #[pin]
+        inner: R,
+        consumed: Arc<AtomicU64>,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for CountingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let read = ready!(this.inner.poll_read(cx, buf))?;
+        this.consumed.fetch_add(read as u64, Ordering::Relaxed);
+        Poll::Ready(Ok(read))
+    }
+}
+
+impl<R: AsyncBufRead> AsyncBufRead for CountingReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.project().inner.poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.project();
+        this.consumed.fetch_add(amt as u64, Ordering::Relaxed);
+        this.inner.consume(amt);
+    }
+}
+
+pin_project! {
+    /// Reports the progress of an encoder or decoder from the `async-compression` crate.
+    ///
+    /// The current value is the number of input bytes consumed, against the total of the
+    /// updater, which should be the size of the input. Every update also carries the
+    /// [`ratio`](crate::ProgressUpdate::ratio) of output bytes produced to input bytes
+    /// consumed. Reading to the end completes the progress.
+    ///
+    /// Any [`AsyncRead`] built on top of a [`CountingReader`] works, so this is not tied to
+    /// a particular codec or to `async-compression`'s features.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use async_compression::futures::bufread::GzipEncoder;
+    /// use futures_util::{AsyncReadExt, io::Cursor};
+    /// use progressor::{CompressionProgress, ProgressExt, progress};
+    ///
+    /// # async fn example() {
+    /// let input = vec![b'a'; 1 << 20];
+    /// let compressed = progress(input.len() as u64, |updater| async move {
+    ///     let mut gzip = CompressionProgress::new(updater, Cursor::new(input), GzipEncoder::new);
+    ///     let mut compressed = Vec::new();
+    ///     gzip.read_to_end(&mut compressed).await.map(|_| compressed)
+    /// })
+    /// .observe(|update| {
+    ///     println!(
+    ///         "{:.0}% compressed, ratio {:.2}",
+    ///         update.completed_fraction() * 100.0,
+    ///         update.ratio().unwrap_or(1.0)
+    ///     );
+    /// })
+    /// .await;
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[derive(Debug)]
+    pub struct CompressionProgress<C> {
+        #[pin]
+        codec: C,
+        consumed: Arc<AtomicU64>,
+        produced: u64,
+        updater: ProgressUpdater,
+    }
+}
+
+impl<C> CompressionProgress<C> {
+    /// Wraps the codec that `codec` builds on top of `input`.
+    ///
+    /// The total of `updater` should be the size of `input` in bytes.
+    pub fn new<R>(
+        updater: ProgressUpdater,
+        input: R,
+        codec: impl FnOnce(CountingReader<R>) -> C,
+    ) -> Self {
+        let consumed = Arc::new(AtomicU64::new(0));
+        let reader = CountingReader {
+            inner: input,
+            consumed: consumed.clone(),
+        };
+        Self {
+            codec: codec(reader),
+            consumed,
+            produced: 0,
+            updater,
+        }
+    }
+
+    /// Returns the number of input bytes consumed so far.
+    #[must_use]
+    pub fn consumed(&self) -> u64 {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of output bytes produced so far.
+    #[must_use]
+    pub const fn produced(&self) -> u64 {
+        self.produced
+    }
+}
+
+impl<C: AsyncRead> AsyncRead for CompressionProgress<C> {
+    #[allow(clippy::cast_precision_loss)]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let read = ready!(this.codec.poll_read(cx, buf))?;
+        if read == 0 {
+            this.updater.complete();
+        } else {
+            *this.produced += read as u64;
+            let consumed = this.consumed.load(Ordering::Relaxed);
+            let total = this.updater.total();
+            let produced = *this.produced;
+            this.updater.report(total, consumed, |update| {
+                if consumed == 0 {
+                    update
+                } else {
+                    update.with_ratio(produced as f64 / consumed as f64)
+                }
+            });
+        }
+        Poll::Ready(Ok(read))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+    use async_compression::futures::bufread::{GzipDecoder, GzipEncoder};
+    use futures_util::{AsyncReadExt, StreamExt, io::Cursor};
+
+    #[tokio::test]
+    async fn test_gzip_round_trip() {
+        let input = vec![7u8; 64 * 1024];
+        let len = input.len() as u64;
+        let task = progress(len, |updater| async move {
+            let mut gzip = CompressionProgress::new(updater, Cursor::new(input), GzipEncoder::new);
+            let mut compressed = Vec::new();
+            gzip.read_to_end(&mut compressed).await.unwrap();
+            assert_eq!(gzip.consumed(), len);
+            assert_eq!(gzip.produced(), compressed.len() as u64);
+            compressed
+        });
+        let updates = task.progress();
+        let compressed = task.await;
+
+        let updates: Vec<_> = updates.collect().await;
+        let (last, working) = updates.split_last().unwrap();
+        assert!(last.is_completed());
+        assert_eq!(last.current(), len);
+        assert!(
+            working
+                .iter()
+                .all(|update| update.ratio().is_some_and(|r| r < 1.0))
+        );
+
+        let mut gunzip = GzipDecoder::new(Cursor::new(compressed));
+        let mut output = Vec::new();
+        gunzip.read_to_end(&mut output).await.unwrap();
+        assert_eq!(output.len() as u64, len);
+    }
+}
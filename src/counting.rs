@@ -0,0 +1,153 @@
+use core::fmt::Write;
+
+use crate::{ProgressUpdate, ProgressUpdater};
+
+/// A progress updater for units that finish out of order, like crates in a build.
+///
+/// Build tools start many units of varying size in parallel, and they finish in no
+/// particular order. This updater counts started and finished units: the number finished
+/// is reported as the current value, and the labels of the units in flight are carried
+/// as [`ProgressUpdate::active_items`]. The `verb` passed to [`new`](Self::new) is
+/// reported as the [extra state](ProgressUpdate::extra_state).
+///
+/// Observers can format updates as `Building [45/120]: foo, bar, baz` with
+/// [`render`](Self::render).
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{CountingUpdater, ProgressExt, progress};
+///
+/// # async fn example() {
+/// progress(3, |updater| async move {
+///     let mut build = CountingUpdater::new(updater, "Building");
+///     build.start("serde");
+///     build.start("tokio");
+///     build.finish("tokio");
+///     build.start("progressor");
+///     build.finish("serde");
+///     build.finish("progressor");
+///     build.complete();
+/// })
+/// .observe(|update| println!("{}", CountingUpdater::render(&update)))
+/// .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct CountingUpdater {
+    updater: ProgressUpdater,
+}
+
+impl CountingUpdater {
+    /// Wraps an updater whose total is the number of units, describing the work as `verb`.
+    #[must_use]
+    pub fn new(updater: ProgressUpdater, verb: &'static str) -> Self {
+        updater.set_extra_state_silently(Some(verb));
+        Self { updater }
+    }
+
+    /// Records that the unit labelled `item` has started.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn start(&self, item: impl Into<String>) {
+        self.updater.item_started(item);
+    }
+
+    /// Records that the unit labelled `item` has finished.
+    ///
+    /// A label that was never started still counts as a finished unit.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn finish(&mut self, item: &str) {
+        self.updater.item_finished_silently(item);
+        let total = self.updater.total();
+        let finished = self.finished().saturating_add(1);
+        self.updater.report(total, finished, |update| update);
+    }
+
+    /// Returns the number of units started so far, including finished ones.
+    #[must_use]
+    pub fn started(&self) -> u64 {
+        self.finished()
+            .saturating_add(self.updater.active_items().len() as u64)
+    }
+
+    /// Returns the number of units finished so far.
+    #[must_use]
+    pub fn finished(&self) -> u64 {
+        self.updater.current()
+    }
+
+    /// Returns the labels of the units in flight, in the order they started.
+    #[must_use]
+    pub fn active(&self) -> Vec<String> {
+        self.updater.active_items()
+    }
+
+    /// Marks the operation as completed.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn complete(&mut self) {
+        self.updater.complete();
+    }
+
+    /// Returns the wrapped updater.
+    #[must_use]
+    pub fn into_inner(self) -> ProgressUpdater {
+        self.updater
+    }
+
+    /// Formats an update as `Building [45/120]: foo, bar, baz`.
+    ///
+    /// The verb is the update's extra state, `Working` if it has none. The list of
+    /// active items is left out when nothing is in flight.
+    #[must_use]
+    pub fn render(update: &ProgressUpdate) -> String {
+        let mut line = format!(
+            "{} [{}/{}]",
+            update.extra_state().unwrap_or("Working"),
+            update.current(),
+            update.total()
+        );
+        if !update.active_items().is_empty() {
+            let _ = write!(line, ": {}", update.active_items().join(", "));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_out_of_order_units() {
+        let task = progress(3, |updater| async move {
+            let mut build = CountingUpdater::new(updater, "Building");
+            build.start("a");
+            build.start("b");
+            build.start("c");
+            build.finish("b");
+            assert_eq!((build.started(), build.finished()), (3, 1));
+            build.finish("a");
+        });
+        let updates = task.progress();
+        task.await;
+
+        let lines: Vec<_> = updates
+            .map(|update| CountingUpdater::render(&update))
+            .collect()
+            .await;
+        assert_eq!(
+            lines,
+            [
+                "Building [0/3]: a",
+                "Building [0/3]: a, b",
+                "Building [0/3]: a, b, c",
+                "Building [1/3]: a, c",
+                "Building [2/3]: c",
+                "Building [2/3]: c",
+            ]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use futures_core::Stream;
+use futures_util::{StreamExt, select};
+use zbus::{Connection, zvariant::Value};
+
+use crate::{CancelReason, ProgressController, ProgressUpdate, State, stream::percent};
+
+/// The interface desktop shells listen on for application progress.
+const INTERFACE: &str = "com.canonical.Unity.LauncherEntry";
+
+/// The unit of the amounts a [`JobView`] reports.
+const UNIT: &str = "items";
+
+/// KDE's `KJob::Killable | KJob::Suspendable`: the job can be cancelled and suspended.
+const CAPABILITIES: i32 = 0x0001 | 0x0002;
+
+/// KDE's `KJob::KilledJobError`, reported for cancelled tasks.
+const KILLED_JOB_ERROR: u32 = 1;
+
+/// KDE's `KJob::UserDefinedError`, reported for failed tasks.
+const USER_DEFINED_ERROR: u32 = 100;
+
+#[zbus::proxy(
+    interface = "org.kde.JobViewServer",
+    default_service = "org.kde.kuiserver",
+    default_path = "/JobViewServer"
+)]
+trait JobViewServer {
+    #[zbus(name = "requestView")]
+    fn request_view(
+        &self,
+        app_name: &str,
+        app_icon_name: &str,
+        capabilities: i32,
+    ) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+}
+
+#[zbus::proxy(interface = "org.kde.JobViewV2", default_service = "org.kde.kuiserver")]
+trait JobViewV2 {
+    fn terminate(&self, error_message: &str) -> zbus::Result<()>;
+
+    #[zbus(name = "setSuspended")]
+    fn set_suspended(&self, suspended: bool) -> zbus::Result<()>;
+
+    #[zbus(name = "setTotalAmount")]
+    fn set_total_amount(&self, amount: u64, unit: &str) -> zbus::Result<()>;
+
+    #[zbus(name = "setProcessedAmount")]
+    fn set_processed_amount(&self, amount: u64, unit: &str) -> zbus::Result<()>;
+
+    #[zbus(name = "setPercent")]
+    fn set_percent(&self, percent: u32) -> zbus::Result<()>;
+
+    #[zbus(name = "setInfoMessage")]
+    fn set_info_message(&self, message: &str) -> zbus::Result<()>;
+
+    #[zbus(name = "setError")]
+    fn set_error(&self, error_code: u32) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "cancelRequested")]
+    fn cancel_requested(&self) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "suspendRequested")]
+    fn suspend_requested(&self) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "resumeRequested")]
+    fn resume_requested(&self) -> zbus::Result<()>;
+}
+
+/// Shows a task in the job tracker of KDE Plasma, next to file copies and downloads.
+///
+/// The view is requested from the `org.kde.JobViewServer` at `/JobViewServer` of the
+/// `org.kde.kuiserver` service, which Plasma provides, and is then driven through its
+/// `org.kde.JobViewV2` interface: the percentage, processed and total amounts and the
+/// message follow the task's updates, and a paused task is shown suspended. The cancel,
+/// suspend and resume buttons of the view reach the task through its controller. Once
+/// the task finishes, the view is terminated, with the error message if it failed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use progressor::{JobView, Progress, progress};
+///
+/// # async fn example() -> zbus::Result<()> {
+/// let connection = zbus::Connection::session().await?;
+/// let copy = progress(100, |mut updater| async move {
+///     for i in 1..=100 {
+///         updater.checkpoint().await;
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+///
+/// let job = JobView::new(&connection, "Files", "system-file-manager", copy.controller()).await?;
+/// let updates = copy.progress();
+/// let (_, reported) = futures_util::join!(copy, job.report(updates));
+/// reported?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "zbus")))]
+#[derive(Debug, Clone)]
+pub struct JobView {
+    view: JobViewV2Proxy<'static>,
+    controller: ProgressController,
+}
+
+impl JobView {
+    /// Requests a view for a job of the application `app_name`, shown with the icon
+    /// `icon_name`, controlling the task through `controller`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error if no job view server runs on the bus or it refused the view.
+    pub async fn new(
+        connection: &Connection,
+        app_name: &str,
+        icon_name: &str,
+        controller: ProgressController,
+    ) -> zbus::Result<Self> {
+        let server = JobViewServerProxy::new(connection).await?;
+        let path = server
+            .request_view(app_name, icon_name, CAPABILITIES)
+            .await?;
+        let view = JobViewV2Proxy::builder(connection)
+            .path(path)?
+            .build()
+            .await?;
+        Ok(Self { view, controller })
+    }
+
+    /// Returns the object path of the view on the job view server.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        self.view.inner().path().as_str()
+    }
+
+    /// Reports `updates` until the stream ends or the task finishes, then terminates
+    /// the view.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error if the view could not be updated or terminated.
+    pub async fn report(&self, updates: impl Stream<Item = ProgressUpdate>) -> zbus::Result<()> {
+        let mut cancels = self.view.receive_cancel_requested().await?.fuse();
+        let mut suspends = self.view.receive_suspend_requested().await?.fuse();
+        let mut resumes = self.view.receive_resume_requested().await?.fuse();
+        let mut updates = core::pin::pin!(updates.fuse());
+        let mut shown = None;
+        let last = loop {
+            select! {
+                update = updates.next() => {
+                    let Some(update) = update else {
+                        break None;
+                    };
+                    let next = status(&update);
+                    self.show(shown.as_ref(), &next).await?;
+                    shown = Some(next);
+                    if update.is_terminal() {
+                        break Some(update);
+                    }
+                }
+                _ = cancels.next() => self.controller.cancel(CancelReason::UserRequest),
+                _ = suspends.next() => self.controller.pause(),
+                _ = resumes.next() => self.controller.resume(),
+            }
+        };
+
+        // The stream may have dropped the final update, or the task went away without
+        // one; the controller knows how it ended.
+        let state = self.controller.final_state().unwrap_or(State::Cancelled);
+        match state {
+            State::Failed => {
+                let message = last
+                    .as_ref()
+                    .filter(|update| update.is_failed())
+                    .and_then(ProgressUpdate::message)
+                    .unwrap_or("failed");
+                self.view.set_error(USER_DEFINED_ERROR).await?;
+                self.view.terminate(message).await
+            }
+            State::Cancelled => {
+                self.view.set_error(KILLED_JOB_ERROR).await?;
+                self.view.terminate("").await
+            }
+            _ => self.view.terminate("").await,
+        }
+    }
+
+    /// Sends what changed between `shown` and `next`, or all of `next` at first.
+    async fn show(&self, shown: Option<&Status>, next: &Status) -> zbus::Result<()> {
+        let previous = shown.cloned().unwrap_or_default();
+        let first = shown.is_none();
+        if first || previous.suspended != next.suspended {
+            self.view.set_suspended(next.suspended).await?;
+        }
+        if first || previous.total != next.total {
+            self.view.set_total_amount(next.total, UNIT).await?;
+        }
+        if first || previous.processed != next.processed {
+            self.view.set_processed_amount(next.processed, UNIT).await?;
+        }
+        if first || previous.percent != next.percent {
+            self.view.set_percent(u32::from(next.percent)).await?;
+        }
+        if first || previous.message != next.message {
+            self.view.set_info_message(&next.message).await?;
+        }
+        Ok(())
+    }
+}
+
+/// What a job view shows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Status {
+    suspended: bool,
+    percent: u8,
+    processed: u64,
+    total: u64,
+    message: String,
+}
+
+fn status(update: &ProgressUpdate) -> Status {
+    Status {
+        suspended: update.is_paused(),
+        percent: percent(update),
+        processed: update.current(),
+        total: update.total(),
+        message: update.message().unwrap_or_default().to_owned(),
+    }
+}
+
+/// Shows a task's progress on an application's launcher icon on Linux desktops.
+///
+/// Progress is reported with the `com.canonical.Unity.LauncherEntry` D-Bus signal,
+/// which GNOME docks, KDE Plasma task managers and other shells render as a progress
+/// bar on the icon of the application with the given desktop file. Signals are only
+/// sent when the displayed percentage or the state changes; the bar is hidden once the
+/// task completes, is cancelled or fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// use progressor::{LauncherEntry, Progress, progress};
+///
+/// # async fn example() -> zbus::Result<()> {
+/// let connection = zbus::Connection::session().await?;
+/// let copy = progress(100, |mut updater| async move {
+///     for i in 1..=100 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+///
+/// let entry = LauncherEntry::new(connection, "org.example.Files");
+/// let updates = copy.progress();
+/// let (_, reported) = futures_util::join!(copy, entry.report(updates));
+/// reported?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "zbus")))]
+#[derive(Debug, Clone)]
+pub struct LauncherEntry {
+    connection: Connection,
+    app_uri: String,
+    path: String,
+}
+
+impl LauncherEntry {
+    /// Creates an entry for the application whose desktop file is `desktop_id.desktop`.
+    #[must_use]
+    pub fn new(connection: Connection, desktop_id: &str) -> Self {
+        let object: String = desktop_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        Self {
+            connection,
+            app_uri: format!("application://{desktop_id}.desktop"),
+            path: format!("/com/canonical/unity/launcherentry/{object}"),
+        }
+    }
+
+    /// Reports `updates` until the stream ends or the task finishes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error if a signal could not be sent.
+    pub async fn report(&self, updates: impl Stream<Item = ProgressUpdate>) -> zbus::Result<()> {
+        let mut updates = core::pin::pin!(updates);
+        let mut shown = None;
+        while let Some(update) = updates.next().await {
+            let finished = update.is_terminal();
+            let properties = properties(&update);
+            if shown.as_ref() != Some(&properties) {
+                self.send(&properties).await?;
+                shown = Some(properties);
+            }
+            if finished {
+                return Ok(());
+            }
+        }
+        // The task went away without a final update: hide the bar.
+        if shown.is_some() {
+            self.send(&Properties {
+                percent: 0,
+                visible: false,
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn send(&self, properties: &Properties) -> zbus::Result<()> {
+        let values = HashMap::from([
+            (
+                "progress",
+                Value::from(f64::from(properties.percent) / 100.0),
+            ),
+            ("progress-visible", Value::from(properties.visible)),
+        ]);
+        self.connection
+            .emit_signal(
+                None::<()>,
+                self.path.as_str(),
+                INTERFACE,
+                "Update",
+                &(self.app_uri.as_str(), values),
+            )
+            .await
+    }
+}
+
+/// What the launcher icon shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Properties {
+    percent: u8,
+    visible: bool,
+}
+
+fn properties(update: &ProgressUpdate) -> Properties {
+    Properties {
+        percent: percent(update),
+        visible: !update.is_terminal(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties() {
+        let working = ProgressUpdate::new(200, 101, State::Working, None);
+        assert_eq!(
+            properties(&working),
+            Properties {
+                percent: 50,
+                visible: true
+            }
+        );
+        let overshot = ProgressUpdate::new(200, 300, State::Working, None);
+        assert_eq!(properties(&overshot).percent, 100);
+        let done = ProgressUpdate::new(200, 200, State::Completed, None);
+        assert!(!properties(&done).visible);
+    }
+
+    #[test]
+    fn test_status() {
+        let paused = ProgressUpdate::new(200, 50, State::Paused, Some("Copying a.txt".into()));
+        assert_eq!(
+            status(&paused),
+            Status {
+                suspended: true,
+                percent: 25,
+                processed: 50,
+                total: 200,
+                message: "Copying a.txt".into(),
+            }
+        );
+        let working = ProgressUpdate::new(200, 50, State::Working, None);
+        assert!(!status(&working).suspended);
+    }
+}
@@ -0,0 +1,131 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_util::{future::Either, stream};
+
+use crate::{Progress, ProgressController, ProgressUpdate, TransitionLog};
+
+/// The work of a [`Detached`] task, handed to the spawner when the task is dropped.
+pub type DetachedWork = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+type Spawn = Box<dyn FnOnce(DetachedWork) + Send>;
+
+/// A task that keeps running when dropped, created by
+/// [`ProgressExt::detach`](crate::ProgressExt::detach).
+///
+/// Awaiting it behaves like awaiting the task. Dropping it before the task finished
+/// hands the remaining work to the spawner instead of cancelling it, and the task keeps
+/// reporting to the progress streams observers already hold.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct Detached<P: Progress + Send + 'static> {
+    task: Option<Pin<Box<P>>>,
+    spawn: Option<Spawn>,
+}
+
+impl<P: Progress + Send + 'static> core::fmt::Debug for Detached<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Detached")
+            .field("finished", &self.task.is_none())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: Progress + Send + 'static> Detached<P> {
+    pub(crate) fn new(task: P, spawn: impl FnOnce(DetachedWork) + Send + 'static) -> Self {
+        Self {
+            task: Some(Box::pin(task)),
+            spawn: Some(Box::new(spawn)),
+        }
+    }
+}
+
+impl<P: Progress + Send + 'static> Future for Detached<P> {
+    type Output = P::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let task = self
+            .task
+            .as_mut()
+            .expect("`Detached` polled after completion");
+        let output = task.as_mut().poll(cx);
+        if output.is_ready() {
+            self.task = None;
+        }
+        output
+    }
+}
+
+impl<P: Progress + Send + 'static> Progress for Detached<P> {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.task.as_ref().map_or_else(
+            || Either::Right(stream::empty()),
+            |task| Either::Left(task.progress()),
+        )
+    }
+
+    fn transitions(&self) -> TransitionLog {
+        self.task
+            .as_ref()
+            .map_or_else(TransitionLog::default, Progress::transitions)
+    }
+
+    fn controller(&self) -> Option<ProgressController> {
+        self.task.as_ref().and_then(Progress::controller)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&self) -> crate::Diagnostics {
+        self.task
+            .as_ref()
+            .map_or_else(crate::Diagnostics::default, Progress::diagnostics)
+    }
+}
+
+impl<P: Progress + Send + 'static> Drop for Detached<P> {
+    fn drop(&mut self) {
+        if let (Some(task), Some(spawn)) = (self.task.take(), self.spawn.take()) {
+            spawn(Box::pin(async move {
+                task.await;
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{FutureExt, StreamExt};
+
+    use crate::{Progress, ProgressExt, progress};
+
+    #[tokio::test]
+    async fn test_dropped_task_keeps_running() {
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            tokio::task::yield_now().await;
+            updater.update(2);
+            updater.complete();
+        })
+        .detach(|work| {
+            tokio::spawn(work);
+        });
+        let mut task = task;
+        let updates = task.progress();
+        // Start the work, then drop it while it is suspended.
+        assert!((&mut task).now_or_never().is_none());
+        drop(task);
+
+        let updates: Vec<_> = updates.collect().await;
+        assert_eq!(updates.len(), 3);
+        assert!(updates[2].is_completed());
+    }
+
+    #[tokio::test]
+    async fn test_awaited_task_is_not_spawned() {
+        let task = progress(1, |_| async { 7 }).detach(|_| panic!("should not spawn"));
+        assert_eq!(task.await, 7);
+    }
+}
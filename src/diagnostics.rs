@@ -0,0 +1,243 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_broadcast::{Sender, TrySendError};
+
+use crate::{ProgressUpdate, State, sync::lock};
+
+/// The number of events a [`Diagnostics`] handle retains.
+const EVENT_CAPACITY: usize = 64;
+
+/// What went wrong while delivering an update, see [`DiagnosticEvent`].
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// The task's channel was full, so no [`progress`](crate::Progress::progress) stream
+    /// received the update.
+    Dropped,
+    /// The update filled the task's channel; the next one is dropped unless a stream
+    /// catches up first.
+    BufferFull,
+    /// A subscriber fell behind, so its oldest buffered update was discarded to make room
+    /// for this one.
+    SlowSubscriber,
+}
+
+/// A delivery problem recorded by [`Diagnostics`].
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticEvent {
+    kind: DiagnosticKind,
+    current: u64,
+    total: u64,
+    state: State,
+    at: Instant,
+}
+
+impl DiagnosticEvent {
+    /// Returns what went wrong.
+    #[must_use]
+    pub const fn kind(&self) -> DiagnosticKind {
+        self.kind
+    }
+
+    /// Returns the current value of the update concerned.
+    #[must_use]
+    pub const fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Returns the total of the update concerned.
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the state of the update concerned.
+    #[must_use]
+    pub const fn state(&self) -> State {
+        self.state
+    }
+
+    /// Returns when the problem occurred.
+    #[must_use]
+    pub const fn at(&self) -> Instant {
+        self.at
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    dropped: u64,
+    full_buffers: u64,
+    slow_subscribers: u64,
+    events: VecDeque<DiagnosticEvent>,
+}
+
+/// Counts the updates of a task that did not reach its observers, and why.
+///
+/// Updates are delivered through bounded buffers, so a stream that is not polled often
+/// enough misses updates: a progress bar that froze at 97% usually never received the
+/// rest. The handle is shared with the task and obtained from
+/// [`Progress::diagnostics`](crate::Progress::diagnostics); it counts updates dropped on
+/// the task's channel, the times that channel filled up, and subscribers that fell
+/// behind, and keeps the most recent of these events with the update concerned.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{DiagnosticKind, Progress, progress};
+///
+/// # async fn example() {
+/// let task = progress(100, |mut updater| async move {
+///     for i in 1..=100 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+/// let diagnostics = task.diagnostics();
+/// let bar = task.progress();
+/// task.await;
+///
+/// for event in diagnostics.events() {
+///     if event.kind() == DiagnosticKind::Dropped {
+///         println!("lost update {}/{} ({:?})", event.current(), event.total(), event.state());
+///     }
+/// }
+/// println!("{} updates dropped", diagnostics.dropped());
+/// # drop(bar);
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    counters: Arc<Mutex<Counters>>,
+}
+
+impl Diagnostics {
+    /// Returns the number of updates no [`progress`](crate::Progress::progress) stream
+    /// received because the task's channel was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+
+    /// Returns the number of times the task's channel filled up.
+    #[must_use]
+    pub fn full_buffers(&self) -> u64 {
+        self.lock().full_buffers
+    }
+
+    /// Returns the number of updates discarded because a subscriber fell behind.
+    #[must_use]
+    pub fn slow_subscribers(&self) -> u64 {
+        self.lock().slow_subscribers
+    }
+
+    /// Returns the most recent events, oldest first.
+    #[must_use]
+    pub fn events(&self) -> Vec<DiagnosticEvent> {
+        self.lock().events.iter().copied().collect()
+    }
+
+    /// Sends `update` on the task's channel, recording a drop or a full buffer.
+    pub(crate) fn send(&self, sender: &Sender<ProgressUpdate>, update: ProgressUpdate) {
+        let (current, total, state) = (update.current(), update.total(), update.state());
+        match sender.try_broadcast(update) {
+            Err(TrySendError::Full(update)) => self.record(DiagnosticKind::Dropped, &update),
+            Ok(_) if sender.is_full() => {
+                self.push(DiagnosticKind::BufferFull, current, total, state);
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends `item` to a subscriber that drops its oldest updates when it falls behind.
+    pub(crate) fn send_to_subscriber<T: Clone>(
+        &self,
+        sender: &Sender<T>,
+        item: T,
+        update: &ProgressUpdate,
+    ) {
+        if let Ok(Some(_)) = sender.try_broadcast(item) {
+            self.record(DiagnosticKind::SlowSubscriber, update);
+        }
+    }
+
+    fn record(&self, kind: DiagnosticKind, update: &ProgressUpdate) {
+        self.push(kind, update.current(), update.total(), update.state());
+    }
+
+    fn push(&self, kind: DiagnosticKind, current: u64, total: u64, state: State) {
+        let mut counters = self.lock();
+        match kind {
+            DiagnosticKind::Dropped => counters.dropped += 1,
+            DiagnosticKind::BufferFull => counters.full_buffers += 1,
+            DiagnosticKind::SlowSubscriber => counters.slow_subscribers += 1,
+        }
+        if counters.events.len() == EVENT_CAPACITY {
+            counters.events.pop_front();
+        }
+        counters.events.push_back(DiagnosticEvent {
+            kind,
+            current,
+            total,
+            state,
+            at: Instant::now(),
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        lock(&self.counters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::{Progress, progress};
+
+    #[tokio::test]
+    async fn test_dropped_updates() {
+        let task = progress(40, |mut updater| async move {
+            for i in 1..=40 {
+                updater.update(i);
+            }
+            updater.complete();
+        });
+        let diagnostics = task.diagnostics();
+        let updates = task.progress();
+        task.await;
+
+        assert_eq!(updates.count().await, 32);
+        assert_eq!(diagnostics.full_buffers(), 1);
+        assert_eq!(diagnostics.dropped(), 9);
+        let events = diagnostics.events();
+        assert_eq!(events[0].kind(), DiagnosticKind::BufferFull);
+        assert_eq!(events[0].current(), 32);
+        let last = events.last().unwrap();
+        assert_eq!(last.kind(), DiagnosticKind::Dropped);
+        assert_eq!(last.state(), State::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber() {
+        let task = progress(40, |mut updater| async move {
+            for i in 1..=40 {
+                updater.update(i);
+            }
+        });
+        let diagnostics = task.diagnostics();
+        let updates = task.subscribe_filtered(|_| true);
+        task.await;
+
+        assert_eq!(updates.count().await, 32);
+        assert_eq!(diagnostics.slow_subscribers(), 9);
+        assert_eq!(diagnostics.dropped(), 0);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{ProgressUpdate, State};
+
+/// The changes between two progress updates.
+///
+/// Created by [`ProgressUpdate::diff`]. Renderers use it to redraw only what changed,
+/// and transports can send it instead of the full update.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UpdateDelta {
+    current: i64,
+    total: Option<u64>,
+    state: Option<State>,
+    message_changed: bool,
+    message: Option<String>,
+}
+
+impl UpdateDelta {
+    /// Returns how far the current value moved. Negative if it went backwards.
+    ///
+    /// The difference saturates at the bounds of `i64`.
+    #[must_use]
+    pub const fn current_delta(&self) -> i64 {
+        self.current
+    }
+
+    /// Returns the new total, if it changed.
+    #[must_use]
+    pub const fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Returns the new state, if it changed.
+    #[must_use]
+    pub const fn state(&self) -> Option<State> {
+        self.state
+    }
+
+    /// Returns `true` if the message changed, including when it was cleared.
+    #[must_use]
+    pub const fn message_changed(&self) -> bool {
+        self.message_changed
+    }
+
+    /// Returns the new message if it changed, or `None` if it is unchanged or cleared.
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns `true` if nothing changed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.current == 0 && self.total.is_none() && self.state.is_none() && !self.message_changed
+    }
+}
+
+impl ProgressUpdate {
+    /// Describes what changed from `previous` to this update.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{ProgressUpdate, State};
+    ///
+    /// let previous = ProgressUpdate::new(100, 40, State::Working, None);
+    /// let next = ProgressUpdate::new(100, 55, State::Paused, None);
+    ///
+    /// let delta = next.diff(&previous);
+    /// assert_eq!(delta.current_delta(), 15);
+    /// assert_eq!(delta.state(), Some(State::Paused));
+    /// assert_eq!(delta.total(), None);
+    /// assert!(!delta.message_changed());
+    /// ```
+    #[must_use]
+    pub fn diff(&self, previous: &Self) -> UpdateDelta {
+        let message_changed = self.message() != previous.message();
+        UpdateDelta {
+            current: signed_delta(previous.current(), self.current()),
+            total: (self.total() != previous.total()).then_some(self.total()),
+            state: (self.state() != previous.state()).then_some(self.state()),
+            message_changed,
+            message: message_changed
+                .then(|| self.message().map(ToString::to_string))
+                .flatten(),
+        }
+    }
+}
+
+/// Returns `to - from`, saturating at the bounds of `i64`.
+pub fn signed_delta(from: u64, to: u64) -> i64 {
+    if to >= from {
+        i64::try_from(to - from).unwrap_or(i64::MAX)
+    } else {
+        i64::try_from(from - to).map_or(i64::MIN, |delta| -delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_unchanged() {
+        let update = ProgressUpdate::new(10, 5, State::Working, Some("copying".to_string()));
+        assert!(update.diff(&update).is_empty());
+    }
+
+    #[test]
+    fn test_diff_changes() {
+        let previous = ProgressUpdate::new(10, 5, State::Working, Some("copying".to_string()));
+        let next = ProgressUpdate::new(20, 3, State::Working, None);
+
+        let delta = next.diff(&previous);
+        assert_eq!(delta.current_delta(), -2);
+        assert_eq!(delta.total(), Some(20));
+        assert_eq!(delta.state(), None);
+        assert!(delta.message_changed());
+        assert_eq!(delta.message(), None);
+        assert!(!delta.is_empty());
+    }
+
+    #[test]
+    fn test_diff_saturates() {
+        let previous = ProgressUpdate::new(u64::MAX, 0, State::Working, None);
+        let next = ProgressUpdate::new(u64::MAX, u64::MAX, State::Working, None);
+        assert_eq!(next.diff(&previous).current_delta(), i64::MAX);
+        assert_eq!(previous.diff(&next).current_delta(), i64::MIN);
+    }
+}
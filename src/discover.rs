@@ -0,0 +1,124 @@
+use crate::ProgressUpdater;
+
+/// Weight of the newest raw fraction in the smoothed fraction.
+const SMOOTHING: f64 = 0.1;
+
+/// A progress updater for work whose total is discovered while it runs.
+///
+/// Crawlers and scanners do not know up front how much work there is: every processed
+/// item may reveal new ones. This updater tracks the number of items known so far
+/// (reported as the total) and the number of items done (reported as the current value).
+///
+/// Because the total keeps growing, the raw fraction `done / known` jumps back whenever a
+/// batch of new work is found. Every update therefore also carries an exponentially
+/// smoothed fraction, available through [`ProgressUpdate::stable_fraction`], which is
+/// better suited for drawing a progress bar.
+///
+/// [`ProgressUpdate::stable_fraction`]: crate::ProgressUpdate::stable_fraction
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{DiscoveringUpdater, ProgressExt, progress};
+///
+/// # async fn example() {
+/// progress(0, |updater| async move {
+///     let mut crawler = DiscoveringUpdater::new(updater);
+///     let mut queue = vec!["/"];
+///     crawler.discover(1);
+///     while let Some(page) = queue.pop() {
+///         if page == "/" {
+///             queue.extend(["/about", "/blog"]);
+///             crawler.discover(2);
+///         }
+///         crawler.finish(1);
+///     }
+///     crawler.complete();
+/// })
+/// .observe(|update| {
+///     println!(
+///         "{} of {} pages crawled ({:.0}%)",
+///         update.current(),
+///         update.total(),
+///         update.stable_fraction() * 100.0
+///     );
+/// })
+/// .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct DiscoveringUpdater {
+    updater: ProgressUpdater,
+    stable_fraction: f64,
+}
+
+impl DiscoveringUpdater {
+    /// Wraps an updater, starting from its current total and progress.
+    #[must_use]
+    pub const fn new(updater: ProgressUpdater) -> Self {
+        Self {
+            updater,
+            stable_fraction: 0.0,
+        }
+    }
+
+    /// Records that `count` new items of work have been found.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn discover(&mut self, count: u64) {
+        let known = self.known().saturating_add(count);
+        self.report(known, self.done());
+    }
+
+    /// Records that `count` items of work have been finished.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn finish(&mut self, count: u64) {
+        let done = self.done().saturating_add(count);
+        self.report(self.known().max(done), done);
+    }
+
+    /// Returns the number of items found so far.
+    #[must_use]
+    pub fn known(&self) -> u64 {
+        self.updater.total()
+    }
+
+    /// Returns the number of items finished so far.
+    #[must_use]
+    pub fn done(&self) -> u64 {
+        self.updater.current()
+    }
+
+    /// Returns the smoothed completion fraction reported with the last update.
+    #[must_use]
+    pub const fn stable_fraction(&self) -> f64 {
+        self.stable_fraction
+    }
+
+    /// Marks the operation as completed.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn complete(&mut self) {
+        self.updater.complete();
+    }
+
+    /// Returns the wrapped updater.
+    #[must_use]
+    pub fn into_inner(self) -> ProgressUpdater {
+        self.updater
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    fn report(&mut self, known: u64, done: u64) {
+        let raw = if known == 0 {
+            0.0
+        } else {
+            done as f64 / known as f64
+        };
+        self.stable_fraction += (raw - self.stable_fraction) * SMOOTHING;
+        let stable_fraction = self.stable_fraction;
+        self.updater.report(known, done, |update| {
+            update.with_stable_fraction(stable_fraction)
+        });
+    }
+}
@@ -0,0 +1,201 @@
+use core::{future::Future, pin::pin};
+use std::time::{Duration, Instant};
+
+use futures_timer::Delay;
+use futures_util::{FutureExt, select};
+
+use crate::{DurationStore, ProgressFuture, progress};
+
+/// Resolution of the synthesized progress: updates report basis points.
+const TOTAL: u64 = 10_000;
+/// Fraction reported when the expected duration has elapsed.
+const AT_EXPECTED: f64 = 0.9;
+/// Fraction approached, but never reached, while the future keeps running.
+const CEILING: f64 = 0.99;
+
+/// Wraps a future that does not report progress and estimates it from elapsed time.
+///
+/// Many futures, especially from third-party crates, cannot be instrumented. This
+/// synthesizes progress by comparing the elapsed time against the `expected` duration:
+/// it advances linearly to 90% at the expected duration and then approaches 99%
+/// asymptotically, so a future that runs long never appears finished. The operation is
+/// reported as completed once the wrapped future resolves.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{ProgressExt, estimate};
+///
+/// # async fn example() {
+/// let body = estimate(Duration::from_secs(5), async {
+///     // A request from a client that offers no progress reporting.
+///     tokio::time::sleep(Duration::from_secs(4)).await;
+///     "response"
+/// })
+/// .observe(|update| println!("{:.0}%", update.completed_fraction() * 100.0))
+/// .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn estimate<Fut>(
+    expected: Duration,
+    fut: Fut,
+) -> ProgressFuture<impl Future<Output = Fut::Output>>
+where
+    Fut: Future,
+{
+    track(expected, fut, |_| {})
+}
+
+/// Like [`estimate`], but learns the expected duration from previous runs.
+///
+/// The expected duration is the median of the durations recorded in `store` for `name`,
+/// or `fallback` if there are none yet. When the future completes, its actual duration
+/// is recorded, so recurring jobs such as builds or backups get increasingly accurate
+/// progress. Runs that are dropped before completing are not recorded.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{MemoryDurationStore, ProgressExt, estimate_with};
+///
+/// # async fn example() {
+/// let store = MemoryDurationStore::new(20);
+/// for _ in 0..3 {
+///     estimate_with(&store, "nightly-backup", Duration::from_secs(30), async {
+///         tokio::time::sleep(Duration::from_secs(42)).await;
+///     })
+///     .observe(|update| println!("{:.0}%", update.completed_fraction() * 100.0))
+///     .await;
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn estimate_with<S, Fut>(
+    store: S,
+    name: impl Into<String>,
+    fallback: Duration,
+    fut: Fut,
+) -> ProgressFuture<impl Future<Output = Fut::Output>>
+where
+    S: DurationStore,
+    Fut: Future,
+{
+    let name = name.into();
+    let expected = median(store.durations(&name)).unwrap_or(fallback);
+    track(expected, fut, move |elapsed| store.record(&name, elapsed))
+}
+
+fn track<Fut>(
+    expected: Duration,
+    fut: Fut,
+    finished: impl FnOnce(Duration),
+) -> ProgressFuture<impl Future<Output = Fut::Output>>
+where
+    Fut: Future,
+{
+    let tick = (expected / 100).clamp(Duration::from_millis(50), Duration::from_secs(1));
+    progress(TOTAL, move |mut updater| async move {
+        let started = Instant::now();
+        let mut fut = pin!(fut.fuse());
+        loop {
+            select! {
+                output = fut => {
+                    finished(started.elapsed());
+                    updater.complete();
+                    break output;
+                }
+                () = Delay::new(tick).fuse() => {
+                    updater.update(estimated(started.elapsed(), expected));
+                }
+            }
+        }
+    })
+}
+
+/// Returns the median of `durations`, averaging the middle pair for an even count.
+fn median(mut durations: Vec<Duration>) -> Option<Duration> {
+    durations.sort_unstable();
+    let middle = durations.len() / 2;
+    match durations.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(durations[middle]),
+        _ => Some((durations[middle - 1] + durations[middle]) / 2),
+    }
+}
+
+/// Returns the estimated progress, in basis points, after `elapsed` of `expected` time.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn estimated(elapsed: Duration, expected: Duration) -> u64 {
+    let ratio = if expected.is_zero() {
+        f64::INFINITY
+    } else {
+        elapsed.as_secs_f64() / expected.as_secs_f64()
+    };
+    let fraction = if ratio <= 1.0 {
+        ratio * AT_EXPECTED
+    } else {
+        (CEILING - AT_EXPECTED).mul_add(-(1.0 - ratio).exp(), CEILING)
+    };
+    (fraction * TOTAL as f64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Progress;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn test_estimated_curve() {
+        let expected = Duration::from_secs(10);
+        assert_eq!(estimated(Duration::ZERO, expected), 0);
+        assert_eq!(estimated(Duration::from_secs(5), expected), 4_500);
+        assert_eq!(estimated(expected, expected), 9_000);
+        let long = estimated(Duration::from_secs(1_000), expected);
+        assert!(long > 9_800 && long < TOTAL);
+        assert!(estimated(Duration::from_secs(1), Duration::ZERO) < TOTAL);
+    }
+
+    #[test]
+    fn test_median() {
+        let secs = |values: &[u64]| values.iter().copied().map(Duration::from_secs).collect();
+        assert_eq!(median(Vec::new()), None);
+        assert_eq!(median(secs(&[9, 1, 5])), Some(Duration::from_secs(5)));
+        assert_eq!(median(secs(&[4, 1, 2, 9])), Some(Duration::from_secs(3)));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_with_records_duration() {
+        let store = crate::MemoryDurationStore::new(2);
+        for _ in 0..3 {
+            estimate_with(&store, "job", Duration::from_secs(30), async {}).await;
+        }
+        let durations = store.durations("job");
+        assert_eq!(durations.len(), 2);
+        assert!(durations.iter().all(|d| *d < Duration::from_secs(30)));
+        assert!(store.durations("other").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_completes() {
+        let task = estimate(Duration::from_millis(100), async {
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            7
+        });
+        let updates = task.progress();
+        assert_eq!(task.await, 7);
+
+        let updates: Vec<_> = updates.collect().await;
+        let (last, working) = updates.split_last().unwrap();
+        assert!(last.is_completed());
+        assert!(!working.is_empty());
+        assert!(working.windows(2).all(|w| w[0].current() <= w[1].current()));
+    }
+}
@@ -0,0 +1,189 @@
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::{ProgressUpdate, suspend::SuspendTracker};
+
+/// Measures elapsed time, separating the time spent paused or with the system suspended.
+#[derive(Debug, Clone, Copy)]
+pub struct PauseClock {
+    started: Instant,
+    paused_since: Option<(Instant, Duration)>,
+    paused: Duration,
+    suspend: SuspendTracker,
+}
+
+impl PauseClock {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            paused_since: None,
+            paused: Duration::ZERO,
+            suspend: SuspendTracker::new(started),
+        }
+    }
+
+    /// Accounts for an update seen at `now`; returns `true` if it starts a pause.
+    pub fn observe(&mut self, update: &ProgressUpdate, now: Instant) -> bool {
+        match (self.paused_since, update.is_paused()) {
+            (None, true) => {
+                self.paused_since = Some((now, self.suspend.suspended(now)));
+                true
+            }
+            (Some(since), false) => {
+                self.paused += self.paused_interval(since, now);
+                self.paused_since = None;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    pub fn wall(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+
+    pub fn paused(&self, now: Instant) -> Duration {
+        self.paused
+            + self
+                .paused_since
+                .map_or(Duration::ZERO, |since| self.paused_interval(since, now))
+    }
+
+    // Measuring suspension is not const on Windows.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn suspended(&self, now: Instant) -> Duration {
+        self.suspend.suspended(now)
+    }
+
+    pub fn active(&self, now: Instant) -> Duration {
+        self.wall(now)
+            .saturating_sub(self.paused(now))
+            .saturating_sub(self.suspended(now))
+    }
+
+    /// Returns the time paused since `since`, minus any suspension, which is counted apart.
+    fn paused_interval(&self, (since, suspended): (Instant, Duration), now: Instant) -> Duration {
+        let suspended_while_paused = self.suspended(now).saturating_sub(suspended);
+        now.saturating_duration_since(since)
+            .saturating_sub(suspended_while_paused)
+    }
+}
+
+/// A progress update together with the rate and remaining time derived from it.
+///
+/// Produced by [`ProgressStreamExt::estimated`](crate::ProgressStreamExt::estimated).
+/// Time spent in the [`Paused`](crate::State::Paused) state does not count toward the
+/// rate, so a long pause does not make the remaining time look longer than it is.
+/// Neither does time the system spent suspended, so rates stay sensible after a
+/// laptop wakes up; on Windows this needs the `suspend-aware` feature.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EstimatedUpdate {
+    update: ProgressUpdate,
+    wall_elapsed: Duration,
+    active_elapsed: Duration,
+    rate: Option<f64>,
+}
+
+impl EstimatedUpdate {
+    /// Returns the underlying update.
+    #[must_use]
+    pub const fn update(&self) -> &ProgressUpdate {
+        &self.update
+    }
+
+    /// Returns the underlying update, consuming the estimate.
+    #[must_use]
+    pub fn into_update(self) -> ProgressUpdate {
+        self.update
+    }
+
+    /// Returns the time since the first update was observed.
+    #[must_use]
+    pub const fn wall_elapsed(&self) -> Duration {
+        self.wall_elapsed
+    }
+
+    /// Returns the time since the first update was observed, excluding time paused and
+    /// time the system was suspended.
+    #[must_use]
+    pub const fn active_elapsed(&self) -> Duration {
+        self.active_elapsed
+    }
+
+    /// Returns the average progress per second of active time, once there is any.
+    #[must_use]
+    pub const fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// Returns the estimated active time until the work is done, if the rate is known.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.rate.filter(|rate| *rate > 0.0)?;
+        Duration::try_from_secs_f64(self.update.remaining() as f64 / rate).ok()
+    }
+}
+
+/// Attaches rate and time estimates to every update.
+pub fn estimated<S>(updates: S) -> impl Stream<Item = EstimatedUpdate>
+where
+    S: Stream<Item = ProgressUpdate>,
+{
+    let mut state: Option<(PauseClock, u64)> = None;
+    updates.map(move |update| {
+        let now = Instant::now();
+        let (clock, baseline) =
+            state.get_or_insert_with(|| (PauseClock::new(now), update.current()));
+        clock.observe(&update, now);
+        let active_elapsed = clock.active(now);
+        #[allow(clippy::cast_precision_loss)]
+        let rate = (!active_elapsed.is_zero()).then(|| {
+            update.current().saturating_sub(*baseline) as f64 / active_elapsed.as_secs_f64()
+        });
+        EstimatedUpdate {
+            wall_elapsed: clock.wall(now),
+            active_elapsed,
+            rate,
+            update,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+
+    #[test]
+    fn test_pause_clock_excludes_paused_time() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let working = ProgressUpdate::new(10, 1, State::Working, None);
+        let paused = ProgressUpdate::new(10, 1, State::Paused, None);
+
+        let mut clock = PauseClock::new(start);
+        assert!(!clock.observe(&working, at(100)));
+        assert!(clock.observe(&paused, at(200)));
+        assert!(!clock.observe(&paused, at(300)));
+        assert_eq!(clock.active(at(400)), Duration::from_millis(200));
+        clock.observe(&working, at(700));
+        assert_eq!(clock.wall(at(1000)), Duration::from_secs(1));
+        assert_eq!(clock.paused(at(1000)), Duration::from_millis(500));
+        assert_eq!(clock.active(at(1000)), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_eta_from_rate() {
+        let estimate = EstimatedUpdate {
+            update: ProgressUpdate::new(100, 40, State::Working, None),
+            wall_elapsed: Duration::from_secs(10),
+            active_elapsed: Duration::from_secs(4),
+            rate: Some(10.0),
+        };
+        assert_eq!(estimate.eta(), Some(Duration::from_secs(6)));
+    }
+}
@@ -1,6 +1,14 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
 use futures_util::{FutureExt, StreamExt, pin_mut, select};
 
-use crate::{Progress, ProgressUpdate};
+#[cfg(feature = "std")]
+use crate::{
+    CatchPanic, Detached, DetachedWork, PanicPolicy, ProgressSummary, alarm::RateAlarm,
+    summary::SummaryRecorder,
+};
+use crate::{Progress, ProgressUpdate, runner::forward_updates, stream::percent};
 
 /// Extension trait providing convenient methods for observing progress updates.
 ///
@@ -123,6 +131,278 @@ pub trait ProgressExt: Progress {
             }
         }
     }
+
+    /// Observes progress updates like [`observe`](Self::observe) and also returns a
+    /// [`ProgressSummary`] of the whole operation alongside the output.
+    ///
+    /// The summary covers the time from the first poll of the returned future until
+    /// the operation finished, and is useful for printing a completion report.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{progress, ProgressExt};
+    ///
+    /// # async fn example() {
+    /// let (result, summary) = progress(100, |mut updater| async move {
+    ///     for i in 0..=100 {
+    ///         updater.update(i);
+    ///     }
+    ///     "Done"
+    /// })
+    /// .observe_summary(|_| {})
+    /// .await;
+    ///
+    /// println!(
+    ///     "{result} in {:?}: {} updates, {:.1} items/s on average, paused {} times",
+    ///     summary.duration(),
+    ///     summary.updates(),
+    ///     summary.average_rate(),
+    ///     summary.pauses(),
+    /// );
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn observe_summary(
+        self,
+        receiver: impl Fn(ProgressUpdate) + Send,
+    ) -> impl Future<Output = (Self::Output, ProgressSummary)> + Send
+    where
+        Self: Send + Sized,
+    {
+        async move {
+            let mut recorder = SummaryRecorder::new();
+            let recording = &mut recorder;
+            let result = forward_updates(self, move |update| {
+                recording.record(&update);
+                receiver(update);
+            })
+            .await;
+            (result, recorder.finish())
+        }
+    }
+
+    /// Calls `handler` every time progress crosses a multiple of `step` percent, and with
+    /// the final update.
+    ///
+    /// This is the usual way to tell a person about a long task now and then, such as in
+    /// a chat message or an email. An update that jumps over several boundaries calls
+    /// `handler` once. The final completed, cancelled or failed update is always handed
+    /// over, even if it crosses no boundary. A `step` of zero is treated as one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// use progressor::{ProgressExt, progress};
+    ///
+    /// # async fn example() {
+    /// let mut notified = Vec::new();
+    /// progress(1000, |mut updater| async move {
+    ///     for i in 1..=1000 {
+    ///         updater.update(i);
+    ///         tokio::task::yield_now().await;
+    ///     }
+    ///     updater.complete();
+    /// })
+    /// .notify_every(10, |update| notified.push(update.current()))
+    /// .await;
+    ///
+    /// assert_eq!(notified, [100, 200, 300, 400, 500, 600, 700, 800, 900, 1000, 1000]);
+    /// # }
+    /// # }
+    /// ```
+    fn notify_every(
+        self,
+        step: u8,
+        mut handler: impl FnMut(ProgressUpdate) + Send,
+    ) -> impl Future<Output = Self::Output> + Send
+    where
+        Self: Send + Sized,
+    {
+        async move {
+            let step = step.max(1);
+            let mut reached = 0;
+            forward_updates(self, move |update| {
+                let boundary = percent(&update) / step;
+                let crossed = boundary > reached;
+                reached = boundary;
+                if crossed || update.is_terminal() {
+                    handler(update);
+                }
+            })
+            .await
+        }
+    }
+
+    /// Calls `handler` when throughput stays under `threshold` units per second for a
+    /// whole `window`, for example when a network transfer degrades.
+    ///
+    /// The rate is measured over the most recent `window`, so the alarm cannot fire
+    /// before the task ran that long, and it is also checked while no updates arrive.
+    /// It fires once per slowdown, with the measured rate, and re-arms once the rate
+    /// recovers. Time spent paused is not counted as a slowdown. The handler is a good
+    /// place to cancel the task through its controller to retry from another mirror.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use progressor::{CancelReason, ProgressExt, progress};
+    ///
+    /// # async fn example() {
+    /// let download = progress(1 << 30, |mut updater| async move {
+    ///     let mut received = 0;
+    ///     while received < 1 << 30 && updater.cancel_requested().is_none() {
+    ///         received += 64 << 10;
+    ///         updater.update(received);
+    ///         tokio::time::sleep(Duration::from_millis(10)).await;
+    ///     }
+    /// });
+    /// let controller = download.controller();
+    ///
+    /// download
+    ///     .alarm_if_rate_below(1_000_000.0, Duration::from_secs(10), move |rate| {
+    ///         eprintln!("only {rate:.0} B/s, switching mirrors");
+    ///         controller.cancel(CancelReason::UserRequest);
+    ///     })
+    ///     .await;
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn alarm_if_rate_below(
+        self,
+        threshold: f64,
+        window: Duration,
+        mut handler: impl FnMut(f64) + Send,
+    ) -> impl Future<Output = Self::Output> + Send
+    where
+        Self: Send + Sized,
+    {
+        async move {
+            let tick = (window / 4).max(Duration::from_millis(10));
+            let mut alarm = RateAlarm::new(threshold, window, Instant::now());
+            // Kept across updates, so a busy task still has its rate checked every tick.
+            let mut timer = futures_timer::Delay::new(tick).fuse();
+            let progress_stream = self.progress().fuse();
+            let future = self.fuse();
+            pin_mut!(progress_stream, future);
+
+            loop {
+                select! {
+                    result = future => return result,
+                    update = progress_stream.next() => {
+                        if let Some(update) = update {
+                            alarm.record(&update, Instant::now());
+                        }
+                    }
+                    () = timer => timer = futures_timer::Delay::new(tick).fuse(),
+                }
+                if let Some(rate) = alarm.check(Instant::now()) {
+                    handler(rate);
+                }
+            }
+        }
+    }
+
+    /// Lets the task keep running when the returned future is dropped.
+    ///
+    /// Dropping a task normally stops its work and reports it as cancelled. Some work
+    /// should outlive the code waiting for it, such as a job started by an HTTP request
+    /// whose client went away. The returned future behaves like the task while it is
+    /// awaited, but if it is dropped before the task finished, the remaining work is
+    /// passed to `spawn`, typically the spawner of the runtime in use. Observers keep
+    /// receiving updates through the streams they already hold.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{Progress, ProgressExt, progress};
+    ///
+    /// # async fn example() {
+    /// let export = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///         tokio::task::yield_now().await;
+    ///     }
+    /// })
+    /// .detach(|work| {
+    ///     tokio::spawn(work);
+    /// });
+    /// let updates = export.progress();
+    ///
+    /// // The request handler returns without waiting; the export keeps running.
+    /// drop(export);
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn detach(self, spawn: impl FnOnce(DetachedWork) + Send + 'static) -> Detached<Self>
+    where
+        Self: Send + Sized + 'static,
+    {
+        Detached::new(self, spawn)
+    }
+
+    /// Handles panics of the task according to `policy`, see [`PanicPolicy`].
+    ///
+    /// By default a panic in a task propagates to whoever awaits it. Services that run
+    /// user-supplied jobs can contain them instead, without wrapping each job in
+    /// [`AssertUnwindSafe`](std::panic::AssertUnwindSafe): the task then resolves to a
+    /// [`TaskPanic`](crate::TaskPanic) error. Observers see the task as [`Failed`](crate::State::Failed).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{PanicPolicy, ProgressExt, progress};
+    ///
+    /// # async fn example() {
+    /// // Once at startup, so errors tell where jobs panicked.
+    /// PanicPolicy::install_location_hook();
+    ///
+    /// let job = progress(10, |_| async {
+    ///     panic!("plugin bug");
+    /// })
+    /// .catch_panic(PanicPolicy::Quarantine);
+    ///
+    /// if let Err(panic) = job.await {
+    ///     eprintln!("job quarantined: {panic}");
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn catch_panic(self, policy: PanicPolicy) -> CatchPanic<Self>
+    where
+        Self: Sized,
+    {
+        CatchPanic::new(self, policy)
+    }
 }
 
 impl<T: Progress> ProgressExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress;
+
+    #[tokio::test]
+    async fn test_notify_every() {
+        let mut notified = Vec::new();
+        progress(100, |mut updater| async move {
+            for i in [5, 10, 12, 35, 99] {
+                updater.update(i);
+                tokio::task::yield_now().await;
+            }
+            updater.fail_with_message("lost connection");
+        })
+        .notify_every(25, |update| notified.push(update.current()))
+        .await;
+
+        assert_eq!(notified, [35, 99, 99]);
+    }
+}
@@ -0,0 +1,168 @@
+use async_broadcast::{Sender, broadcast};
+use futures_core::Stream;
+use futures_util::{StreamExt, pin_mut};
+
+use crate::ProgressUpdate;
+
+/// What a [`Fanout`] consumer loses when its buffer is full.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered update to make room, so the consumer catches up with
+    /// the latest progress. Suits interfaces.
+    DropOldest,
+    /// Discard the new update, so the consumer sees early updates without gaps. Suits
+    /// logs and audits that care about the beginning of a task.
+    DropNewest,
+}
+
+/// Feeds one progress stream to several consumers, each with its own buffer.
+///
+/// All [`progress`](crate::Progress::progress) streams of a task share one channel, so
+/// a consumer that reads slowly, such as a webhook, fills it up and the updates it
+/// causes to be dropped are lost to fast consumers too, such as the interface. A fanout
+/// reads the stream as fast as it arrives and buffers it separately for every consumer,
+/// with the capacity and [`OverflowPolicy`] that consumer needs. A slow consumer then
+/// only loses its own updates. The final completed, cancelled or failed update always
+/// reaches every consumer, replacing the oldest buffered one if needed.
+///
+/// Consumers are added with [`subscribe`](Self::subscribe) before the fanout is
+/// [run](Self::run).
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{Fanout, OverflowPolicy, Progress, progress};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let task = progress(1000, |mut updater| async move {
+///     for i in 1..=1000 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+/// let mut fanout = Fanout::new(task.progress());
+/// let mut ui = fanout.subscribe(256, OverflowPolicy::DropOldest);
+/// let mut webhook = fanout.subscribe(4, OverflowPolicy::DropOldest);
+/// tokio::spawn(fanout.run());
+/// tokio::spawn(task);
+///
+/// tokio::spawn(async move {
+///     while let Some(update) = webhook.next().await {
+///         // Slow network call; the interface does not wait for it.
+///         println!("POST {}/{}", update.current(), update.total());
+///     }
+/// });
+/// while let Some(update) = ui.next().await {
+///     println!("{}/{}", update.current(), update.total());
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct Fanout<S> {
+    source: S,
+    consumers: Vec<Sender<ProgressUpdate>>,
+}
+
+impl<S> Fanout<S>
+where
+    S: Stream<Item = ProgressUpdate>,
+{
+    /// Creates a fanout of `source` without consumers.
+    pub const fn new(source: S) -> Self {
+        Self {
+            source,
+            consumers: Vec::new(),
+        }
+    }
+
+    /// Adds a consumer buffering up to `capacity` updates, losing updates by `policy`
+    /// when it falls further behind.
+    ///
+    /// The stream ends after the source ended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn subscribe(
+        &mut self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        let (mut sender, receiver) = broadcast(capacity);
+        sender.set_overflow(policy == OverflowPolicy::DropOldest);
+        self.consumers.push(sender);
+        receiver
+    }
+
+    /// Returns the number of consumers.
+    #[must_use]
+    pub const fn consumers(&self) -> usize {
+        self.consumers.len()
+    }
+
+    /// Reads the source and feeds its updates to the consumers until the source ends or
+    /// every consumer went away.
+    pub async fn run(self) {
+        let Self {
+            source,
+            mut consumers,
+        } = self;
+        pin_mut!(source);
+        while let Some(update) = source.next().await {
+            let terminal = update.is_terminal();
+            consumers.retain_mut(|sender| {
+                if terminal {
+                    // The final update makes room for itself under every policy.
+                    sender.set_overflow(true);
+                }
+                let _ = sender.try_broadcast(update.clone());
+                sender.receiver_count() > 0
+            });
+            if consumers.is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+
+    #[tokio::test]
+    async fn test_independent_buffers() {
+        let task = progress(20, |mut updater| async move {
+            for i in 1..=20 {
+                updater.update(i);
+            }
+            updater.complete();
+        });
+        let mut fanout = Fanout::new(task.progress());
+        let fast = fanout.subscribe(64, OverflowPolicy::DropOldest);
+        let latest = fanout.subscribe(2, OverflowPolicy::DropOldest);
+        let earliest = fanout.subscribe(2, OverflowPolicy::DropNewest);
+        assert_eq!(fanout.consumers(), 3);
+        task.await;
+        fanout.run().await;
+
+        assert_eq!(fast.count().await, 21);
+        let latest: Vec<_> = latest.collect().await;
+        assert_eq!(latest[0].current(), 20);
+        assert!(latest[1].is_completed());
+        let earliest: Vec<_> = earliest.collect().await;
+        assert_eq!(earliest[0].current(), 2);
+        assert!(earliest[1].is_completed());
+    }
+
+    #[tokio::test]
+    async fn test_stops_without_consumers() {
+        let update = ProgressUpdate::new(1, 0, crate::State::Working, None);
+        let mut fanout = Fanout::new(futures_util::stream::repeat(update));
+        drop(fanout.subscribe(1, OverflowPolicy::DropOldest));
+        fanout.run().await;
+    }
+}
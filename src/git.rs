@@ -0,0 +1,159 @@
+use core::cell::RefCell;
+
+use git2::{RemoteCallbacks, build::CheckoutBuilder};
+
+use crate::ProgressUpdater;
+
+const RECEIVING: &str = "Receiving objects";
+const RESOLVING: &str = "Resolving deltas";
+const CHECKING_OUT: &str = "Checking out";
+
+/// Reports libgit2 transfer and checkout callbacks through a [`ProgressUpdater`].
+///
+/// Fetching and cloning go through several phases. Each phase is reported as an
+/// [extra state](crate::ProgressUpdate::extra_state) with its own total:
+///
+/// - `"Receiving objects"`: objects received out of the total to download
+/// - `"Resolving deltas"`: deltas indexed out of the total to resolve
+/// - `"Checking out"`: files written out of the total to check out
+///
+/// Text sent by the remote, such as `"Counting objects: 45%"`, is forwarded as the
+/// message of an update.
+///
+/// # Examples
+///
+/// ```no_run
+/// use git2::{FetchOptions, build::RepoBuilder};
+/// use progressor::{GitProgress, ProgressExt, progress};
+///
+/// # async fn example() {
+/// let repository = progress(0, |updater| async move {
+///     tokio::task::spawn_blocking(move || {
+///         let git = GitProgress::new(updater);
+///         let mut fetch = FetchOptions::new();
+///         fetch.remote_callbacks(git.remote_callbacks());
+///         let result = RepoBuilder::new()
+///             .fetch_options(fetch)
+///             .with_checkout(git.checkout_builder())
+///             .clone("https://github.com/lexoliu/progressor", "progressor".as_ref());
+///         git.complete();
+///         result.map(|_| ())
+///     })
+///     .await
+/// })
+/// .observe(|update| {
+///     if let Some(phase) = update.extra_state() {
+///         println!("{phase}: {}/{}", update.current(), update.total());
+///     }
+/// })
+/// .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "git2")))]
+#[derive(Debug)]
+pub struct GitProgress {
+    updater: RefCell<ProgressUpdater>,
+}
+
+impl GitProgress {
+    /// Wraps an updater to receive libgit2 progress.
+    #[must_use]
+    pub const fn new(updater: ProgressUpdater) -> Self {
+        Self {
+            updater: RefCell::new(updater),
+        }
+    }
+
+    /// Returns remote callbacks that report transfer progress and remote messages.
+    ///
+    /// Use them with [`git2::FetchOptions::remote_callbacks`] or when connecting a remote.
+    #[must_use]
+    pub fn remote_callbacks(&self) -> RemoteCallbacks<'_> {
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.transfer_progress(|stats| {
+            self.transfer(&stats);
+            true
+        });
+        callbacks.sideband_progress(|data| {
+            self.sideband(data);
+            true
+        });
+        callbacks
+    }
+
+    /// Returns a checkout builder that reports checkout progress.
+    #[must_use]
+    pub fn checkout_builder(&self) -> CheckoutBuilder<'_> {
+        let mut checkout = CheckoutBuilder::new();
+        checkout.progress(|_, completed, total| self.checkout(completed, total));
+        checkout
+    }
+
+    /// Reports transfer statistics from a `transfer_progress` callback.
+    pub fn transfer(&self, stats: &git2::Progress<'_>) {
+        let receiving = stats.received_objects() < stats.total_objects();
+        if receiving || stats.total_deltas() == 0 {
+            self.report(RECEIVING, stats.total_objects(), stats.received_objects());
+        } else {
+            self.report(RESOLVING, stats.total_deltas(), stats.indexed_deltas());
+        }
+    }
+
+    /// Reports text sent by the remote from a `sideband_progress` callback.
+    pub fn sideband(&self, data: &[u8]) {
+        let text = String::from_utf8_lossy(data);
+        let text = text.trim();
+        if !text.is_empty() {
+            let mut updater = self.updater.borrow_mut();
+            let current = updater.current();
+            updater.update_with_message(current, text);
+        }
+    }
+
+    /// Reports checkout progress from a checkout `progress` callback.
+    pub fn checkout(&self, completed: usize, total: usize) {
+        self.report(CHECKING_OUT, total, completed);
+    }
+
+    /// Marks the operation as completed.
+    pub fn complete(self) {
+        self.updater.into_inner().complete();
+    }
+
+    /// Returns the wrapped updater.
+    #[must_use]
+    pub fn into_inner(self) -> ProgressUpdater {
+        self.updater.into_inner()
+    }
+
+    fn report(&self, phase: &'static str, total: usize, current: usize) {
+        let updater = self.updater.borrow();
+        updater.set_extra_state_silently(Some(phase));
+        updater.report(total as u64, current as u64, |update| update);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_checkout_phase() {
+        let task = progress(0, |updater| async move {
+            let git = GitProgress::new(updater);
+            git.sideband(b"Counting objects: 100% (3/3)\r");
+            git.checkout(1, 3);
+            git.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates: Vec<_> = updates.collect().await;
+        assert_eq!(updates[0].message(), Some("Counting objects: 100% (3/3)"));
+        assert_eq!(updates[1].extra_state(), Some(CHECKING_OUT));
+        assert_eq!((updates[1].current(), updates[1].total()), (1, 3));
+        assert!(updates[2].is_completed());
+    }
+}
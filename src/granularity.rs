@@ -0,0 +1,197 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::{ProgressUpdate, State, Transport, sync::lock};
+
+/// How often an observer wants to hear about a task.
+///
+/// A local interface can redraw on every update, while a remote observer behind a
+/// webhook or a metered link only wants to hear about meaningful steps. A task reports
+/// once with [`update`](crate::ProgressUpdater::update), and every observer applies its
+/// own granularity: to a stream with
+/// [`ProgressStreamExt::with_granularity`](crate::ProgressStreamExt::with_granularity),
+/// or to a transport with [`transport`](Self::transport).
+///
+/// An update is let through once both the interval has passed and the progress moved by
+/// the step since the last update let through. Changes of [`State`], updates carrying a
+/// [`ProgressEvent`](crate::ProgressEvent) and the final update always pass, so a coarse
+/// observer misses intermediate values but never the outcome.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{Granularity, Progress, progress};
+///
+/// # async fn example() {
+/// let (webhook, remote) = async_broadcast::broadcast(16);
+/// let task = progress(1000, |mut updater| async move {
+///     for i in 1..=1000 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// })
+/// .with_transport(
+///     Granularity::new()
+///         .with_min_interval(Duration::from_secs(5))
+///         .with_min_step(0.1)
+///         .transport(webhook),
+/// );
+/// let local = task.progress();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Granularity {
+    min_interval: Duration,
+    min_step: f64,
+}
+
+impl Granularity {
+    /// Creates a granularity that lets every update through.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            min_interval: Duration::ZERO,
+            min_step: 0.0,
+        }
+    }
+
+    /// Lets at most one update through per `interval`.
+    #[must_use]
+    pub const fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Lets an update through only once progress moved by `step`, a fraction of the total.
+    #[must_use]
+    pub const fn with_min_step(mut self, step: f64) -> Self {
+        self.min_step = step;
+        self
+    }
+
+    /// Returns the minimum time between updates.
+    #[must_use]
+    pub const fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    /// Returns the minimum movement between updates, as a fraction of the total.
+    #[must_use]
+    pub const fn min_step(&self) -> f64 {
+        self.min_step
+    }
+
+    /// Returns a [`Transport`] that delivers updates to `transport` at this granularity.
+    #[must_use]
+    pub fn transport(self, transport: impl Transport) -> impl Transport {
+        Coarse {
+            gate: Mutex::new(Gate::new(self)),
+            transport,
+        }
+    }
+
+    pub(crate) fn filter<S>(self, stream: S) -> impl Stream<Item = ProgressUpdate>
+    where
+        S: Stream<Item = ProgressUpdate>,
+    {
+        let mut gate = Gate::new(self);
+        stream.filter(move |update| core::future::ready(gate.admit(update, Instant::now())))
+    }
+}
+
+/// The last update an observer received, deciding whether the next one is news to it.
+#[derive(Debug)]
+struct Gate {
+    granularity: Granularity,
+    last: Option<(Instant, f64, State)>,
+}
+
+impl Gate {
+    const fn new(granularity: Granularity) -> Self {
+        Self {
+            granularity,
+            last: None,
+        }
+    }
+
+    fn admit(&mut self, update: &ProgressUpdate, now: Instant) -> bool {
+        let fraction = update.completed_fraction();
+        let admitted = self.last.is_none_or(|(at, last_fraction, state)| {
+            state != update.state()
+                || update.event().is_some()
+                || update.is_terminal()
+                || (now.duration_since(at) >= self.granularity.min_interval
+                    && (fraction - last_fraction).abs() >= self.granularity.min_step)
+        });
+        if admitted {
+            self.last = Some((now, fraction, update.state()));
+        }
+        admitted
+    }
+}
+
+/// Delivers the updates a [`Gate`] admits to another transport.
+struct Coarse<T> {
+    gate: Mutex<Gate>,
+    transport: T,
+}
+
+impl<T: Transport> Transport for Coarse<T> {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        let admitted = lock(&self.gate).admit(update, Instant::now());
+        !admitted || self.transport.send(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, ProgressStreamExt, progress};
+
+    #[test]
+    fn test_gate() {
+        let mut gate = Gate::new(
+            Granularity::new()
+                .with_min_interval(Duration::from_secs(1))
+                .with_min_step(0.1),
+        );
+        let start = Instant::now();
+        let update = |current| ProgressUpdate::new(100, current, State::Working, None);
+        assert!(gate.admit(&update(1), start));
+        // Neither too early nor too small a step passes.
+        assert!(!gate.admit(&update(20), start + Duration::from_millis(500)));
+        assert!(!gate.admit(&update(5), start + Duration::from_secs(2)));
+        assert!(gate.admit(&update(20), start + Duration::from_secs(2)));
+        let paused = ProgressUpdate::new(100, 21, State::Paused, None);
+        assert!(gate.admit(&paused, start + Duration::from_secs(2)));
+    }
+
+    #[tokio::test]
+    async fn test_coarse_and_fine() {
+        let (sender, coarse) = async_broadcast::broadcast(16);
+        let task = progress(20, |mut updater| async move {
+            for i in 1..=20 {
+                updater.update(i);
+            }
+            updater.complete();
+        })
+        .with_transport(Granularity::new().with_min_step(0.25).transport(sender));
+        let fine = task.progress();
+        let steps = task
+            .progress()
+            .with_granularity(Granularity::new().with_min_step(0.5));
+        task.await;
+
+        assert_eq!(fine.count().await, 21);
+        let coarse: Vec<_> = coarse.map(|update| update.current()).collect().await;
+        assert_eq!(coarse, [1, 6, 11, 16, 20]);
+        let steps: Vec<_> = steps.map(|update| update.current()).collect().await;
+        assert_eq!(steps, [1, 11, 20]);
+    }
+}
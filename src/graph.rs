@@ -0,0 +1,603 @@
+use core::{future::Future, pin::Pin};
+use std::collections::VecDeque;
+
+use async_broadcast::{InactiveReceiver, Sender, broadcast};
+use futures_core::Stream;
+use futures_util::{
+    FutureExt, StreamExt, select,
+    stream::{FuturesUnordered, SelectAll},
+};
+
+use crate::{
+    CancelReason, Progress, ProgressController, ProgressFuture, ProgressUpdate, ProgressUpdater,
+    State, progress,
+};
+
+type BoxedTask<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type NodeTask<T> = Box<dyn FnOnce(ProgressUpdater) -> BoxedTask<T> + Send>;
+
+/// Identifies a task added to a [`TaskGraph`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Returns the position of the task in the order it was added to the graph.
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+struct Node<T> {
+    cost: u64,
+    dependents: Vec<usize>,
+    pending_dependencies: usize,
+    task: Option<NodeTask<T>>,
+}
+
+/// A set of progress-tracked tasks with dependencies between them.
+///
+/// Tasks run in topological order: a task starts only after all of its dependencies
+/// have finished. A task that reports [`Failed`](State::Failed) never lets its dependents
+/// start: they are reported [`Cancelled`](State::Cancelled) with
+/// [`CancelReason::DependencyFailed`], while tasks that do not depend on it keep running.
+/// Each task declares a cost, which is both the initial total of its own
+/// [`ProgressUpdater`] and its weight in the aggregate progress of the graph.
+///
+/// Dependencies can only refer to tasks that were added earlier, so a graph can never
+/// contain a cycle.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{Progress, TaskGraph};
+///
+/// # async fn example() {
+/// let mut graph = TaskGraph::new();
+/// let fetch = graph.add(10, &[], |mut updater| async move {
+///     updater.update(10);
+///     "sources"
+/// });
+/// let build = graph.add(90, &[fetch], |mut updater| async move {
+///     for i in 0..=90 {
+///         updater.update(i);
+///     }
+///     "binary"
+/// });
+/// graph.add(5, &[build], |_| async { "tests" });
+///
+/// let node_updates = graph.node_updates();
+/// let run = graph.run(4);
+/// let overall = run.progress();
+///
+/// let outputs = run.await;
+/// assert_eq!(outputs, Ok(vec!["sources", "binary", "tests"]));
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct TaskGraph<T> {
+    nodes: Vec<Node<T>>,
+    sender: Sender<(NodeId, ProgressUpdate)>,
+    receiver: InactiveReceiver<(NodeId, ProgressUpdate)>,
+}
+
+impl<T> core::fmt::Debug for TaskGraph<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaskGraph")
+            .field("nodes", &self.nodes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for TaskGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TaskGraph<T> {
+    /// Creates an empty task graph.
+    #[must_use]
+    pub fn new() -> Self {
+        let (sender, receiver) = broadcast(32);
+        Self {
+            nodes: Vec::new(),
+            sender,
+            receiver: receiver.deactivate(),
+        }
+    }
+
+    /// Adds a task that starts once all `dependencies` have finished.
+    ///
+    /// The closure receives a [`ProgressUpdater`] whose total is `cost`, just like the
+    /// closure passed to [`progress`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dependency does not belong to this graph.
+    pub fn add<F, Fut>(&mut self, cost: u64, dependencies: &[NodeId], f: F) -> NodeId
+    where
+        F: FnOnce(ProgressUpdater) -> Fut + Send + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let id = self.nodes.len();
+        for dependency in dependencies {
+            assert!(
+                dependency.0 < id,
+                "dependency {} is not part of this graph",
+                dependency.0
+            );
+            self.nodes[dependency.0].dependents.push(id);
+        }
+        self.nodes.push(Node {
+            cost,
+            dependents: Vec::new(),
+            pending_dependencies: dependencies.len(),
+            task: Some(Box::new(move |updater| Box::pin(f(updater)))),
+        });
+        NodeId(id)
+    }
+
+    /// Returns a stream of the updates reported by each individual task.
+    ///
+    /// When a task finishes, a [`Completed`](State::Completed) update is emitted for it,
+    /// unless it failed. Tasks skipped because a dependency failed get a single
+    /// [`Cancelled`](State::Cancelled) update with [`CancelReason::DependencyFailed`].
+    pub fn node_updates(
+        &self,
+    ) -> impl Stream<Item = (NodeId, ProgressUpdate)> + Unpin + Send + 'static {
+        self.receiver.activate_cloned()
+    }
+
+    /// Returns the updates the tasks would report if each ran to completion in turn,
+    /// without running any of them.
+    ///
+    /// Tasks are listed in the order a run with one task in flight would start them,
+    /// each with a [`Working`](State::Working) update at zero and a
+    /// [`Completed`](State::Completed) update at its cost. Interfaces use this to render
+    /// the list of steps and their totals before the user confirms the operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::TaskGraph;
+    ///
+    /// let mut graph = TaskGraph::new();
+    /// let download = graph.add(70, &[], |_| async {});
+    /// graph.add(30, &[download], |_| async {});
+    ///
+    /// for (id, update) in graph.dry_run() {
+    ///     if update.is_working() {
+    ///         println!("step {}: {} units", id.index() + 1, update.total());
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn dry_run(&self) -> Vec<(NodeId, ProgressUpdate)> {
+        let mut pending: Vec<usize> = self
+            .nodes
+            .iter()
+            .map(|node| node.pending_dependencies)
+            .collect();
+        let mut ready: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&id| pending[id] == 0)
+            .collect();
+        let mut timeline = Vec::with_capacity(self.nodes.len() * 2);
+        while let Some(id) = ready.pop_front() {
+            let cost = self.nodes[id].cost;
+            timeline.push((
+                NodeId(id),
+                ProgressUpdate::new(cost, 0, State::Working, None),
+            ));
+            timeline.push((
+                NodeId(id),
+                ProgressUpdate::new(cost, cost, State::Completed, None).with_sequence(1),
+            ));
+            for &dependent in &self.nodes[id].dependents {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    ready.push_back(dependent);
+                }
+            }
+        }
+        timeline
+    }
+}
+
+impl<T: Send + 'static> TaskGraph<T> {
+    /// Runs the graph with at most `max_parallel` tasks in flight.
+    ///
+    /// The returned future resolves to the outputs of all tasks, in the order they were
+    /// added. Its progress stream reports the cost-weighted progress of the whole graph.
+    ///
+    /// Cancelling the graph through its [`ProgressController`] forwards the cancellation
+    /// request to every running task and starts no new ones. Once the running tasks have
+    /// stopped, the graph reports [`Cancelled`](State::Cancelled) and resolves to the
+    /// [`CancelReason`].
+    ///
+    /// If a task fails, the graph runs every task that does not depend on it, then
+    /// reports [`Cancelled`](State::Cancelled) and resolves to
+    /// [`CancelReason::DependencyFailed`].
+    #[must_use]
+    pub fn run(
+        self,
+        max_parallel: usize,
+    ) -> ProgressFuture<impl Future<Output = Result<Vec<T>, CancelReason>> + Send> {
+        let total = self.nodes.iter().map(|node| node.cost).sum();
+        progress(total, move |mut updater| async move {
+            let max_parallel = max_parallel.max(1);
+            let mut scheduler = Scheduler::new(self);
+            let mut running = FuturesUnordered::new();
+            let mut updates = SelectAll::new();
+            let mut cancelled = updater.cancelled().fuse();
+            let mut cancel_reason = None;
+
+            while !scheduler.is_finished() {
+                if let (None, Some(reason)) = (cancel_reason, updater.cancel_requested()) {
+                    cancel_reason = Some(reason);
+                    scheduler.cancel_running(reason);
+                }
+                if cancel_reason.is_none() {
+                    while running.len() < max_parallel {
+                        let Some((id, task)) = scheduler.start_next() else {
+                            break;
+                        };
+                        updates.push(task.progress().map(move |update| (id, update)));
+                        running.push(async move { (id, task.await) });
+                    }
+                } else if running.is_empty() {
+                    break;
+                }
+
+                select! {
+                    (id, output) = running.select_next_some() => {
+                        // Forward what the task reported before finishing, including
+                        // its terminal update.
+                        while let Some(Some((id, update))) = updates.next().now_or_never() {
+                            scheduler.update(id, update);
+                        }
+                        scheduler.finish(id, output, cancel_reason.is_some());
+                    }
+                    (id, update) = updates.select_next_some() => scheduler.update(id, update),
+                    reason = cancelled => {
+                        cancel_reason = Some(reason);
+                        scheduler.cancel_running(reason);
+                    }
+                }
+                updater.update(scheduler.weighted_current());
+            }
+
+            if let Some(reason) = cancel_reason {
+                return Err(reason);
+            }
+            if scheduler.has_failed() {
+                updater.cancel_with_reason(CancelReason::DependencyFailed);
+                return Err(CancelReason::DependencyFailed);
+            }
+            updater.complete();
+            Ok(scheduler.into_outputs())
+        })
+    }
+}
+
+/// Bookkeeping for a running [`TaskGraph`].
+struct Scheduler<T> {
+    nodes: Vec<Node<T>>,
+    sender: Sender<(NodeId, ProgressUpdate)>,
+    ready: VecDeque<usize>,
+    fractions: Vec<f64>,
+    last_updates: Vec<Option<(State, u64)>>,
+    controllers: Vec<Option<ProgressController>>,
+    outputs: Vec<Option<T>>,
+    finished: usize,
+    failed: bool,
+}
+
+impl<T: Send + 'static> Scheduler<T> {
+    fn new(graph: TaskGraph<T>) -> Self {
+        let TaskGraph { nodes, sender, .. } = graph;
+        let ready = (0..nodes.len())
+            .filter(|&id| nodes[id].pending_dependencies == 0)
+            .collect();
+        Self {
+            ready,
+            fractions: vec![0.0; nodes.len()],
+            last_updates: vec![None; nodes.len()],
+            controllers: nodes.iter().map(|_| None).collect(),
+            outputs: nodes.iter().map(|_| None).collect(),
+            finished: 0,
+            failed: false,
+            nodes,
+            sender,
+        }
+    }
+
+    const fn is_finished(&self) -> bool {
+        self.finished == self.nodes.len()
+    }
+
+    const fn has_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn start_next(&mut self) -> Option<(usize, ProgressFuture<BoxedTask<T>>)> {
+        let id = self.ready.pop_front()?;
+        let task = self.nodes[id].task.take()?;
+        let task = progress(self.nodes[id].cost, task);
+        self.controllers[id] = Some(task.controller());
+        Some((id, task))
+    }
+
+    fn update(&mut self, id: usize, update: ProgressUpdate) {
+        // Updates buffered before a task finished must not move it backwards.
+        if self.outputs[id].is_none() {
+            self.fractions[id] = update.completed_fraction().clamp(0.0, 1.0);
+            self.last_updates[id] = Some((update.state(), update.sequence()));
+            let _ = self.sender.try_broadcast((NodeId(id), update));
+        }
+    }
+
+    fn finish(&mut self, id: usize, output: T, cancelling: bool) {
+        self.outputs[id] = Some(output);
+        self.finished += 1;
+
+        // The task's own stream drops updates once its buffer is full, so how the task
+        // ended is read from the state it shares with its controller.
+        let final_state = self.controllers[id]
+            .take()
+            .and_then(|controller| controller.final_state());
+        let (last_state, next_sequence) = self.last_updates[id]
+            .map_or((None, 0), |(state, sequence)| (Some(state), sequence + 1));
+        if final_state == Some(State::Failed) {
+            // What depends on the failed task must not run.
+            self.failed = true;
+            self.skip_dependents(id);
+            return;
+        }
+        for dependent in core::mem::take(&mut self.nodes[id].dependents) {
+            self.nodes[dependent].pending_dependencies -= 1;
+            if self.nodes[dependent].pending_dependencies == 0 {
+                self.ready.push_back(dependent);
+            }
+        }
+
+        let was_cancelled = cancelling && final_state == Some(State::Cancelled);
+        if !was_cancelled {
+            self.fractions[id] = 1.0;
+            if last_state != Some(State::Completed) {
+                let cost = self.nodes[id].cost;
+                let done = ProgressUpdate::new(cost, cost, State::Completed, None)
+                    .with_sequence(next_sequence);
+                let _ = self.sender.try_broadcast((NodeId(id), done));
+            }
+        }
+    }
+
+    /// Reports every task depending on `id`, directly or not, as cancelled without
+    /// starting it.
+    fn skip_dependents(&mut self, id: usize) {
+        let mut skipped = core::mem::take(&mut self.nodes[id].dependents);
+        while let Some(dependent) = skipped.pop() {
+            // A task depending on several failed ones is only skipped once.
+            if self.nodes[dependent].task.take().is_none() {
+                continue;
+            }
+            self.finished += 1;
+            let cost = self.nodes[dependent].cost;
+            let cancelled = ProgressUpdate::new(cost, 0, State::Cancelled, None)
+                .with_cancel_reason(CancelReason::DependencyFailed);
+            let _ = self.sender.try_broadcast((NodeId(dependent), cancelled));
+            skipped.append(&mut self.nodes[dependent].dependents);
+        }
+    }
+
+    fn cancel_running(&self, reason: CancelReason) {
+        for controller in self.controllers.iter().flatten() {
+            controller.cancel(reason);
+        }
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn weighted_current(&self) -> u64 {
+        // Summed before truncating, so tasks with small costs still add up.
+        let current: f64 = self
+            .nodes
+            .iter()
+            .zip(&self.fractions)
+            .map(|(node, fraction)| node.cost as f64 * fraction)
+            .sum();
+        current as u64
+    }
+
+    fn into_outputs(self) -> Vec<T> {
+        self.outputs.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_dry_run() {
+        let mut graph = TaskGraph::<()>::new();
+        let a = graph.add(1, &[], |_| async {});
+        let b = graph.add(2, &[a], |_| async {});
+        let c = graph.add(3, &[], |_| async {});
+        graph.add(4, &[b, c], |_| async {});
+
+        let timeline = graph.dry_run();
+        let steps: Vec<_> = timeline
+            .iter()
+            .filter(|(_, update)| update.is_working())
+            .map(|(id, update)| (id.index(), update.total()))
+            .collect();
+        assert_eq!(steps, [(0, 1), (2, 3), (1, 2), (3, 4)]);
+        assert!(
+            timeline
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .all(|(_, update)| update.is_completed())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runs_in_topological_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut graph = TaskGraph::new();
+        let record = |name: &'static str| {
+            let order = order.clone();
+            move |_| async move { order.lock().unwrap().push(name) }
+        };
+        let root = graph.add(1, &[], record("root"));
+        let left = graph.add(1, &[root], record("left"));
+        let right = graph.add(1, &[root], record("right"));
+        graph.add(1, &[left, right], record("join"));
+
+        let outputs = graph.run(1).await.unwrap();
+        assert_eq!(outputs.len(), 4);
+        assert_eq!(*order.lock().unwrap(), ["root", "left", "right", "join"]);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_progress_is_weighted_by_cost() {
+        let mut graph = TaskGraph::new();
+        graph.add(30, &[], |_| async {});
+        graph.add(70, &[], |_| async {});
+
+        let run = graph.run(2);
+        let updates = run.progress();
+        run.await.unwrap();
+
+        let updates: Vec<_> = updates.collect().await;
+        let last = updates.last().unwrap();
+        assert_eq!(last.total(), 100);
+        assert_eq!(last.current(), 100);
+        assert!(last.is_completed());
+    }
+
+    #[tokio::test]
+    async fn test_failed_task_skips_its_dependents() {
+        let mut graph = TaskGraph::new();
+        let compile = graph.add(10, &[], |mut updater| async move {
+            updater.update(4);
+            updater.fail_with_message("checksum mismatch");
+        });
+        let link = graph.add(10, &[compile], |_| async {
+            unreachable!("dependents of a failed task")
+        });
+        graph.add(10, &[link], |_| async {
+            unreachable!("indirect dependents of a failed task")
+        });
+        graph.add(5, &[], |mut updater| async move {
+            updater.update(5);
+            updater.complete();
+        });
+
+        let node_updates = graph.node_updates();
+        let run = graph.run(1);
+        let updates = run.progress();
+        assert_eq!(run.await, Err(CancelReason::DependencyFailed));
+
+        let node_updates: Vec<_> = node_updates
+            .map(|(id, update)| (id.index(), update.current(), update.state()))
+            .collect()
+            .await;
+        assert_eq!(
+            node_updates,
+            [
+                (0, 4, State::Working),
+                (0, 4, State::Failed),
+                (1, 0, State::Cancelled),
+                (2, 0, State::Cancelled),
+                (3, 5, State::Working),
+                (3, 5, State::Completed),
+            ]
+        );
+        let last = updates.collect::<Vec<_>>().await.pop().unwrap();
+        assert!(last.is_cancelled());
+        assert_eq!(last.cancel_reason(), Some(CancelReason::DependencyFailed));
+        assert_eq!(last.current(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_detected_past_a_full_buffer() {
+        let mut graph = TaskGraph::new();
+        let compile = graph.add(100, &[], |mut updater| async move {
+            // More updates than the task's stream buffers, all sent before it is polled.
+            for current in 1..=40 {
+                updater.update(current);
+            }
+            updater.fail_with_message("checksum mismatch");
+        });
+        graph.add(10, &[compile], |_| async {
+            unreachable!("dependents of a failed task")
+        });
+
+        assert_eq!(graph.run(1).await, Err(CancelReason::DependencyFailed));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_propagates_to_running_tasks() {
+        let mut graph = TaskGraph::new();
+        let fetch = graph.add(10, &[], |mut updater| async move {
+            updater.update(10);
+            updater.complete();
+            "fetched"
+        });
+        let build = graph.add(10, &[fetch], |mut updater| async move {
+            updater.update(4);
+            let reason = updater.cancelled().await;
+            updater.cancel_with_reason(reason);
+            "interrupted"
+        });
+        graph.add(10, &[build], |_| async {
+            unreachable!("dependents of a cancelled task")
+        });
+
+        let node_updates = graph.node_updates();
+        let run = graph.run(2);
+        let controller = run.controller();
+        let updates = run.progress();
+        let mut observed = run.progress();
+        let run = tokio::spawn(run);
+
+        // Wait until the second task is running before cancelling the graph.
+        while let Some(update) = observed.next().await {
+            if update.current() == 14 {
+                break;
+            }
+        }
+        controller.cancel(CancelReason::UserRequest);
+        assert_eq!(run.await.unwrap(), Err(CancelReason::UserRequest));
+
+        let node_updates: Vec<_> = node_updates.collect().await;
+        let (node, last) = node_updates.last().unwrap();
+        assert_eq!(*node, build);
+        assert!(last.is_cancelled());
+        assert_eq!(last.cancel_reason(), Some(CancelReason::UserRequest));
+        assert!(node_updates.iter().all(|(node, _)| node.index() < 2));
+
+        let last = updates.collect::<Vec<_>>().await.pop().unwrap();
+        assert!(last.is_cancelled());
+        assert_eq!(last.current(), 14);
+        assert_eq!(last.cancel_reason(), Some(CancelReason::UserRequest));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_before_start() {
+        let mut graph = TaskGraph::new();
+        graph.add(1, &[], |_| async {});
+        let run = graph.run(1);
+        run.controller().cancel(CancelReason::Shutdown);
+
+        assert_eq!(run.await, Err(CancelReason::Shutdown));
+    }
+}
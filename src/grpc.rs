@@ -0,0 +1,335 @@
+//! Progress over gRPC server-streaming calls, with the `tonic` feature.
+//!
+//! A [`ProgressServer`] serves the `progressor.v1.Progress` service defined in
+//! `proto/progressor.proto`, streaming the updates published on a
+//! [`ProgressBus`]. On the other end, [`RemoteProgress`] watches one of those tasks and
+//! implements [`Progress`], so orchestrators observe remote jobs like local ones.
+//!
+//! The server is a plain tower service, so it can be added to a tonic `Server` or any
+//! other HTTP/2 stack. The client works with any tonic `GrpcService`, such as a
+//! `tonic::transport::Channel`.
+
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_broadcast::{InactiveReceiver, Sender, broadcast};
+use futures_core::Stream;
+use futures_util::StreamExt;
+use tonic::{
+    Status,
+    client::{Grpc, GrpcService},
+    codegen::{Body, BoxFuture, BoxStream, Service, StdError, http},
+    server::{NamedService, ServerStreamingService},
+};
+use tonic_prost::ProstCodec;
+
+use crate::{Progress, ProgressBus, ProgressUpdate, State};
+
+/// Messages of the `progressor.v1` protocol, matching `proto/progressor.proto`.
+pub mod proto {
+    /// Asks for the updates of one task.
+    #[derive(Clone, PartialEq, Eq, Hash, prost::Message)]
+    pub struct WatchRequest {
+        /// The tag the task publishes its updates under.
+        #[prost(string, tag = "1")]
+        pub task: String,
+    }
+
+    /// The lifecycle state of a task.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum State {
+        /// See [`crate::State::Working`].
+        Working = 0,
+        /// See [`crate::State::Paused`].
+        Paused = 1,
+        /// See [`crate::State::Completed`].
+        Completed = 2,
+        /// See [`crate::State::Cancelled`].
+        Cancelled = 3,
+        /// See [`crate::State::Failed`].
+        Failed = 4,
+    }
+
+    /// A progress update on the wire.
+    #[derive(Clone, PartialEq, Eq, Hash, prost::Message)]
+    pub struct Update {
+        /// The current progress value.
+        #[prost(uint64, tag = "1")]
+        pub current: u64,
+        /// The total progress value.
+        #[prost(uint64, tag = "2")]
+        pub total: u64,
+        /// The lifecycle state.
+        #[prost(enumeration = "State", tag = "3")]
+        pub state: i32,
+        /// The message, if any.
+        #[prost(string, optional, tag = "4")]
+        pub message: Option<String>,
+        /// The per-task sequence number.
+        #[prost(uint64, tag = "5")]
+        pub sequence: u64,
+    }
+}
+
+/// The fully qualified name of the service.
+const SERVICE: &str = "progressor.v1.Progress";
+
+/// The path of the `Watch` method.
+const WATCH: &str = "/progressor.v1.Progress/Watch";
+
+impl From<&ProgressUpdate> for proto::Update {
+    fn from(update: &ProgressUpdate) -> Self {
+        let state = match update.state() {
+            State::Working => proto::State::Working,
+            State::Paused => proto::State::Paused,
+            State::Completed => proto::State::Completed,
+            State::Cancelled => proto::State::Cancelled,
+            State::Failed => proto::State::Failed,
+        };
+        Self {
+            current: update.current(),
+            total: update.total(),
+            state: state as i32,
+            message: update.message().map(str::to_owned),
+            sequence: update.sequence(),
+        }
+    }
+}
+
+impl From<proto::Update> for ProgressUpdate {
+    fn from(update: proto::Update) -> Self {
+        // Unknown states from newer producers are treated as working.
+        let state = match proto::State::try_from(update.state) {
+            Ok(proto::State::Paused) => State::Paused,
+            Ok(proto::State::Completed) => State::Completed,
+            Ok(proto::State::Cancelled) => State::Cancelled,
+            Ok(proto::State::Failed) => State::Failed,
+            Ok(proto::State::Working) | Err(_) => State::Working,
+        };
+        Self::new(update.total, update.current, state, update.message)
+            .with_sequence(update.sequence)
+    }
+}
+
+/// Serves the updates published on a [`ProgressBus`] as the `progressor.v1.Progress`
+/// gRPC service.
+///
+/// A `Watch` call streams the updates published under the requested task tag from then
+/// on, and ends after the task completes, is cancelled or fails. Publish a task's updates on
+/// the bus with [`ProgressBus::transport`] or [`ProgressBus::updater`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use progressor::{ProgressBus, grpc::ProgressServer, progress};
+///
+/// # async fn example() {
+/// let bus = ProgressBus::new();
+/// let task = progress(100, |mut updater| async move {
+///     for i in 1..=100 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// })
+/// .with_transport(bus.transport("import"));
+///
+/// let service = ProgressServer::new(bus);
+/// // tonic::transport::Server::builder().add_service(service).serve(addr)
+/// # drop((task, service));
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "tonic")))]
+#[derive(Debug, Clone, Default)]
+pub struct ProgressServer {
+    bus: ProgressBus,
+}
+
+impl ProgressServer {
+    /// Creates a server streaming the updates published on `bus`.
+    #[must_use]
+    pub const fn new(bus: ProgressBus) -> Self {
+        Self { bus }
+    }
+}
+
+/// Handles `Watch` calls.
+struct Watch(ProgressBus);
+
+impl ServerStreamingService<proto::WatchRequest> for Watch {
+    type Response = proto::Update;
+    type ResponseStream = BoxStream<proto::Update>;
+    type Future = BoxFuture<tonic::Response<Self::ResponseStream>, Status>;
+
+    fn call(&mut self, request: tonic::Request<proto::WatchRequest>) -> Self::Future {
+        let task = request.into_inner().task;
+        let updates = self.0.subscribe(task.clone());
+        Box::pin(async move {
+            let stream = updates
+                // Tags containing `*` would also match other tags.
+                .filter(move |(tag, _)| core::future::ready(**tag == *task))
+                .scan(false, |finished, (_, update)| {
+                    let item = (!*finished).then(|| {
+                        *finished = update.is_terminal();
+                        Ok(proto::Update::from(&update))
+                    });
+                    core::future::ready(item)
+                });
+            Ok(tonic::Response::new(
+                Box::pin(stream) as Self::ResponseStream
+            ))
+        })
+    }
+}
+
+impl<B> Service<http::Request<B>> for ProgressServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = core::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if request.uri().path() == WATCH {
+            let watch = Watch(self.bus.clone());
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(ProstCodec::default());
+                Ok(grpc.server_streaming(watch, request).await)
+            })
+        } else {
+            Box::pin(async move { Ok(Status::unimplemented(request.uri().path()).into_http()) })
+        }
+    }
+}
+
+impl NamedService for ProgressServer {
+    const NAME: &'static str = SERVICE;
+}
+
+pin_project_lite::pin_project! {
+    /// A task running in another process, observed over gRPC.
+    ///
+    /// Created by [`RemoteProgress::watch`]. The future must be polled to receive
+    /// updates; it resolves to the last update once the remote task completes or is
+    /// cancelled, or to `None` if the stream ended before any update arrived.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tonic")))]
+    #[must_use = "futures do nothing unless polled"]
+    pub struct RemoteProgress {
+        updates: tonic::Streaming<proto::Update>,
+        sender: Sender<ProgressUpdate>,
+        receiver: InactiveReceiver<ProgressUpdate>,
+        last: Option<ProgressUpdate>,
+    }
+}
+
+impl core::fmt::Debug for RemoteProgress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RemoteProgress")
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RemoteProgress {
+    /// Starts watching the task published under `task` on the server behind `service`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the status of the call if the server rejects it.
+    pub async fn watch<T>(service: T, task: impl Into<String>) -> Result<Self, Status>
+    where
+        T: GrpcService<tonic::body::Body>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = tonic::codegen::Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        let mut grpc = Grpc::new(service);
+        grpc.ready()
+            .await
+            .map_err(|error| Status::unknown(format!("service was not ready: {}", error.into())))?;
+        let request = tonic::Request::new(proto::WatchRequest { task: task.into() });
+        let updates = grpc
+            .server_streaming(
+                request,
+                http::uri::PathAndQuery::from_static(WATCH),
+                ProstCodec::default(),
+            )
+            .await?
+            .into_inner();
+        let (mut sender, receiver) = broadcast(32);
+        sender.set_overflow(true);
+        Ok(Self {
+            updates,
+            sender,
+            receiver: receiver.deactivate(),
+            last: None,
+        })
+    }
+}
+
+impl Future for RemoteProgress {
+    type Output = Result<Option<ProgressUpdate>, Status>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        loop {
+            match core::task::ready!(Pin::new(&mut *this.updates).poll_next(cx)) {
+                Some(Ok(update)) => {
+                    let update = ProgressUpdate::from(update);
+                    let _ = this.sender.try_broadcast(update.clone());
+                    let finished = update.is_terminal();
+                    *this.last = Some(update);
+                    if finished {
+                        return Poll::Ready(Ok(this.last.take()));
+                    }
+                }
+                Some(Err(status)) => return Poll::Ready(Err(status)),
+                None => return Poll::Ready(Ok(this.last.take())),
+            }
+        }
+    }
+}
+
+impl Progress for RemoteProgress {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.receiver.activate_cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress;
+
+    #[tokio::test]
+    async fn test_watch_in_memory() {
+        let bus = ProgressBus::new();
+        // The call is accepted, and the server subscribed, before the task starts.
+        let remote = RemoteProgress::watch(ProgressServer::new(bus.clone()), "job")
+            .await
+            .unwrap();
+
+        let task = progress(3, |mut updater| async move {
+            updater.update_with_message(1, "first");
+            updater.pause();
+            updater.complete();
+        })
+        .with_transport(bus.transport("job"));
+        tokio::spawn(task);
+
+        let updates = remote.progress();
+        let last = remote.await.unwrap().unwrap();
+        assert!(last.is_completed());
+
+        let states: Vec<_> = updates.map(|update| update.state()).collect().await;
+        assert_eq!(states, [State::Working, State::Paused, State::Completed]);
+    }
+}
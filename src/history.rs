@@ -0,0 +1,258 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::{ProgressUpdate, State, sync::lock};
+
+/// The progress of a task at one point in time, recorded by a [`History`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    at: Instant,
+    current: u64,
+    total: u64,
+    state: State,
+}
+
+impl Sample {
+    /// Returns when the update was recorded.
+    #[must_use]
+    pub const fn at(&self) -> Instant {
+        self.at
+    }
+
+    /// Returns the current value of the update.
+    #[must_use]
+    pub const fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Returns the total of the update.
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the state of the update.
+    #[must_use]
+    pub const fn state(&self) -> State {
+        self.state
+    }
+}
+
+/// The recent progress of a task, retained for a period of time.
+///
+/// A history records the updates of a stream passed through [`record`](Self::record),
+/// and answers questions about the recent past: where the task stood some time
+/// [`ago`](Self::ago), how fast it moved over a recent window with
+/// [`rate`](Self::rate), or every retained [sample](Self::samples) to draw a speed
+/// graph. Samples older than the retention period are discarded, except the one still
+/// in effect at its start. The handle is cheap to clone; all clones share the samples.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{History, Progress, progress};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let task = progress(1 << 30, |mut updater| async move {
+///     for received in (1..=1024).map(|chunk| chunk << 20) {
+///         updater.update(received);
+///         tokio::time::sleep(Duration::from_millis(10)).await;
+///     }
+///     updater.complete();
+/// });
+/// let history = History::new(Duration::from_mins(1));
+/// let mut updates = history.record(task.progress());
+/// tokio::spawn(task);
+///
+/// while updates.next().await.is_some() {
+///     if let Some(rate) = history.rate(Duration::from_secs(5)) {
+///         println!("{:.1} MiB/s over the last 5 seconds", rate / f64::from(1 << 20));
+///     }
+///     if let Some(then) = history.ago(Duration::from_secs(30)) {
+///         println!("{} bytes received 30 seconds ago", then.current());
+///     }
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct History {
+    retention: Duration,
+    samples: Arc<Mutex<VecDeque<Sample>>>,
+}
+
+impl History {
+    /// Creates an empty history retaining samples for `retention`.
+    #[must_use]
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            samples: Arc::default(),
+        }
+    }
+
+    /// Returns how long samples are retained.
+    #[must_use]
+    pub const fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Passes the updates of `stream` through, recording each one.
+    pub fn record<S>(&self, stream: S) -> impl Stream<Item = ProgressUpdate> + use<S>
+    where
+        S: Stream<Item = ProgressUpdate>,
+    {
+        let history = self.clone();
+        stream.inspect(move |update| history.push(update))
+    }
+
+    /// Records `update` as of now.
+    pub fn push(&self, update: &ProgressUpdate) {
+        self.push_at(update, Instant::now());
+    }
+
+    /// Returns the retained samples, oldest first.
+    #[must_use]
+    pub fn samples(&self) -> Vec<Sample> {
+        self.lock().iter().copied().collect()
+    }
+
+    /// Returns the most recent sample.
+    #[must_use]
+    pub fn latest(&self) -> Option<Sample> {
+        self.lock().back().copied()
+    }
+
+    /// Returns the sample in effect at `at`: the last one recorded at or before it.
+    #[must_use]
+    pub fn at(&self, at: Instant) -> Option<Sample> {
+        self.lock()
+            .iter()
+            .take_while(|sample| sample.at <= at)
+            .last()
+            .copied()
+    }
+
+    /// Returns the sample in effect `duration` ago.
+    #[must_use]
+    pub fn ago(&self, duration: Duration) -> Option<Sample> {
+        self.at(Instant::now().checked_sub(duration)?)
+    }
+
+    /// Returns the rate, in units per second, over the last `window`.
+    ///
+    /// If the retained samples cover less than `window`, the rate is measured since the
+    /// oldest one. Returns `None` without samples.
+    #[must_use]
+    pub fn rate(&self, window: Duration) -> Option<f64> {
+        self.rate_at(window, Instant::now())
+    }
+
+    fn push_at(&self, update: &ProgressUpdate, now: Instant) {
+        let mut samples = self.lock();
+        samples.push_back(Sample {
+            at: now,
+            current: update.current(),
+            total: update.total(),
+            state: update.state(),
+        });
+        if let Some(start) = now.checked_sub(self.retention) {
+            // Keep the sample in effect at the start of the retention period.
+            while samples.len() > 1 && samples[1].at <= start {
+                samples.pop_front();
+            }
+        }
+        drop(samples);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn rate_at(&self, window: Duration, now: Instant) -> Option<f64> {
+        let samples = self.lock();
+        let latest = samples.back()?.current;
+        let start = now.checked_sub(window);
+        let (from, baseline) = samples
+            .iter()
+            .take_while(|sample| start.is_some_and(|start| sample.at <= start))
+            .last()
+            .map_or_else(
+                || samples.front().map(|oldest| (oldest.at, oldest.current)),
+                |sample| start.map(|start| (start, sample.current)),
+            )?;
+        drop(samples);
+        let elapsed = now.saturating_duration_since(from).as_secs_f64();
+        (elapsed > 0.0).then(|| latest.saturating_sub(baseline) as f64 / elapsed)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Sample>> {
+        lock(&self.samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+
+    fn update(current: u64) -> ProgressUpdate {
+        ProgressUpdate::new(1000, current, State::Working, None)
+    }
+
+    #[test]
+    fn test_queries() {
+        let history = History::new(Duration::from_mins(1));
+        let start = Instant::now();
+        for (second, current) in [(0, 0), (10, 100), (20, 300), (30, 600)] {
+            history.push_at(&update(current), start + Duration::from_secs(second));
+        }
+
+        let at = |second| history.at(start + Duration::from_secs(second)).unwrap();
+        assert_eq!(at(15).current(), 100);
+        assert_eq!(at(20).current(), 300);
+        let now = start + Duration::from_secs(40);
+        // 600 at 40s against 300 in effect at 20s.
+        let rate = history.rate_at(Duration::from_secs(20), now).unwrap();
+        assert!((rate - 15.0).abs() < 1e-9, "{rate}");
+        // Only 40 seconds are retained, so the rate is measured since the first sample.
+        let rate = history.rate_at(Duration::from_mins(2), now).unwrap();
+        assert!((rate - 15.0).abs() < 1e-9, "{rate}");
+    }
+
+    #[test]
+    fn test_retention() {
+        let history = History::new(Duration::from_secs(15));
+        let start = Instant::now();
+        for (second, current) in [(0, 0), (10, 100), (20, 300), (30, 600)] {
+            history.push_at(&update(current), start + Duration::from_secs(second));
+        }
+
+        let retained: Vec<_> = history.samples().iter().map(Sample::current).collect();
+        assert_eq!(retained, [100, 300, 600]);
+        assert_eq!(history.latest().map(|sample| sample.current()), Some(600));
+    }
+
+    #[tokio::test]
+    async fn test_record() {
+        let task = progress(3, |mut updater| async move {
+            updater.update(1);
+            updater.update(3);
+            updater.complete();
+        });
+        let history = History::new(Duration::from_mins(1));
+        let updates = history.record(task.progress());
+        task.await;
+
+        assert_eq!(updates.count().await, 3);
+        assert_eq!(history.samples().len(), 3);
+        assert_eq!(history.latest().unwrap().state(), State::Completed);
+        assert!(history.ago(Duration::from_hours(1)).is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::ProgressUpdate;
+
+/// Infers a provisional total for work whose total is unknown.
+///
+/// Used by [`ProgressStreamExt::infer_total`](crate::ProgressStreamExt::infer_total).
+/// This is implemented for closures taking the elapsed time and the current value, so a
+/// domain-specific guess can be plugged in directly.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub trait TotalEstimator {
+    /// Returns the estimated total after `elapsed` time at progress `current`, if any.
+    fn estimate(&mut self, elapsed: Duration, current: u64) -> Option<u64>;
+}
+
+impl<F> TotalEstimator for F
+where
+    F: FnMut(Duration, u64) -> Option<u64>,
+{
+    fn estimate(&mut self, elapsed: Duration, current: u64) -> Option<u64> {
+        self(elapsed, current)
+    }
+}
+
+/// Estimates the total from how quickly the rate of progress slows down.
+///
+/// Work such as scanning a tree or draining a backlog often slows down as it runs out.
+/// This measures the rate over consecutive windows and, while the rate keeps falling,
+/// models it as exponential decay: the remaining work is the current rate divided by
+/// the decay constant. While the rate is steady or rising there is no estimate.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct DecelerationEstimator {
+    window: Duration,
+    sample: Option<(Duration, u64)>,
+    rate: Option<f64>,
+    estimate: Option<u64>,
+}
+
+impl DecelerationEstimator {
+    /// Creates an estimator that measures the rate over windows of `window`.
+    #[must_use]
+    pub const fn new(window: Duration) -> Self {
+        Self {
+            window,
+            sample: None,
+            rate: None,
+            estimate: None,
+        }
+    }
+}
+
+impl Default for DecelerationEstimator {
+    /// Measures the rate over one-second windows.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+impl TotalEstimator for DecelerationEstimator {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn estimate(&mut self, elapsed: Duration, current: u64) -> Option<u64> {
+        let (sampled_at, sampled) = *self.sample.get_or_insert((Duration::ZERO, 0));
+        let span = elapsed.saturating_sub(sampled_at);
+        if span < self.window || span.is_zero() {
+            return self.estimate;
+        }
+        let span = span.as_secs_f64();
+        let rate = current.saturating_sub(sampled) as f64 / span;
+        if let Some(previous) = self.rate
+            && rate > 0.0
+            && rate < previous
+        {
+            // Rates are window averages, centred half a window before the sample.
+            let decay = (previous / rate).ln() / span;
+            let rate_now = rate * (-decay * span / 2.0).exp();
+            let remaining = rate_now / decay;
+            self.estimate = Some(current.saturating_add(remaining.round() as u64));
+        }
+        self.sample = Some((elapsed, current));
+        self.rate = Some(rate);
+        self.estimate
+    }
+}
+
+/// Fills in estimated totals for updates whose total is unknown.
+pub fn infer_total<S, E>(updates: S, mut estimator: E) -> impl Stream<Item = ProgressUpdate>
+where
+    S: Stream<Item = ProgressUpdate>,
+    E: TotalEstimator,
+{
+    let mut started = None;
+    updates.map(move |update| {
+        let elapsed = started.get_or_insert_with(Instant::now).elapsed();
+        if update.total() != 0 || !update.is_working() {
+            return update;
+        }
+        match estimator.estimate(elapsed, update.current()) {
+            Some(total) => {
+                let total = total.max(update.current());
+                update.with_estimated_total(total)
+            }
+            None => update,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn test_deceleration_estimate() {
+        let total = 10_000.0_f64;
+        let mut estimator = DecelerationEstimator::new(Duration::from_secs(1));
+        let mut estimate = None;
+        for tenth in 0..=40 {
+            let elapsed = Duration::from_millis(tenth * 100);
+            let current = total * (1.0 - (-0.5 * elapsed.as_secs_f64()).exp());
+            estimate = estimator.estimate(elapsed, current as u64);
+        }
+        let estimate = estimate.unwrap();
+        assert!(estimate.abs_diff(10_000) < 500, "estimated {estimate}");
+    }
+
+    #[test]
+    fn test_no_estimate_at_steady_rate() {
+        let mut estimator = DecelerationEstimator::default();
+        for second in 0..10 {
+            let estimate = estimator.estimate(Duration::from_secs(second), second * 100);
+            assert_eq!(estimate, None);
+        }
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This crate provides types and utilities for tracking progress of long-running operations
 //! in an async context. It uses Rust's `Stream` API to emit progress updates with support for
-//! different states (working, paused, completed, cancelled).
+//! different states (working, paused, completed, cancelled, failed).
 //!
 //! # Features
 //!
@@ -124,6 +124,7 @@
 //!         State::Paused => println!("Paused at {}%", (update.completed_fraction() * 100.0) as u32),
 //!         State::Completed => println!("Completed!"),
 //!         State::Cancelled => println!("Cancelled!"),
+//!         State::Failed => println!("Failed: {}", update.message().unwrap_or_default()),
 //!     }
 //! })
 //! .await;
@@ -135,16 +136,300 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "std")]
+mod actor;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use actor::{ActorJob, ProgressMessage, ProgressReporter, actor_job};
+#[cfg(feature = "std")]
+mod alarm;
+#[cfg(feature = "std")]
+mod bus;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use bus::{ProgressBus, TaggedUpdate};
+#[cfg(feature = "std")]
+mod compact;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use compact::{CompactProgress, CompactUpdate, CounterUpdate};
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub use compression::{CompressionProgress, CountingReader};
+#[cfg(feature = "std")]
+mod counting;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use counting::CountingUpdater;
+#[cfg(feature = "zbus")]
+mod dbus;
+#[cfg(feature = "zbus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zbus")))]
+pub use dbus::{JobView, LauncherEntry};
+#[cfg(feature = "std")]
+mod detach;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use detach::{Detached, DetachedWork};
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+#[cfg(feature = "diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+pub use diagnostics::{DiagnosticEvent, DiagnosticKind, Diagnostics};
+mod diff;
+pub use diff::UpdateDelta;
+#[cfg(feature = "std")]
+mod discover;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use discover::DiscoveringUpdater;
+#[cfg(feature = "std")]
+mod estimate;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use estimate::{estimate, estimate_with};
+#[cfg(feature = "std")]
+mod eta;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use eta::EstimatedUpdate;
 mod ext;
 pub use ext::ProgressExt;
 #[cfg(feature = "std")]
+mod fanout;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use fanout::{Fanout, OverflowPolicy};
+#[cfg(feature = "git2")]
+mod git;
+#[cfg(feature = "git2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "git2")))]
+pub use git::GitProgress;
+#[cfg(feature = "std")]
+mod granularity;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use granularity::Granularity;
+#[cfg(feature = "std")]
+mod graph;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use graph::{NodeId, TaskGraph};
+#[cfg(feature = "tonic")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tonic")))]
+pub mod grpc;
+#[cfg(feature = "std")]
+mod history;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use history::{History, Sample};
+#[cfg(feature = "std")]
+mod infer;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use infer::{DecelerationEstimator, TotalEstimator};
+mod message;
+pub use message::{KeyedMessage, Localizer};
+#[cfg(feature = "rumqttc")]
+mod mqtt;
+#[cfg(feature = "rumqttc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rumqttc")))]
+pub use mqtt::MqttPublisher;
+#[cfg(feature = "std")]
+mod notification;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use notification::{NotificationAdapter, NotificationProgress};
+#[cfg(all(feature = "objc2", target_os = "macos"))]
+mod nsprogress;
+#[cfg(all(feature = "objc2", target_os = "macos"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "objc2", target_os = "macos"))))]
+pub use nsprogress::NsProgress;
+#[cfg(feature = "std")]
+mod pacing;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use pacing::PacingProvider;
+#[cfg(feature = "system-pacing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "system-pacing")))]
+pub use pacing::SystemPacing;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use policy::MessagePolicy;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use pool::{PoolStatus, PoolTask, ProgressPool};
+#[cfg(feature = "governor")]
+mod ratelimit;
+#[cfg(feature = "governor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "governor")))]
+pub use ratelimit::RateLimit;
+#[cfg(feature = "std")]
+mod redact;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use redact::Redactor;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod render;
+mod runner;
+#[cfg(feature = "std")]
+mod scheduler;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use scheduler::{ScheduleEvent, Scheduler};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod shutdown;
+#[cfg(feature = "std")]
+mod sink;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use sink::{ProgressSink, ProgressSinkExt};
+#[cfg(feature = "std")]
+mod store;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use store::{DurationStore, MemoryDurationStore};
+mod stream;
+pub use stream::ProgressStreamExt;
+#[cfg(feature = "std")]
+mod summary;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use summary::ProgressSummary;
+#[cfg(feature = "std")]
+mod supervisor;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use supervisor::{Supervisor, SupervisorEvent};
+#[cfg(feature = "std")]
+mod suspend;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod test_util;
+#[cfg(feature = "std")]
+mod timebased;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use timebased::TimebasedUpdater;
+#[cfg(feature = "std")]
+mod transitions;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use transitions::{Transition, TransitionLog};
+#[cfg(feature = "std")]
+mod transport;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use transport::Transport;
+#[cfg(feature = "std")]
+mod unwind;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use unwind::{CatchPanic, PanicLocation, PanicPolicy, TaskPanic};
+#[cfg(feature = "std")]
 mod updater;
-
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-pub use updater::{ProgressUpdater, progress};
+pub use updater::{
+    BudgetAction, ProgressController, ProgressFuture, ProgressHandle, ProgressUpdater, progress,
+    progress_indeterminate, progress_inline, set_max_observer_depth,
+};
+#[cfg(feature = "std")]
+mod validate;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use validate::{ProgressValidator, Violation};
+#[cfg(feature = "std")]
+mod verify;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use verify::{DeliveryReport, DeliveryVerifier};
 
-use core::future::Future;
+/// Implements [`Progress`] for a wrapper by forwarding to its tracked field.
+///
+/// # Examples
+///
+/// ```
+/// use core::{future::Future, pin::Pin, task::{Context, Poll}};
+/// use progressor::{Progress, ProgressFuture, progress};
+///
+/// #[derive(Progress)]
+/// struct Download {
+///     url: String,
+///     #[progress]
+///     task: ProgressFuture<Pin<Box<dyn Future<Output = Vec<u8>> + Send>>>,
+/// }
+///
+/// impl Future for Download {
+///     type Output = Vec<u8>;
+///
+///     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<u8>> {
+///         Pin::new(&mut self.task).poll(cx)
+///     }
+/// }
+///
+/// let download = Download {
+///     url: "https://example.com".to_owned(),
+///     task: progress(3, |mut updater| {
+///         Box::pin(async move {
+///             updater.update(3);
+///             vec![1_u8, 2, 3]
+///         }) as Pin<Box<dyn Future<Output = _> + Send>>
+///     }),
+/// };
+/// let updates = download.progress();
+/// # drop(updates);
+/// ```
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use progressor_macros::Progress;
+
+/// Turns an `async fn` into a function returning `impl Progress`, with an `updater` in scope.
+///
+/// # Examples
+///
+/// ```
+/// #[progressor::tracked(total = items.len() as u64)]
+/// async fn process(items: Vec<u32>) -> u32 {
+///     let mut sum = 0;
+///     for (i, item) in items.iter().enumerate() {
+///         sum += item;
+///         updater.update(i as u64 + 1);
+///     }
+///     sum
+/// }
+///
+/// # async fn example() {
+/// use progressor::ProgressExt;
+///
+/// let sum = process(vec![1, 2, 3])
+///     .observe(|update| println!("{}/{}", update.current(), update.total()))
+///     .await;
+/// assert_eq!(sum, 6);
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(example());
+/// ```
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use progressor_macros::tracked;
+
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    pub use futures_core::Stream;
+}
+
+use core::{future::Future, ops::DerefMut, pin::Pin};
 use futures_core::Stream;
 
 /// A trait for futures that can report progress updates.
@@ -157,6 +442,157 @@ pub trait Progress: Future {
     /// The stream will emit [`ProgressUpdate`] instances as the operation progresses.
     /// The stream should be polled concurrently with the future to receive updates.
     fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static;
+
+    /// Returns a handle to the log of state changes of this operation.
+    ///
+    /// The handle stays valid after the operation finished. Implementations that do not
+    /// record transitions return an empty log.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn transitions(&self) -> TransitionLog {
+        TransitionLog::default()
+    }
+
+    /// Returns a controller for this operation, if it has one.
+    ///
+    /// The controller stays valid after the operation finished, and reads how it ended
+    /// through [`ProgressController::final_state`] even if its progress stream dropped
+    /// the final update. Implementations without a controller return `None`.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn controller(&self) -> Option<ProgressController> {
+        None
+    }
+
+    /// Returns a handle to the delivery diagnostics of this operation.
+    ///
+    /// The handle stays valid after the operation finished. Implementations that do not
+    /// record diagnostics return empty ones.
+    #[cfg(feature = "diagnostics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+    fn diagnostics(&self) -> Diagnostics {
+        Diagnostics::default()
+    }
+
+    /// Returns a future that resolves to whether this operation completed within `duration`.
+    ///
+    /// Watchdogs can learn whether a task finished in time this way while its owner keeps
+    /// awaiting its output. The future resolves to `true` as soon as a
+    /// [`Completed`](State::Completed) update arrives, and to `false` once `duration`
+    /// elapsed or the operation ended otherwise. Only updates reported after the call
+    /// count, so call it before the operation finishes, and poll the future alongside the
+    /// operation, such as by spawning it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use progressor::{Progress, progress};
+    ///
+    /// # async fn example() {
+    /// let backup = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// });
+    /// let in_time = backup.completed_within(Duration::from_secs(3600));
+    /// tokio::spawn(async move {
+    ///     if !in_time.await {
+    ///         eprintln!("backup did not finish within an hour");
+    ///     }
+    /// });
+    /// backup.await;
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn completed_within(
+        &self,
+        duration: core::time::Duration,
+    ) -> impl Future<Output = bool> + Send + 'static + use<Self> {
+        use futures_util::{
+            StreamExt,
+            future::{Either, select},
+        };
+
+        // Boxing erases the borrow of `self` that the type of the stream carries.
+        let updates: Pin<Box<dyn Stream<Item = ProgressUpdate> + Send>> = Box::pin(self.progress());
+        let completed = updates.any(|update| core::future::ready(update.is_completed()));
+        let timeout = futures_timer::Delay::new(duration);
+        async move {
+            match select(core::pin::pin!(completed), timeout).await {
+                Either::Left((completed, _)) => completed,
+                Either::Right(_) => false,
+            }
+        }
+    }
+}
+
+impl<F: Progress + Unpin + ?Sized> Progress for &mut F {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        (**self).progress()
+    }
+
+    #[cfg(feature = "std")]
+    fn transitions(&self) -> TransitionLog {
+        (**self).transitions()
+    }
+
+    #[cfg(feature = "std")]
+    fn controller(&self) -> Option<ProgressController> {
+        (**self).controller()
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&self) -> Diagnostics {
+        (**self).diagnostics()
+    }
+}
+
+impl<F: Progress + Unpin + ?Sized> Progress for Box<F> {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        (**self).progress()
+    }
+
+    #[cfg(feature = "std")]
+    fn transitions(&self) -> TransitionLog {
+        (**self).transitions()
+    }
+
+    #[cfg(feature = "std")]
+    fn controller(&self) -> Option<ProgressController> {
+        (**self).controller()
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&self) -> Diagnostics {
+        (**self).diagnostics()
+    }
+}
+
+impl<P> Progress for Pin<P>
+where
+    P: DerefMut<Target: Progress>,
+{
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        (**self).progress()
+    }
+
+    #[cfg(feature = "std")]
+    fn transitions(&self) -> TransitionLog {
+        (**self).transitions()
+    }
+
+    #[cfg(feature = "std")]
+    fn controller(&self) -> Option<ProgressController> {
+        (**self).controller()
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&self) -> Diagnostics {
+        (**self).diagnostics()
+    }
 }
 
 /// Represents a single progress update with current status, total, and optional metadata.
@@ -174,6 +610,63 @@ pub struct ProgressUpdate {
     total: u64,
     state: State,
     message: Option<String>,
+    priority: Priority,
+    stable_fraction: Option<u32>,
+    outcomes: Option<Outcomes>,
+    extra_state: Option<&'static str>,
+    keyed_message: Option<KeyedMessage>,
+    speed: Option<u32>,
+    cancel_reason: Option<CancelReason>,
+    sequence: u64,
+    /// `None` while nothing is in flight; shared by the updates of a task.
+    active_items: Option<std::sync::Arc<[String]>>,
+    ratio: Option<u64>,
+    secondary: Option<Dimension>,
+    estimated_total: bool,
+    unit_cost: Option<u64>,
+    event: Option<ProgressEvent>,
+    last_progress_age: Option<core::time::Duration>,
+    queue_wait: Option<core::time::Duration>,
+    callsite: Callsite,
+}
+
+/// Where an update was reported, see [`ProgressUpdate::callsite`]; nothing unless enabled.
+///
+/// The same update reported from two places is still the same update, so all callsites
+/// compare equal and hash alike.
+#[derive(Debug, Clone, Copy)]
+struct Callsite {
+    #[cfg(all(feature = "debug-callsite", debug_assertions))]
+    location: Option<&'static core::panic::Location<'static>>,
+}
+
+const NO_CALLSITE: Callsite = Callsite {
+    #[cfg(all(feature = "debug-callsite", debug_assertions))]
+    location: None,
+};
+
+impl PartialEq for Callsite {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Callsite {}
+
+impl PartialOrd for Callsite {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Callsite {
+    fn cmp(&self, _: &Self) -> core::cmp::Ordering {
+        core::cmp::Ordering::Equal
+    }
+}
+
+impl core::hash::Hash for Callsite {
+    fn hash<H: core::hash::Hasher>(&self, _: &mut H) {}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -187,6 +680,8 @@ pub enum State {
     Paused,
     /// The operation has been cancelled.
     Cancelled,
+    /// The operation has failed; the message of the update describes the error.
+    Failed,
 }
 
 impl State {
@@ -213,6 +708,272 @@ impl State {
     pub const fn is_paused(&self) -> bool {
         matches!(self, Self::Paused)
     }
+
+    /// Returns `true` if the state is [`Failed`](State::Failed).
+    #[must_use]
+    pub const fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed)
+    }
+
+    /// Returns `true` if the state is final: [`Completed`](State::Completed),
+    /// [`Cancelled`](State::Cancelled) or [`Failed`](State::Failed).
+    #[must_use]
+    pub const fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Cancelled | Self::Failed)
+    }
+
+    /// Encodes the state for atomic storage, reserving zero for "no state".
+    #[cfg(feature = "std")]
+    const fn to_u8(self) -> u8 {
+        self as u8 + 1
+    }
+
+    #[cfg(feature = "std")]
+    const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Working),
+            2 => Some(Self::Completed),
+            3 => Some(Self::Paused),
+            4 => Some(Self::Cancelled),
+            5 => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Counts of processed items by outcome.
+///
+/// Batch jobs use these to report results such as "9,800 ok / 12 failed / 188 skipped"
+/// alongside the overall progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Outcomes {
+    succeeded: u64,
+    failed: u64,
+    skipped: u64,
+}
+
+impl Outcomes {
+    /// Creates a set of outcome counts.
+    #[must_use]
+    pub const fn new(succeeded: u64, failed: u64, skipped: u64) -> Self {
+        Self {
+            succeeded,
+            failed,
+            skipped,
+        }
+    }
+
+    /// Returns the number of items that succeeded.
+    #[must_use]
+    pub const fn succeeded(&self) -> u64 {
+        self.succeeded
+    }
+
+    /// Returns the number of items that failed.
+    #[must_use]
+    pub const fn failed(&self) -> u64 {
+        self.failed
+    }
+
+    /// Returns the number of items that were skipped.
+    #[must_use]
+    pub const fn skipped(&self) -> u64 {
+        self.skipped
+    }
+}
+
+/// A second counter tracked alongside the main progress of an update.
+///
+/// File transfers, for example, report bytes as the main progress and use this for the
+/// number of files, see [`ProgressUpdate::secondary`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dimension {
+    current: u64,
+    total: u64,
+}
+
+impl Dimension {
+    /// Creates a counter at `current` out of `total`.
+    #[must_use]
+    pub const fn new(current: u64, total: u64) -> Self {
+        Self { current, total }
+    }
+
+    /// Returns the amount done.
+    #[must_use]
+    pub const fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Returns the total amount expected.
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the amount left, saturating at zero.
+    #[must_use]
+    pub const fn remaining(&self) -> u64 {
+        self.total.saturating_sub(self.current)
+    }
+
+    /// Returns the completion fraction between 0.0 and 1.0, or 0.0 if the total is 0.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn completed_fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.current as f64 / self.total as f64
+        }
+    }
+}
+
+/// Stable fractions are stored in millionths so updates stay `Eq` and `Hash`.
+const STABLE_FRACTION_SCALE: f64 = 1_000_000.0;
+
+/// Speeds are stored in thousandths for the same reason.
+const SPEED_SCALE: f64 = 1_000.0;
+
+/// Ratios are stored in millionths for the same reason.
+const RATIO_SCALE: f64 = 1_000_000.0;
+
+/// Returns the cost of `units` at the unit cost stored as the bits of an `f64`.
+///
+/// Costs are kept as bits rather than scaled like fractions, because costs per call or
+/// per byte are often far below any fixed scale. They are never negative, so the bits
+/// order like the costs.
+#[allow(clippy::cast_precision_loss)]
+fn cost_of(unit_cost_bits: u64, units: u64) -> f64 {
+    f64::from_bits(unit_cost_bits) * units as f64
+}
+
+/// Why a progress-tracked operation was cancelled.
+///
+/// The reason is carried by the [`Cancelled`](State::Cancelled) update, so observers and
+/// logs can explain why something stopped rather than just that it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CancelReason {
+    /// A user asked for the operation to stop.
+    UserRequest,
+    /// The operation took longer than it was allowed to.
+    Timeout,
+    /// The application is shutting down.
+    Shutdown,
+    /// Work the operation depended on has failed.
+    ///
+    /// A [`TaskGraph`] reports the tasks it skipped because a
+    /// dependency failed with this reason, and resolves to it.
+    DependencyFailed,
+}
+
+impl CancelReason {
+    /// Encodes the reason for atomic storage, reserving zero for "no reason".
+    #[cfg(feature = "std")]
+    const fn to_u8(self) -> u8 {
+        self as u8 + 1
+    }
+
+    #[cfg(feature = "std")]
+    const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::UserRequest),
+            2 => Some(Self::Timeout),
+            3 => Some(Self::Shutdown),
+            4 => Some(Self::DependencyFailed),
+            _ => None,
+        }
+    }
+}
+
+/// A request from a task to whoever observes it, carried by a [`ProgressUpdate`].
+///
+/// Requests and their answers form a small protocol multiplexed on the progress
+/// channel, for wizards and command-line tools wrapping long jobs that occasionally
+/// need a decision. Each request has an `id`, unique within its task, that the answer
+/// refers to; the task stays [`Paused`](State::Paused) until it is answered.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProgressEvent {
+    /// The task waits for an observer to approve or deny an action, see
+    /// [`ProgressUpdater::await_confirmation`].
+    ///
+    /// Answer with [`ProgressController::approve`] or [`ProgressController::deny`].
+    NeedsConfirmation {
+        /// Identifies the request.
+        id: u64,
+        /// The question to show, such as `"Overwrite existing files?"`.
+        prompt: String,
+    },
+    /// The task waits for an observer to provide a value, see
+    /// [`ProgressUpdater::request_input`].
+    ///
+    /// Answer with [`ProgressController::respond`].
+    NeedsInput {
+        /// Identifies the request.
+        id: u64,
+        /// What to ask for, such as `"Name of the new database?"`.
+        prompt: String,
+    },
+    /// The task used up its active time budget and is suspended until
+    /// [`ProgressController::extend_budget`] grants more, see
+    /// [`ProgressFuture::with_time_budget`].
+    BudgetExhausted {
+        /// The budget that was used up.
+        budget: core::time::Duration,
+    },
+}
+
+/// A hint from observers about how fast a task should proceed.
+///
+/// Observers cannot force a task to slow down, but they can ask it to. This is useful
+/// for polite background work, for example when the window showing the progress has
+/// been minimized and the user no longer waits on the result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Pacing {
+    /// Proceed at the normal pace.
+    #[default]
+    Normal,
+    /// Reduce resource usage, for example by yielding more often or lowering concurrency.
+    Slow,
+    /// Someone is actively waiting; proceed as fast as possible.
+    Fast,
+}
+
+impl Pacing {
+    #[cfg(feature = "std")]
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Slow,
+            2 => Self::Fast,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// The relative importance of a progress-tracked task.
+///
+/// Priorities are carried by every [`ProgressUpdate`], so observers can sort tasks or
+/// decide which work to preempt. Variants are ordered from lowest to highest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background work that can wait.
+    Low,
+    /// The default priority.
+    #[default]
+    Normal,
+    /// Work that someone is waiting on.
+    High,
+}
+
+impl Priority {
+    #[cfg(feature = "std")]
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Low,
+            2 => Self::High,
+            _ => Self::Normal,
+        }
+    }
 }
 
 impl ProgressUpdate {
@@ -229,9 +990,33 @@ impl ProgressUpdate {
             total,
             state,
             message,
+            priority: Priority::Normal,
+            stable_fraction: None,
+            outcomes: None,
+            extra_state: None,
+            keyed_message: None,
+            speed: None,
+            cancel_reason: None,
+            sequence: 0,
+            active_items: None,
+            ratio: None,
+            secondary: None,
+            estimated_total: false,
+            unit_cost: None,
+            event: None,
+            last_progress_age: None,
+            queue_wait: None,
+            callsite: NO_CALLSITE,
         }
     }
 
+    /// Sets the priority of the task this update belongs to.
+    #[must_use]
+    pub const fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Returns the total expected value when the operation will be complete.
     #[must_use]
     pub const fn total(&self) -> u64 {
@@ -259,6 +1044,161 @@ impl ProgressUpdate {
         }
     }
 
+    /// Returns the completion fraction, or `None` while the progress is
+    /// [indeterminate](Self::is_indeterminate).
+    ///
+    /// Unlike [`completed_fraction`](Self::completed_fraction), this tells an unknown
+    /// total apart from no progress, so interfaces can render a spinner instead of an
+    /// empty bar. A completed operation with an unknown total counts as 1.0.
+    #[must_use]
+    pub fn known_fraction(&self) -> Option<f64> {
+        if self.is_indeterminate() {
+            None
+        } else if self.total == 0 {
+            Some(1.0)
+        } else {
+            Some(self.completed_fraction())
+        }
+    }
+
+    /// Returns `true` if the total is unknown and the operation has not completed.
+    ///
+    /// Tasks created with [`progress_indeterminate`] or reporting a total of zero, such
+    /// as streaming downloads without a known length, only count what they did so far.
+    #[must_use]
+    pub const fn is_indeterminate(&self) -> bool {
+        self.total == 0 && !self.is_completed()
+    }
+
+    /// Returns a smoothed completion fraction between 0.0 and 1.0.
+    ///
+    /// Producers whose total keeps changing, such as a
+    /// [`DiscoveringUpdater`], attach a smoothed fraction that
+    /// does not jump back every time new work is found. For all other updates this is the
+    /// same as [`completed_fraction`](Self::completed_fraction).
+    #[must_use]
+    pub fn stable_fraction(&self) -> f64 {
+        self.stable_fraction.map_or_else(
+            || self.completed_fraction(),
+            |millionths| f64::from(millionths) / STABLE_FRACTION_SCALE,
+        )
+    }
+
+    /// Attaches a smoothed completion fraction, see [`stable_fraction`](Self::stable_fraction).
+    ///
+    /// The value is clamped to the range 0.0 to 1.0.
+    #[must_use]
+    pub fn with_stable_fraction(mut self, fraction: f64) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let millionths = (fraction.clamp(0.0, 1.0) * STABLE_FRACTION_SCALE).round() as u32;
+        self.stable_fraction = Some(millionths);
+        self
+    }
+
+    /// Returns the processing speed relative to real time, if the producer reports one.
+    ///
+    /// Time-based producers such as a [`TimebasedUpdater`] set this,
+    /// where `1.7` means the input is processed 1.7 times faster than real time.
+    #[must_use]
+    pub fn speed(&self) -> Option<f64> {
+        self.speed
+            .map(|thousandths| f64::from(thousandths) / SPEED_SCALE)
+    }
+
+    /// Attaches a processing speed relative to real time, see [`speed`](Self::speed).
+    #[must_use]
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let thousandths = (speed.max(0.0) * SPEED_SCALE).round() as u32;
+        self.speed = Some(thousandths);
+        self
+    }
+
+    /// Returns the ratio of output bytes to input bytes, if the producer reports one.
+    ///
+    /// Compression adapters such as `CompressionProgress` set this: a ratio of `0.25`
+    /// means the output is a quarter of the size of the input consumed so far.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> Option<f64> {
+        self.ratio.map(|millionths| millionths as f64 / RATIO_SCALE)
+    }
+
+    /// Attaches an output to input size ratio, see [`ratio`](Self::ratio).
+    #[must_use]
+    pub fn with_ratio(mut self, ratio: f64) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let millionths = (ratio.max(0.0) * RATIO_SCALE).round() as u64;
+        self.ratio = Some(millionths);
+        self
+    }
+
+    /// Returns the second counter reported with this update, if the task tracks one.
+    ///
+    /// The main [`current`](Self::current) and [`total`](Self::total) track one
+    /// dimension of the work, such as bytes; this tracks a correlated one, such as files.
+    #[must_use]
+    pub const fn secondary(&self) -> Option<Dimension> {
+        self.secondary
+    }
+
+    /// Attaches a second counter, see [`secondary`](Self::secondary).
+    #[must_use]
+    pub const fn with_secondary(mut self, secondary: Dimension) -> Self {
+        self.secondary = Some(secondary);
+        self
+    }
+
+    /// Returns `true` if the [`total`](Self::total) is a guess rather than reported by the task.
+    ///
+    /// Interfaces can render such totals as approximate, for example `~1200`.
+    #[must_use]
+    pub const fn is_total_estimated(&self) -> bool {
+        self.estimated_total
+    }
+
+    /// Replaces the total with an estimate and flags it as such.
+    #[must_use]
+    pub const fn with_estimated_total(mut self, total: u64) -> Self {
+        self.total = total;
+        self.estimated_total = true;
+        self
+    }
+
+    /// Returns the cost of one unit of work, if the task is billed per unit.
+    ///
+    /// The currency is up to the task, such as dollars per API call or per gigabyte of
+    /// egress. It is attached with [`ProgressFuture::with_unit_cost`](crate::ProgressFuture::with_unit_cost).
+    #[must_use]
+    pub fn unit_cost(&self) -> Option<f64> {
+        self.unit_cost.map(f64::from_bits)
+    }
+
+    /// Attaches the cost of one unit of work, see [`unit_cost`](Self::unit_cost).
+    ///
+    /// The cost is kept exactly; a negative or NaN cost counts as zero.
+    #[must_use]
+    pub fn with_unit_cost(mut self, cost: f64) -> Self {
+        let cost = if cost > 0.0 { cost } else { 0.0 };
+        self.unit_cost = Some(cost.to_bits());
+        self
+    }
+
+    /// Returns the cost accumulated so far, the [`current`](Self::current) units at the unit cost.
+    #[must_use]
+    pub fn cost(&self) -> Option<f64> {
+        self.unit_cost.map(|bits| cost_of(bits, self.current))
+    }
+
+    /// Returns the projected cost of the whole operation, the [`total`](Self::total)
+    /// units at the unit cost, or `None` while the total is unknown.
+    #[must_use]
+    pub fn projected_cost(&self) -> Option<f64> {
+        self.unit_cost
+            .filter(|_| self.total > 0)
+            .map(|bits| cost_of(bits, self.total))
+    }
+
     /// Returns the remaining progress (total - current).
     ///
     /// Uses saturating subtraction, so if current > total, returns 0.
@@ -291,17 +1231,217 @@ impl ProgressUpdate {
         matches!(self.state, State::Paused)
     }
 
+    /// Returns `true` if the state is [`Failed`](State::Failed).
+    #[must_use]
+    pub const fn is_failed(&self) -> bool {
+        matches!(self.state, State::Failed)
+    }
+
+    /// Returns `true` if this is the final update of the task, see [`State::is_terminal`].
+    #[must_use]
+    pub const fn is_terminal(&self) -> bool {
+        self.state.is_terminal()
+    }
+
+    /// Shortens the message to at most `max_bytes`, at a character boundary.
+    #[cfg(feature = "std")]
+    pub(crate) fn truncate_message(&mut self, max_bytes: usize) {
+        if let Some(message) = &mut self.message
+            && message.len() > max_bytes
+        {
+            let mut end = max_bytes;
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+            message.truncate(end);
+        }
+    }
+
     /// Returns the optional descriptive message about the current progress.
     #[must_use]
     pub fn message(&self) -> Option<&str> {
         self.message.as_deref()
     }
 
+    /// Returns the localizable message reported with this update, if any.
+    #[must_use]
+    pub const fn keyed_message(&self) -> Option<&KeyedMessage> {
+        self.keyed_message.as_ref()
+    }
+
+    /// Attaches a localizable message to this update.
+    #[must_use]
+    pub fn with_keyed_message(mut self, message: KeyedMessage) -> Self {
+        self.keyed_message = Some(message);
+        self
+    }
+
+    /// Returns the message of this update as display text.
+    ///
+    /// A keyed message is resolved through `localizer`. Otherwise the plain
+    /// [`message`](Self::message) is returned unchanged.
+    #[must_use]
+    pub fn localized_message(&self, localizer: &impl Localizer) -> Option<String> {
+        self.keyed_message.as_ref().map_or_else(
+            || self.message.clone(),
+            |message| Some(localizer.localize(message)),
+        )
+    }
+
+    /// Returns why the operation was cancelled, if it was and a reason was given.
+    #[must_use]
+    pub const fn cancel_reason(&self) -> Option<CancelReason> {
+        self.cancel_reason
+    }
+
+    /// Attaches the reason for a cancellation to this update.
+    #[must_use]
+    pub const fn with_cancel_reason(mut self, reason: CancelReason) -> Self {
+        self.cancel_reason = Some(reason);
+        self
+    }
+
+    /// Returns the request the task makes with this update, if any.
+    #[must_use]
+    pub const fn event(&self) -> Option<&ProgressEvent> {
+        self.event.as_ref()
+    }
+
+    /// Attaches a request to observers, see [`event`](Self::event).
+    #[must_use]
+    pub fn with_event(mut self, event: ProgressEvent) -> Self {
+        self.event = Some(event);
+        self
+    }
+
+    /// Returns how long ago the task last reported, if this update is a heartbeat.
+    ///
+    /// Heartbeats repeat the latest update while the task is busy, see
+    /// [`ProgressUpdater::auto_tick`]. They tell observers the task is still alive, while
+    /// the age tells how long nothing has changed.
+    #[must_use]
+    pub const fn last_progress_age(&self) -> Option<core::time::Duration> {
+        self.last_progress_age
+    }
+
+    /// Returns `true` if this update repeats an earlier one rather than reporting news.
+    #[must_use]
+    pub const fn is_heartbeat(&self) -> bool {
+        self.last_progress_age.is_some()
+    }
+
+    /// Marks this update as a heartbeat, sent `age` after the task last reported.
+    #[must_use]
+    pub const fn with_last_progress_age(mut self, age: core::time::Duration) -> Self {
+        self.last_progress_age = Some(age);
+        self
+    }
+
+    /// Returns how long the task waited in a queue before it started, if it was queued.
+    ///
+    /// This is attached to the updates of tasks run by a [`ProgressPool`].
+    #[must_use]
+    pub const fn queue_wait(&self) -> Option<core::time::Duration> {
+        self.queue_wait
+    }
+
+    /// Attaches how long the task waited before it started, see [`queue_wait`](Self::queue_wait).
+    #[must_use]
+    pub const fn with_queue_wait(mut self, wait: core::time::Duration) -> Self {
+        self.queue_wait = Some(wait);
+        self
+    }
+
+    /// Returns the place in the source code that reported this update.
+    ///
+    /// This points at the call to the [`ProgressUpdater`] method that produced the
+    /// update, to trace which code path reported an unexpected value. It is only
+    /// recorded in debug builds with the `debug-callsite` feature.
+    #[cfg(all(feature = "debug-callsite", debug_assertions))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "debug-callsite", debug_assertions))))]
+    #[must_use]
+    pub const fn callsite(&self) -> Option<&'static core::panic::Location<'static>> {
+        self.callsite.location
+    }
+
+    /// Returns the position of this update among all updates of the same task.
+    ///
+    /// Every task numbers its updates from zero, including updates sent through clones
+    /// of its [`ProgressUpdater`]. When updates of several tasks are
+    /// merged into one stream, the sequence numbers restore the order of each source and
+    /// reveal updates that were dropped on the way.
+    #[must_use]
+    pub const fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Sets the sequence number of this update, see [`sequence`](Self::sequence).
+    #[must_use]
+    pub const fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Returns the labels of the items being processed right now, in the order they started.
+    ///
+    /// Concurrent batch jobs report these so observers can show what is in flight,
+    /// not just how much is done.
+    #[must_use]
+    pub fn active_items(&self) -> &[String] {
+        self.active_items.as_deref().unwrap_or_default()
+    }
+
+    /// Sets the labels of the items being processed right now.
+    #[must_use]
+    pub fn with_active_items(mut self, items: impl IntoIterator<Item = String>) -> Self {
+        let items: std::sync::Arc<[String]> = items.into_iter().collect();
+        self.active_items = (!items.is_empty()).then_some(items);
+        self
+    }
+
     /// Returns the current state of the progress operation.
     #[must_use]
     pub const fn state(&self) -> State {
         self.state
     }
+
+    /// Returns the priority of the task this update belongs to.
+    #[must_use]
+    pub const fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Returns how many processed items succeeded, failed or were skipped.
+    ///
+    /// This is `None` unless the task records outcomes, for example with
+    /// [`ProgressUpdater::record_ok`](crate::ProgressUpdater::record_ok).
+    #[must_use]
+    pub const fn outcomes(&self) -> Option<Outcomes> {
+        self.outcomes
+    }
+
+    /// Attaches per-outcome counts to this update.
+    #[must_use]
+    pub const fn with_outcomes(mut self, outcomes: Outcomes) -> Self {
+        self.outcomes = Some(outcomes);
+        self
+    }
+
+    /// Returns the domain-specific state that refines [`state`](Self::state), if any.
+    ///
+    /// For example, a download may report `Some("Verifying")` while its state is
+    /// [`Working`](State::Working).
+    #[must_use]
+    pub const fn extra_state(&self) -> Option<&'static str> {
+        self.extra_state
+    }
+
+    /// Attaches a domain-specific state to this update.
+    #[must_use]
+    pub const fn with_extra_state(mut self, extra_state: &'static str) -> Self {
+        self.extra_state = Some(extra_state);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -347,6 +1487,11 @@ mod tests {
         assert!(!update.is_cancelled());
         assert!(!update.is_completed());
         assert!(!update.is_paused());
+        assert_eq!(update.extra_state(), None);
+
+        let update = update.with_extra_state("Verifying");
+        assert_eq!(update.extra_state(), Some("Verifying"));
+        assert!(update.is_working());
     }
 
     #[test]
@@ -367,6 +1512,44 @@ mod tests {
         update.state = State::Paused;
         assert!(update.is_paused());
         assert!(!update.is_completed());
+        assert!(!update.is_terminal());
+
+        update.state = State::Failed;
+        assert!(update.is_failed());
+        assert!(!update.is_cancelled());
+        assert!(update.is_terminal());
+    }
+
+    #[test]
+    fn test_stable_fraction() {
+        let update = ProgressUpdate::new(4, 1, State::Working, None);
+        assert!((update.stable_fraction() - 0.25).abs() < f64::EPSILON);
+
+        let update = update.with_stable_fraction(0.6);
+        assert!((update.stable_fraction() - 0.6).abs() < 1e-6);
+        assert!((update.completed_fraction() - 0.25).abs() < f64::EPSILON);
+
+        let update = update.with_stable_fraction(1.5);
+        assert!((update.stable_fraction() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_localized_message() {
+        let localizer = |message: &KeyedMessage| format!("[{}]", message.key());
+
+        let plain = ProgressUpdate::new(10, 1, State::Working, Some("Extracting".to_string()));
+        assert_eq!(
+            plain.localized_message(&localizer),
+            Some("Extracting".to_string())
+        );
+
+        let keyed = ProgressUpdate::new(10, 1, State::Working, None)
+            .with_keyed_message(KeyedMessage::new("msg.extracting", []));
+        assert_eq!(keyed.message(), None);
+        assert_eq!(
+            keyed.localized_message(&localizer),
+            Some("[msg.extracting]".to_string())
+        );
     }
 
     #[test]
@@ -383,4 +1566,33 @@ mod tests {
         update.current = 150; // when exceeding total should return 0
         assert_eq!(update.remaining(), 0);
     }
+
+    #[test]
+    fn test_speed() {
+        let update = ProgressUpdate::new(100, 50, State::Working, None);
+        assert_eq!(update.speed(), None);
+
+        let update = update.with_speed(1.7);
+        assert!((update.speed().unwrap() - 1.7).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_cost() {
+        let update = ProgressUpdate::new(2_000, 500, State::Working, None);
+        assert_eq!(update.cost(), None);
+
+        let update = update.with_unit_cost(0.002);
+        assert!((update.cost().unwrap() - 1.0).abs() < 1e-9);
+        assert!((update.projected_cost().unwrap() - 4.0).abs() < 1e-9);
+
+        let unknown = ProgressUpdate::new(0, 500, State::Working, None).with_unit_cost(0.002);
+        assert_eq!(unknown.projected_cost(), None);
+
+        let tiny =
+            ProgressUpdate::new(2_000_000, 1_000_000, State::Working, None).with_unit_cost(1e-8);
+        assert!((tiny.cost().unwrap() - 0.01).abs() < 1e-12);
+        assert!((tiny.projected_cost().unwrap() - 0.02).abs() < 1e-12);
+        let free = tiny.with_unit_cost(0.0);
+        assert_eq!(free.cost(), Some(0.0));
+    }
 }
@@ -0,0 +1,79 @@
+/// A message identified by a localization key instead of display text.
+///
+/// Tasks report keyed messages with
+/// [`ProgressUpdater::update_keyed`](crate::ProgressUpdater::update_keyed), so the task code
+/// does not have to bake user-facing strings into it. Observers turn them into text with a
+/// [`Localizer`], see [`ProgressUpdate::localized_message`](crate::ProgressUpdate::localized_message).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyedMessage {
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+}
+
+impl KeyedMessage {
+    /// Creates a keyed message with named arguments.
+    pub fn new(key: &'static str, args: impl IntoIterator<Item = (&'static str, String)>) -> Self {
+        Self {
+            key,
+            args: args.into_iter().collect(),
+        }
+    }
+
+    /// Returns the localization key, for example `"msg.extracting"`.
+    #[must_use]
+    pub const fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// Returns the named arguments to interpolate into the localized text.
+    #[must_use]
+    pub fn args(&self) -> &[(&'static str, String)] {
+        &self.args
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn args_mut(&mut self) -> impl Iterator<Item = &mut (&'static str, String)> {
+        self.args.iter_mut()
+    }
+
+    /// Returns the value of the argument with the given name.
+    #[must_use]
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Resolves [`KeyedMessage`]s into display text.
+///
+/// This is implemented for closures taking a message and returning a `String`, so a
+/// lookup into any translation catalog can be used directly.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{KeyedMessage, Localizer};
+///
+/// let german = |message: &KeyedMessage| match message.key() {
+///     "msg.extracting" => format!("Entpacke {}", message.arg("file").unwrap_or("?")),
+///     key => key.to_string(),
+/// };
+///
+/// let message = KeyedMessage::new("msg.extracting", [("file", "data.zip".to_string())]);
+/// assert_eq!(german.localize(&message), "Entpacke data.zip");
+/// ```
+pub trait Localizer {
+    /// Returns the display text for a keyed message.
+    fn localize(&self, message: &KeyedMessage) -> String;
+}
+
+impl<F> Localizer for F
+where
+    F: Fn(&KeyedMessage) -> String,
+{
+    fn localize(&self, message: &KeyedMessage) -> String {
+        self(message)
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    fmt::Write as _,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use rumqttc::{AsyncClient, QoS};
+
+use crate::{ProgressUpdate, State, Transport, sync::lock};
+
+/// Publishes a task's progress to an MQTT topic, throttled.
+///
+/// Attach it with [`ProgressFuture::with_transport`](crate::ProgressFuture::with_transport).
+/// Working updates are published at most once per interval, one second by default,
+/// while state changes are always published. Messages are retained, so a dashboard
+/// subscribing later sees the latest progress of every device. The payload is JSON:
+///
+/// ```json
+/// {"current":40,"total":100,"state":"working","message":"Flashing partition 2"}
+/// ```
+///
+/// Publishing never blocks the task: updates are dropped while the client's request
+/// queue is full.
+///
+/// # Examples
+///
+/// ```no_run
+/// use progressor::{MqttPublisher, progress};
+/// use rumqttc::{AsyncClient, MqttOptions};
+///
+/// # async fn example() {
+/// let (client, mut eventloop) = AsyncClient::new(MqttOptions::new("device-17", "broker", 1883), 16);
+/// tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+///
+/// let update = progress(100, |mut updater| async move {
+///     for block in 1..=100 {
+///         updater.update(block);
+///     }
+///     updater.complete();
+/// })
+/// .with_transport(MqttPublisher::new(client, "devices/17/firmware"));
+/// update.await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rumqttc")))]
+#[derive(Debug)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    topic: String,
+    qos: QoS,
+    interval: Duration,
+    last: Mutex<Option<(Instant, State)>>,
+}
+
+impl MqttPublisher {
+    /// Creates a publisher sending to `topic` through `client`.
+    #[must_use]
+    pub fn new(client: AsyncClient, topic: impl Into<String>) -> Self {
+        Self {
+            client,
+            topic: topic.into(),
+            qos: QoS::AtMostOnce,
+            interval: Duration::from_secs(1),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Publishes working updates at most once per `interval`.
+    #[must_use]
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the quality of service of the published messages, at most once by default.
+    #[must_use]
+    pub const fn with_qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+}
+
+impl Transport for MqttPublisher {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        let now = Instant::now();
+        let mut last = lock(&self.last);
+        let due = last.is_none_or(|(published, state)| {
+            state != update.state() || now.duration_since(published) >= self.interval
+        });
+        if due
+            && self
+                .client
+                .try_publish(&self.topic, self.qos, true, payload(update))
+                .is_ok()
+        {
+            *last = Some((now, update.state()));
+        }
+        drop(last);
+        true
+    }
+}
+
+/// Formats `update` as a JSON object.
+fn payload(update: &ProgressUpdate) -> String {
+    let state = match update.state() {
+        State::Working => "working",
+        State::Paused => "paused",
+        State::Completed => "completed",
+        State::Cancelled => "cancelled",
+        State::Failed => "failed",
+    };
+    let mut json = format!(
+        r#"{{"current":{},"total":{},"state":"{state}""#,
+        update.current(),
+        update.total()
+    );
+    if let Some(message) = update.message() {
+        json.push_str(r#","message":""#);
+        for c in message.chars() {
+            match c {
+                '"' => json.push_str(r#"\""#),
+                '\\' => json.push_str(r"\\"),
+                c if c.is_control() => {
+                    let _ = write!(json, "\\u{:04x}", u32::from(c));
+                }
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+    }
+    json.push('}');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use rumqttc::Request;
+
+    use super::*;
+    use crate::progress;
+
+    #[tokio::test]
+    async fn test_publishes_state_changes_and_throttles() {
+        let (sender, requests) = flume::bounded(16);
+        let publisher = MqttPublisher::new(AsyncClient::from_senders(sender), "jobs/1")
+            .with_interval(Duration::from_hours(1));
+        progress(10, |mut updater| async move {
+            updater.update_with_message(1, "say \"hi\"\n");
+            updater.update(2);
+            updater.update(3);
+            updater.complete();
+        })
+        .with_transport(publisher)
+        .await;
+
+        let payloads: Vec<_> = requests
+            .drain()
+            .filter_map(|request| match request {
+                Request::Publish(publish) => {
+                    assert!(publish.retain);
+                    assert_eq!(publish.topic, "jobs/1");
+                    Some(String::from_utf8(publish.payload.to_vec()).unwrap())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            payloads,
+            [
+                r#"{"current":1,"total":10,"state":"working","message":"say \"hi\"\u000a"}"#,
+                r#"{"current":3,"total":10,"state":"completed"}"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::{Duration, Instant};
+
+use crate::{ProgressUpdate, State, stream::percent};
+
+/// The content of a progress notification, shaped for mobile notification APIs.
+///
+/// Android's `NotificationCompat.Builder.setProgress(max, progress, indeterminate)` and
+/// iOS Live Activities both take an integer percentage, a title and a line of text,
+/// which is all this carries. Produced by a [`NotificationAdapter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationProgress {
+    title: String,
+    text: Option<String>,
+    percent: u8,
+    indeterminate: bool,
+    state: State,
+}
+
+impl NotificationProgress {
+    /// Returns the title of the notification.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the text below the title, the message of the latest update.
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Returns the completed percentage, from 0 to 100.
+    #[must_use]
+    pub const fn percent(&self) -> u8 {
+        self.percent
+    }
+
+    /// Returns `true` if the total is unknown and the bar should be indeterminate.
+    #[must_use]
+    pub const fn is_indeterminate(&self) -> bool {
+        self.indeterminate
+    }
+
+    /// Returns the state of the task.
+    #[must_use]
+    pub const fn state(&self) -> State {
+        self.state
+    }
+
+    /// Returns `true` while the task runs, when the notification should not be dismissible.
+    #[must_use]
+    pub const fn is_ongoing(&self) -> bool {
+        matches!(self.state, State::Working | State::Paused)
+    }
+}
+
+/// Turns progress updates into rate-limited [`NotificationProgress`] values.
+///
+/// Mobile platforms drop or penalize notifications that are updated too often, so the
+/// adapter only yields a notification when its content changes, and working updates at
+/// most once per interval, one second by default. Pauses, completion and cancellation
+/// are always yielded. Every value is plain data, which makes the adapter easy to drive
+/// from bindings: push updates in and post what comes out.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{NotificationAdapter, progress_inline};
+///
+/// # async fn example() {
+/// let mut notifications = NotificationAdapter::new("Downloading maps");
+/// progress_inline(
+///     100,
+///     move |update| {
+///         if let Some(notification) = notifications.push(update) {
+///             println!("{}: {}%", notification.title(), notification.percent());
+///         }
+///     },
+///     |mut updater| async move {
+///         for i in 1..=100 {
+///             updater.update(i);
+///         }
+///         updater.complete();
+///     },
+/// )
+/// .await;
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotificationAdapter {
+    title: String,
+    interval: Duration,
+    last: Option<(Instant, NotificationProgress)>,
+}
+
+impl NotificationAdapter {
+    /// Creates an adapter for notifications titled `title`.
+    #[must_use]
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            interval: Duration::from_secs(1),
+            last: None,
+        }
+    }
+
+    /// Yields working updates at most once per `interval`.
+    #[must_use]
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Accounts for `update`, returning the notification to post if it should be updated.
+    pub fn push(&mut self, update: &ProgressUpdate) -> Option<NotificationProgress> {
+        self.push_at(update, Instant::now())
+    }
+
+    fn push_at(&mut self, update: &ProgressUpdate, now: Instant) -> Option<NotificationProgress> {
+        let notification = self.notification(update);
+        if let Some((at, last)) = &self.last {
+            let due = now.saturating_duration_since(*at) >= self.interval;
+            if *last == notification || (last.state == notification.state && !due) {
+                return None;
+            }
+        }
+        self.last = Some((now, notification.clone()));
+        Some(notification)
+    }
+
+    fn notification(&self, update: &ProgressUpdate) -> NotificationProgress {
+        let indeterminate = update.is_indeterminate();
+        let percent = if update.is_completed() {
+            100
+        } else {
+            percent(update)
+        };
+        NotificationProgress {
+            title: self.title.clone(),
+            text: update.message().map(str::to_owned),
+            percent,
+            indeterminate,
+            state: update.state(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn working(current: u64) -> ProgressUpdate {
+        ProgressUpdate::new(100, current, State::Working, None)
+    }
+
+    #[test]
+    fn test_notifications_are_rate_limited() {
+        let mut adapter = NotificationAdapter::new("Sync");
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        assert_eq!(adapter.push_at(&working(1), at(0)).unwrap().percent(), 1);
+        assert!(adapter.push_at(&working(2), at(500)).is_none());
+        assert_eq!(adapter.push_at(&working(3), at(1000)).unwrap().percent(), 3);
+        assert!(adapter.push_at(&working(3), at(3000)).is_none());
+        assert_eq!(
+            adapter.push_at(&working(250), at(4000)).unwrap().percent(),
+            100
+        );
+
+        let done = ProgressUpdate::new(100, 3, State::Completed, None);
+        let done = adapter.push_at(&done, at(4100)).unwrap();
+        assert_eq!(done.percent(), 100);
+        assert!(!done.is_ongoing());
+        assert_eq!(done.title(), "Sync");
+    }
+}
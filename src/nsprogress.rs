@@ -0,0 +1,89 @@
+use objc2::rc::Retained;
+use objc2_foundation::{NSProgress, NSString};
+
+use crate::{ProgressUpdate, State, Transport};
+
+/// Mirrors a task's progress into an [`NSProgress`], for macOS apps with Rust cores.
+///
+/// Attach it with [`ProgressFuture::with_transport`](crate::ProgressFuture::with_transport).
+/// Created with [`new`](Self::new), the progress joins the thread's current progress
+/// tree, so a Swift or Objective-C caller that called `becomeCurrent(withPendingUnitCount:)`
+/// sees the task's units roll up into its own progress. To show the task in Finder, set
+/// the file URL and kind on [`as_ns_progress`](Self::as_ns_progress) and publish it.
+///
+/// A total of zero is reported as indeterminate progress, and cancelling the task
+/// cancels the `NSProgress`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use progressor::{NsProgress, progress};
+///
+/// # async fn example() {
+/// let copy = progress(100, |mut updater| async move {
+///     for i in 1..=100 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// })
+/// .with_transport(NsProgress::new(100));
+/// copy.await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(all(feature = "objc2", target_os = "macos"))))]
+#[derive(Debug)]
+pub struct NsProgress {
+    progress: Retained<NSProgress>,
+}
+
+impl NsProgress {
+    /// Creates an `NSProgress` with `total` units, attached to the current progress if any.
+    #[must_use]
+    pub fn new(total: u64) -> Self {
+        Self::from_ns_progress(NSProgress::progressWithTotalUnitCount(units(total)))
+    }
+
+    /// Reports into an existing `NSProgress`, such as one created by the application.
+    #[must_use]
+    pub const fn from_ns_progress(progress: Retained<NSProgress>) -> Self {
+        Self { progress }
+    }
+
+    /// Returns the underlying `NSProgress`.
+    #[must_use]
+    pub fn as_ns_progress(&self) -> &NSProgress {
+        &self.progress
+    }
+}
+
+impl Transport for NsProgress {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        let progress = &self.progress;
+        match update.state() {
+            State::Completed => {
+                let total = units(update.total().max(update.current()));
+                progress.setTotalUnitCount(total);
+                progress.setCompletedUnitCount(total);
+            }
+            State::Cancelled | State::Failed => progress.cancel(),
+            State::Working | State::Paused => {
+                let total = if update.total() == 0 {
+                    -1
+                } else {
+                    units(update.total())
+                };
+                progress.setTotalUnitCount(total);
+                progress.setCompletedUnitCount(units(update.current()));
+            }
+        }
+        if let Some(message) = update.message() {
+            progress.setLocalizedDescription(Some(&NSString::from_str(message)));
+        }
+        true
+    }
+}
+
+/// Converts a unit count to the signed count `NSProgress` expects.
+fn units(count: u64) -> i64 {
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,197 @@
+use crate::Pacing;
+
+/// A source of system conditions that should change how fast a task proceeds.
+///
+/// Attach one with [`ProgressFuture::with_pacing_provider`](crate::ProgressFuture::with_pacing_provider).
+/// The task then sees its hint through [`ProgressUpdater::pacing_hint`](crate::ProgressUpdater::pacing_hint)
+/// whenever no observer has asked for a specific pace. [`SystemPacing`] probes the
+/// battery and thermal state with the `system-pacing` feature; closures returning a
+/// [`Pacing`] are providers too.
+pub trait PacingProvider: Send + Sync + 'static {
+    /// Returns the pace the current conditions call for.
+    ///
+    /// This is called every time the task reads its hint, so it should be cheap.
+    fn pacing(&self) -> Pacing;
+}
+
+impl<F: Fn() -> Pacing + Send + Sync + 'static> PacingProvider for F {
+    fn pacing(&self) -> Pacing {
+        self()
+    }
+}
+
+#[cfg(feature = "system-pacing")]
+pub use system::SystemPacing;
+
+#[cfg(feature = "system-pacing")]
+mod system {
+    use std::{
+        sync::Mutex,
+        time::{Duration, Instant},
+    };
+
+    use super::PacingProvider;
+    use crate::{Pacing, sync::lock};
+
+    /// Asks tasks to slow down while the machine runs on battery or is running hot.
+    ///
+    /// The system is probed at most once per refresh interval, five seconds by default.
+    /// On Linux, power supplies and thermal zones are read from `/sys/class`: a
+    /// discharging battery, or a thermal zone at or above its passive trip point, yields
+    /// [`Pacing::Slow`]. On other platforms the hint is always [`Pacing::Normal`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{Pacing, SystemPacing, progress};
+    ///
+    /// # async fn example() {
+    /// let reindex = progress(10_000, |mut updater| async move {
+    ///     for document in 1..=10_000 {
+    ///         if updater.pacing_hint() == Pacing::Slow {
+    ///             std::thread::yield_now();
+    ///         }
+    ///         updater.update(document);
+    ///     }
+    /// })
+    /// .with_pacing_provider(SystemPacing::new());
+    /// reindex.await;
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "system-pacing")))]
+    #[derive(Debug)]
+    pub struct SystemPacing {
+        refresh: Duration,
+        last: Mutex<Option<(Instant, Pacing)>>,
+    }
+
+    impl SystemPacing {
+        /// Creates a provider probing the system every five seconds.
+        #[must_use]
+        pub const fn new() -> Self {
+            Self {
+                refresh: Duration::from_secs(5),
+                last: Mutex::new(None),
+            }
+        }
+
+        /// Probes the system at most once per `refresh`.
+        #[must_use]
+        pub const fn with_refresh(mut self, refresh: Duration) -> Self {
+            self.refresh = refresh;
+            self
+        }
+    }
+
+    impl Default for SystemPacing {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl PacingProvider for SystemPacing {
+        fn pacing(&self) -> Pacing {
+            let now = Instant::now();
+            let mut last = lock(&self.last);
+            match *last {
+                Some((at, pacing)) if now.saturating_duration_since(at) < self.refresh => pacing,
+                _ => {
+                    let pacing = probe();
+                    *last = Some((now, pacing));
+                    pacing
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn probe() -> Pacing {
+        linux::probe(std::path::Path::new("/sys/class"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    const fn probe() -> Pacing {
+        Pacing::Normal
+    }
+
+    #[cfg(target_os = "linux")]
+    mod linux {
+        use std::{fs, path::Path};
+
+        use crate::Pacing;
+
+        pub fn probe(class: &Path) -> Pacing {
+            if on_battery(&class.join("power_supply")) || overheated(&class.join("thermal")) {
+                Pacing::Slow
+            } else {
+                Pacing::Normal
+            }
+        }
+
+        fn read(path: &Path) -> Option<String> {
+            fs::read_to_string(path).ok().map(|s| s.trim().to_owned())
+        }
+
+        fn on_battery(supplies: &Path) -> bool {
+            let Ok(entries) = fs::read_dir(supplies) else {
+                return false;
+            };
+            entries.flatten().any(|supply| {
+                let supply = supply.path();
+                read(&supply.join("type")).as_deref() == Some("Battery")
+                    && read(&supply.join("status")).as_deref() == Some("Discharging")
+            })
+        }
+
+        fn overheated(thermal: &Path) -> bool {
+            let Ok(entries) = fs::read_dir(thermal) else {
+                return false;
+            };
+            entries.flatten().any(|zone| {
+                let zone = zone.path();
+                let Some(temp) = read(&zone.join("temp")).and_then(|t| t.parse::<i64>().ok())
+                else {
+                    return false;
+                };
+                (0..16).any(|trip| {
+                    let kind = read(&zone.join(format!("trip_point_{trip}_type")));
+                    let limit = read(&zone.join(format!("trip_point_{trip}_temp")))
+                        .and_then(|t| t.parse::<i64>().ok());
+                    matches!(
+                        (kind.as_deref(), limit),
+                        (Some("passive" | "hot" | "critical"), Some(limit)) if limit > 0 && temp >= limit
+                    )
+                })
+            })
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+
+            #[test]
+            fn test_probe_reads_sysfs() {
+                let class =
+                    std::env::temp_dir().join(format!("progressor-pacing-{}", std::process::id()));
+                let battery = class.join("power_supply/BAT0");
+                let zone = class.join("thermal/thermal_zone0");
+                fs::create_dir_all(&battery).unwrap();
+                fs::create_dir_all(&zone).unwrap();
+                fs::write(battery.join("type"), "Battery\n").unwrap();
+                fs::write(battery.join("status"), "Charging\n").unwrap();
+                fs::write(zone.join("temp"), "60000\n").unwrap();
+                fs::write(zone.join("trip_point_0_type"), "passive\n").unwrap();
+                fs::write(zone.join("trip_point_0_temp"), "90000\n").unwrap();
+                assert_eq!(probe(&class), Pacing::Normal);
+
+                fs::write(zone.join("temp"), "95000\n").unwrap();
+                assert_eq!(probe(&class), Pacing::Slow);
+
+                fs::write(zone.join("temp"), "60000\n").unwrap();
+                fs::write(battery.join("status"), "Discharging\n").unwrap();
+                assert_eq!(probe(&class), Pacing::Slow);
+                fs::remove_dir_all(&class).unwrap();
+            }
+        }
+    }
+}
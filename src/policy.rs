@@ -0,0 +1,153 @@
+use crate::ProgressUpdate;
+
+/// Rules applied to progress messages before they are broadcast.
+///
+/// Messages often contain untrusted data such as file names or URLs. Escape sequences
+/// in them can corrupt terminals and logs, and overly long ones wrap progress bars.
+/// Attach a policy to a task with
+/// [`ProgressFuture::with_message_policy`](crate::ProgressFuture::with_message_policy).
+///
+/// # Examples
+///
+/// ```
+/// use progressor::MessagePolicy;
+///
+/// let policy = MessagePolicy::new().with_strip_control(true).with_max_chars(12);
+/// assert_eq!(policy.apply("\x1b[31mred\x1b[0m\tfile.txt"), "red file.txt");
+/// assert_eq!(policy.apply("a-very-long-file-name.txt"), "a-very-long…");
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MessagePolicy {
+    max_chars: Option<usize>,
+    strip_control: bool,
+}
+
+impl MessagePolicy {
+    /// Creates a policy that leaves messages unchanged.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_chars: None,
+            strip_control: false,
+        }
+    }
+
+    /// Shortens messages to at most `max_chars` characters, ending truncated ones
+    /// with `…`.
+    #[must_use]
+    pub const fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    /// Removes ANSI escape sequences and control characters from messages.
+    ///
+    /// Tabs and line breaks become spaces so words stay apart.
+    #[must_use]
+    pub const fn with_strip_control(mut self, strip_control: bool) -> Self {
+        self.strip_control = strip_control;
+        self
+    }
+
+    /// Returns `message` with this policy applied.
+    #[must_use]
+    pub fn apply(&self, message: &str) -> String {
+        let mut message = if self.strip_control {
+            strip_control(message)
+        } else {
+            message.to_owned()
+        };
+        if let Some(max_chars) = self.max_chars
+            && let Some((end, _)) = message.char_indices().nth(max_chars)
+        {
+            message.truncate(end);
+            if max_chars > 0 {
+                message.pop();
+                message.push('…');
+            }
+        }
+        message
+    }
+
+    pub(crate) fn apply_to(&self, update: &mut ProgressUpdate) {
+        if *self != Self::new()
+            && let Some(message) = &mut update.message
+        {
+            *message = self.apply(message);
+        }
+    }
+}
+
+/// Removes escape sequences and control characters, turning whitespace into spaces.
+fn strip_control(message: &str) -> String {
+    let mut stripped = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // Control sequence: parameters and intermediates up to a final byte.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('\u{40}'..='\u{7e}').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // Operating system command: up to BEL or the string terminator.
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\t' | '\n' | '\r' => stripped.push(' '),
+            c if c.is_control() => {}
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+    use futures_util::StreamExt;
+
+    #[test]
+    fn test_strip_control() {
+        let policy = MessagePolicy::new().with_strip_control(true);
+        assert_eq!(policy.apply("\x1b]0;title\x07ok\x00\x1b[1;31m!"), "ok!");
+        assert_eq!(policy.apply("a\r\nb"), "a  b");
+        assert_eq!(MessagePolicy::new().apply("\x1b[0m"), "\x1b[0m");
+    }
+
+    #[test]
+    fn test_max_chars() {
+        let policy = MessagePolicy::new().with_max_chars(3);
+        assert_eq!(policy.apply("abc"), "abc");
+        assert_eq!(policy.apply("abcd"), "ab…");
+        assert_eq!(MessagePolicy::new().with_max_chars(0).apply("abc"), "");
+    }
+
+    #[tokio::test]
+    async fn test_applied_at_broadcast() {
+        let task = progress(1, |mut updater| async move {
+            updater.update_with_message(1, "\x1b[2Jdownloaded.zip");
+        })
+        .with_message_policy(MessagePolicy::new().with_strip_control(true));
+        let mut updates = task.progress();
+        task.await;
+
+        let update = updates.next().await.unwrap();
+        assert_eq!(update.message(), Some("downloaded.zip"));
+    }
+}
@@ -0,0 +1,435 @@
+use core::{
+    future::{Future, poll_fn},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_broadcast::{InactiveReceiver, broadcast};
+use futures_core::Stream;
+use futures_util::{StreamExt, stream::FuturesUnordered};
+
+use crate::{
+    Progress, ProgressFuture, ProgressUpdate, progress, runner::forward_updates, sync::lock,
+};
+
+/// A queued task, started with the time it waited in the queue.
+type Job = Box<dyn FnOnce(Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Number of recent queue waits the wait percentiles are computed from.
+const WAIT_SAMPLES: usize = 1024;
+
+/// The number of tasks in each stage of a [`ProgressPool`], and how long they waited.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PoolStatus {
+    queued: usize,
+    running: usize,
+    finished: usize,
+    wait_p50: Option<Duration>,
+    wait_p95: Option<Duration>,
+}
+
+impl PoolStatus {
+    /// Returns the number of tasks waiting for a free slot.
+    #[must_use]
+    pub const fn queued(&self) -> usize {
+        self.queued
+    }
+
+    /// Returns the number of tasks currently running.
+    #[must_use]
+    pub const fn running(&self) -> usize {
+        self.running
+    }
+
+    /// Returns the number of tasks that have finished.
+    #[must_use]
+    pub const fn finished(&self) -> usize {
+        self.finished
+    }
+
+    /// Returns the number of tasks submitted to the pool.
+    #[must_use]
+    pub const fn submitted(&self) -> usize {
+        self.queued + self.running + self.finished
+    }
+
+    /// Returns the median time recently started tasks waited in the queue.
+    ///
+    /// Returns `None` before the first task started.
+    #[must_use]
+    pub const fn wait_p50(&self) -> Option<Duration> {
+        self.wait_p50
+    }
+
+    /// Returns the time 95% of recently started tasks waited in the queue at most.
+    ///
+    /// A p95 wait that keeps growing while the median stays low means some tasks are
+    /// starved; both growing means work is backing up.
+    #[must_use]
+    pub const fn wait_p95(&self) -> Option<Duration> {
+        self.wait_p95
+    }
+}
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<(Instant, Job)>,
+    status: PoolStatus,
+    waits: VecDeque<Duration>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl core::fmt::Debug for State {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("State")
+            .field("status", &self.status)
+            .field("closed", &self.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs submitted progress-tracked tasks with a limit on how many run at once.
+///
+/// Tasks are started in the order they were submitted, as soon as a slot is free. Each
+/// submission returns a [`PoolTask`] to observe and await that task, while
+/// [`status`](Self::status) and the progress of [`run`](Self::run) report on the pool as
+/// a whole. The pool does not spawn anything itself: the future returned by `run` drives
+/// all tasks, so it works on any runtime. It is cheap to clone; all clones submit to the
+/// same pool, which is driven by a single `run`.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{Progress, ProgressPool, progress};
+///
+/// # async fn example() {
+/// let pool = ProgressPool::new(4);
+/// let uploads: Vec<_> = (0..10)
+///     .map(|file| {
+///         pool.submit(progress(100, move |mut updater| async move {
+///             updater.update(100);
+///             file
+///         }))
+///     })
+///     .collect();
+/// pool.close();
+///
+/// let run = pool.run();
+/// let overall = run.progress();
+/// tokio::spawn(run);
+/// for upload in uploads {
+///     assert!(upload.await.is_some());
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct ProgressPool {
+    max_concurrency: usize,
+    state: Arc<Mutex<State>>,
+}
+
+impl ProgressPool {
+    /// Creates a pool that runs at most `max_concurrency` tasks at once.
+    #[must_use]
+    pub fn new(max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency: max_concurrency.max(1),
+            state: Arc::default(),
+        }
+    }
+
+    /// Queues `task` to run once a slot is free.
+    ///
+    /// The updates of the task carry how long it waited in the queue, see
+    /// [`ProgressUpdate::queue_wait`].
+    ///
+    /// Tasks submitted after the pool was [closed](Self::close) are dropped without
+    /// running, and their [`PoolTask`] resolves to `None`.
+    pub fn submit<P>(&self, task: P) -> PoolTask<P::Output>
+    where
+        P: Progress + Send + 'static,
+        P::Output: Send + 'static,
+    {
+        let (mut sender, receiver) = broadcast(32);
+        sender.set_overflow(true);
+        let slot = Arc::new(Mutex::new(Slot {
+            output: None,
+            closed: false,
+            waker: None,
+        }));
+        let completer = Completer(slot.clone());
+        let job: Job = Box::new(move |wait| {
+            Box::pin(async move {
+                let output = forward_updates(task, |update| {
+                    let _ = sender.try_broadcast(update.with_queue_wait(wait));
+                })
+                .await;
+                completer.finish(output);
+            })
+        });
+
+        self.change(|state| {
+            if !state.closed {
+                state.queue.push_back((Instant::now(), job));
+                state.status.queued += 1;
+            }
+        });
+        PoolTask {
+            receiver: receiver.deactivate(),
+            slot,
+        }
+    }
+
+    /// Stops accepting tasks, letting [`run`](Self::run) finish once the queue is drained.
+    pub fn close(&self) {
+        self.change(|state| state.closed = true);
+    }
+
+    /// Applies `change` to the state and wakes the pool's driver.
+    fn change(&self, change: impl FnOnce(&mut State)) {
+        let mut state = lock(&self.state);
+        change(&mut state);
+        let waker = state.waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Returns the number of queued, running and finished tasks, and their queue waits.
+    #[must_use]
+    pub fn status(&self) -> PoolStatus {
+        let state = lock(&self.state);
+        let mut waits: Vec<_> = state.waits.iter().copied().collect();
+        let status = state.status;
+        drop(state);
+        waits.sort_unstable();
+        PoolStatus {
+            wait_p50: percentile(&waits, 50),
+            wait_p95: percentile(&waits, 95),
+            ..status
+        }
+    }
+
+    /// Runs the submitted tasks until the pool is closed and every task has finished.
+    ///
+    /// The progress of the returned future counts finished tasks out of all submitted
+    /// tasks, so its total grows as tasks are submitted. Dropping it drops the running
+    /// and queued tasks, whose [`PoolTask`]s then resolve to `None`.
+    #[must_use]
+    pub fn run(&self) -> ProgressFuture<impl Future<Output = ()> + Send + use<>> {
+        let pool = self.clone();
+        progress(0, move |mut updater| async move {
+            let mut running = FuturesUnordered::new();
+            let mut reported = PoolStatus::default();
+            poll_fn(|cx| {
+                loop {
+                    let mut state = lock(&pool.state);
+                    while running.len() < pool.max_concurrency {
+                        let Some((queued_at, job)) = state.queue.pop_front() else {
+                            break;
+                        };
+                        let wait = queued_at.elapsed();
+                        if state.waits.len() == WAIT_SAMPLES {
+                            state.waits.pop_front();
+                        }
+                        state.waits.push_back(wait);
+                        running.push(job(wait));
+                        state.status.queued -= 1;
+                        state.status.running += 1;
+                    }
+                    let status = state.status;
+                    let drained = state.closed && running.is_empty();
+                    state.waker = Some(cx.waker().clone());
+                    drop(state);
+
+                    if status.submitted() != reported.submitted() {
+                        updater.set_total(status.submitted() as u64);
+                    }
+                    if status.finished() != reported.finished() {
+                        updater.update(status.finished() as u64);
+                    }
+                    reported = status;
+                    if drained {
+                        return Poll::Ready(());
+                    }
+
+                    match running.poll_next_unpin(cx) {
+                        Poll::Ready(Some(())) => {
+                            let mut state = lock(&pool.state);
+                            state.status.running -= 1;
+                            state.status.finished += 1;
+                        }
+                        // Without running tasks, wait for the next submission.
+                        Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+                    }
+                }
+            })
+            .await;
+            updater.complete();
+        })
+    }
+}
+
+/// Returns the nearest-rank `percent`th percentile of the sorted `values`.
+fn percentile(values: &[Duration], percent: usize) -> Option<Duration> {
+    let rank = (values.len() * percent).div_ceil(100).max(1);
+    values.get(rank - 1).copied()
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    output: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Delivers a task's output to its [`PoolTask`], or closes the slot if dropped unfinished.
+struct Completer<T>(Arc<Mutex<Slot<T>>>);
+
+impl<T> Completer<T> {
+    fn finish(self, output: T) {
+        lock(&self.0).output = Some(output);
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        let mut slot = lock(&self.0);
+        slot.closed = true;
+        let waker = slot.waker.take();
+        drop(slot);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A task submitted to a [`ProgressPool`].
+///
+/// It implements [`Progress`] with the updates of the task, and resolves to the task's
+/// output, or to `None` if the task was dropped without finishing.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct PoolTask<T> {
+    receiver: InactiveReceiver<ProgressUpdate>,
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for PoolTask<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = lock(&self.slot);
+        if let Some(output) = slot.output.take() {
+            return Poll::Ready(Some(output));
+        }
+        if slot.closed {
+            return Poll::Ready(None);
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Progress for PoolTask<T> {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.receiver.activate_cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_limits_concurrency() {
+        let pool = ProgressPool::new(2);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..6_u64)
+            .map(|i| {
+                let in_flight = in_flight.clone();
+                let peak = peak.clone();
+                pool.submit(progress(1, move |mut updater| async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                    updater.update(1);
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    i
+                }))
+            })
+            .collect();
+        let first = tasks[0].progress();
+        assert_eq!(pool.status().queued(), 6);
+        pool.close();
+
+        let run = pool.run();
+        let overall = run.progress();
+        run.await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.status().finished(), 6);
+        for (i, task) in tasks.into_iter().enumerate() {
+            assert_eq!(task.await, Some(i as u64));
+        }
+        let first: Vec<_> = first.map(|update| update.current()).collect().await;
+        assert_eq!(first, [1, 1]);
+        let overall: Vec<_> = overall.collect().await;
+        assert!(overall.last().unwrap().is_completed());
+        assert_eq!(overall.last().unwrap().total(), 6);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let waits: Vec<_> = (1..=20).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&waits, 50), Some(Duration::from_millis(10)));
+        assert_eq!(percentile(&waits, 95), Some(Duration::from_millis(19)));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[tokio::test]
+    async fn test_queue_wait() {
+        let pool = ProgressPool::new(1);
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                pool.submit(progress(1, |mut updater| async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    updater.update(1);
+                }))
+            })
+            .collect();
+        let last = tasks[2].progress();
+        pool.close();
+        pool.run().await;
+
+        let status = pool.status();
+        assert!(status.wait_p50().unwrap() >= Duration::from_millis(10));
+        assert!(status.wait_p95().unwrap() >= Duration::from_millis(20));
+        let waits: Vec<_> = last.map(|update| update.queue_wait()).collect().await;
+        assert!(
+            waits
+                .iter()
+                .all(|wait| *wait >= Some(Duration::from_millis(20)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_closed_pool_rejects_tasks() {
+        let pool = ProgressPool::new(1);
+        pool.close();
+        let task = pool.submit(progress(1, |_| async {}));
+        pool.run().await;
+        assert_eq!(task.await, None);
+    }
+}
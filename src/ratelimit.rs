@@ -0,0 +1,158 @@
+use std::sync::{Arc, Mutex};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+
+use crate::{ProgressUpdate, State, Transport, sync::lock};
+
+/// Throttles updates with a [`governor`] rate limiter.
+///
+/// Unlike [`Granularity`](crate::Granularity), which only knows about one observer, a
+/// rate limit draws from a quota that can be shared: pass the limiter that already
+/// guards an API client or a message queue to [`shared`](Self::shared), and progress
+/// reports count against the same budget as the rest of the application. Apply it to a
+/// transport with [`transport`](Self::transport), so the task sends no more than the
+/// quota allows, or to a stream with
+/// [`ProgressStreamExt::rate_limited`](crate::ProgressStreamExt::rate_limited).
+///
+/// Each working update takes one cell of the quota and is dropped when none is left.
+/// Changes of [`State`], updates carrying a [`ProgressEvent`](crate::ProgressEvent) and
+/// the final update always pass without taking one, so an observer never misses the
+/// outcome.
+///
+/// # Examples
+///
+/// ```
+/// use std::{num::NonZeroU32, sync::Arc};
+/// use governor::{Quota, RateLimiter};
+/// use progressor::{Progress, RateLimit, progress};
+///
+/// # async fn example() {
+/// // Also used by the rest of the application's webhook client.
+/// let webhooks = Arc::new(RateLimiter::direct(Quota::per_second(NonZeroU32::new(10).unwrap())));
+///
+/// let (webhook, remote) = async_broadcast::broadcast(16);
+/// let task = progress(1000, |mut updater| async move {
+///     for i in 1..=1000 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// })
+/// .with_transport(RateLimit::shared(webhooks).transport(webhook));
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "governor")))]
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    limiter: Arc<DefaultDirectRateLimiter>,
+}
+
+impl RateLimit {
+    /// Creates a rate limit with a limiter of its own, allowing `quota`.
+    #[must_use]
+    pub fn new(quota: Quota) -> Self {
+        Self::shared(Arc::new(RateLimiter::direct(quota)))
+    }
+
+    /// Creates a rate limit drawing from `limiter`, which may be shared with other
+    /// subsystems.
+    #[must_use]
+    pub const fn shared(limiter: Arc<DefaultDirectRateLimiter>) -> Self {
+        Self { limiter }
+    }
+
+    /// Returns the limiter the quota is drawn from.
+    #[must_use]
+    pub const fn limiter(&self) -> &Arc<DefaultDirectRateLimiter> {
+        &self.limiter
+    }
+
+    /// Returns a [`Transport`] that delivers updates to `transport` within this limit.
+    #[must_use]
+    pub fn transport(self, transport: impl Transport) -> impl Transport {
+        Limited {
+            gate: Mutex::new(Gate::new(self)),
+            transport,
+        }
+    }
+
+    pub(crate) fn filter<S>(self, stream: S) -> impl Stream<Item = ProgressUpdate>
+    where
+        S: Stream<Item = ProgressUpdate>,
+    {
+        let mut gate = Gate::new(self);
+        stream.filter(move |update| core::future::ready(gate.admit(update)))
+    }
+}
+
+/// The state an observer last heard about, deciding whether an update may skip the quota.
+#[derive(Debug)]
+struct Gate {
+    limit: RateLimit,
+    state: Option<State>,
+}
+
+impl Gate {
+    const fn new(limit: RateLimit) -> Self {
+        Self { limit, state: None }
+    }
+
+    fn admit(&mut self, update: &ProgressUpdate) -> bool {
+        let admitted = self.state != Some(update.state())
+            || update.event().is_some()
+            || update.is_terminal()
+            || self.limit.limiter.check().is_ok();
+        if admitted {
+            self.state = Some(update.state());
+        }
+        admitted
+    }
+}
+
+/// Delivers the updates a [`Gate`] admits to another transport.
+struct Limited<T> {
+    gate: Mutex<Gate>,
+    transport: T,
+}
+
+impl<T: Transport> Transport for Limited<T> {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        let admitted = lock(&self.gate).admit(update);
+        !admitted || self.transport.send(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU32;
+
+    use super::*;
+    use crate::{Progress, ProgressStreamExt, progress};
+
+    #[tokio::test]
+    async fn test_shared_quota() {
+        let shared = Arc::new(RateLimiter::direct(Quota::per_hour(
+            NonZeroU32::new(6).unwrap(),
+        )));
+        let (sender, transported) = async_broadcast::broadcast(16);
+        let task = progress(20, |mut updater| async move {
+            for i in 1..=20 {
+                updater.update(i);
+            }
+            updater.complete();
+        })
+        .with_transport(RateLimit::shared(shared.clone()).transport(sender));
+        let streamed = task.progress().rate_limited(RateLimit::shared(shared));
+        let all = task.progress();
+        task.await;
+
+        assert_eq!(all.count().await, 21);
+        // The first update of each observer is a change of state and takes no cell.
+        let transported: Vec<_> = transported.map(|update| update.current()).collect().await;
+        assert_eq!(transported, [1, 2, 3, 4, 5, 6, 7, 20]);
+        // The transport used up the quota, so the stream only hears about the states.
+        let streamed: Vec<_> = streamed.map(|update| update.current()).collect().await;
+        assert_eq!(streamed, [1, 20]);
+    }
+}
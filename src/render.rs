@@ -0,0 +1,183 @@
+//! Helpers for drawing progress.
+//!
+//! Tasks often report progress only every few hundred milliseconds, which makes a bar
+//! that is redrawn on every update jump. [`Animator`] smooths the displayed value.
+
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use futures_timer::Delay;
+use futures_util::{FutureExt, StreamExt, stream};
+
+use crate::ProgressUpdate;
+
+/// Interval between frames produced by [`Animator::animate`], for 60 frames per second.
+pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Differences below this are considered caught up.
+const EPSILON: f64 = 1e-4;
+
+/// Interpolates the displayed fraction between discrete progress updates.
+///
+/// Each update sets a new target fraction, and the displayed fraction eases toward it
+/// exponentially: after one time constant, about two thirds of the gap is closed.
+/// Completed updates make the target `1.0`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{Progress, progress, render::Animator};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let task = progress(10, |mut updater| async move {
+///     for i in 1..=10 {
+///         tokio::time::sleep(Duration::from_millis(300)).await;
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+///
+/// let mut frames = Animator::default().animate(task.progress());
+/// tokio::spawn(task);
+/// while let Some(fraction) = frames.next().await {
+///     print!("\r{:>5.1}%", fraction * 100.0);
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct Animator {
+    time_constant: Duration,
+    displayed: f64,
+    target: f64,
+    last_frame: Option<Instant>,
+}
+
+impl Default for Animator {
+    /// Eases with a time constant of 150 milliseconds.
+    fn default() -> Self {
+        Self::new(Duration::from_millis(150))
+    }
+}
+
+impl Animator {
+    /// Creates an animator starting at zero that eases with the given time constant.
+    #[must_use]
+    pub const fn new(time_constant: Duration) -> Self {
+        Self {
+            time_constant,
+            displayed: 0.0,
+            target: 0.0,
+            last_frame: None,
+        }
+    }
+
+    /// Sets the fraction to ease toward from an update.
+    pub fn push(&mut self, update: &ProgressUpdate) {
+        self.target = if update.is_completed() {
+            1.0
+        } else {
+            update.completed_fraction()
+        };
+    }
+
+    /// Advances the animation to `now` and returns the fraction to display.
+    pub fn frame(&mut self, now: Instant) -> f64 {
+        let elapsed = self
+            .last_frame
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_frame = Some(now);
+        let eased = if self.time_constant.is_zero() {
+            1.0
+        } else {
+            1.0 - (-elapsed.as_secs_f64() / self.time_constant.as_secs_f64()).exp()
+        };
+        self.displayed += (self.target - self.displayed) * eased;
+        if self.is_settled() {
+            self.displayed = self.target;
+        }
+        self.displayed
+    }
+
+    /// Returns the fraction displayed at the last frame.
+    #[must_use]
+    pub const fn displayed(&self) -> f64 {
+        self.displayed
+    }
+
+    /// Returns `true` once the displayed fraction has caught up with the target.
+    #[must_use]
+    pub fn is_settled(&self) -> bool {
+        (self.target - self.displayed).abs() < EPSILON
+    }
+
+    /// Turns a stream of updates into a stream of fractions to draw, one per [`FRAME`].
+    ///
+    /// The stream ends once `updates` has ended and the animation has caught up.
+    pub fn animate<S>(self, updates: S) -> impl Stream<Item = f64> + Unpin
+    where
+        S: Stream<Item = ProgressUpdate> + Unpin,
+    {
+        Box::pin(stream::unfold(
+            (self, updates, false, true),
+            |(mut animator, mut updates, mut ended, first)| async move {
+                if !first {
+                    Delay::new(FRAME).await;
+                }
+                while !ended {
+                    match updates.next().now_or_never() {
+                        Some(Some(update)) => animator.push(&update),
+                        Some(None) => ended = true,
+                        None => break,
+                    }
+                }
+                if ended && animator.is_settled() && !first {
+                    return None;
+                }
+                let fraction = animator.frame(Instant::now());
+                Some((fraction, (animator, updates, ended, false)))
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+
+    #[test]
+    fn test_eases_toward_target() {
+        let start = Instant::now();
+        let mut animator = Animator::new(Duration::from_millis(100));
+        animator.push(&ProgressUpdate::new(10, 5, State::Working, None));
+        assert!(animator.frame(start).abs() < f64::EPSILON);
+
+        let first = animator.frame(start + Duration::from_millis(100));
+        let expected = 0.5 * (1.0 - (-1.0f64).exp());
+        assert!((first - expected).abs() < 1e-9);
+        let later = animator.frame(start + Duration::from_secs(2));
+        assert!((later - 0.5).abs() < f64::EPSILON);
+        assert!(animator.is_settled());
+
+        animator.push(&ProgressUpdate::new(10, 5, State::Completed, None));
+        assert!(!animator.is_settled());
+    }
+
+    #[tokio::test]
+    async fn test_animate_ends_when_settled() {
+        let updates = stream::iter([
+            ProgressUpdate::new(4, 2, State::Working, None),
+            ProgressUpdate::new(4, 4, State::Completed, None),
+        ]);
+        let frames: Vec<_> = Animator::new(Duration::from_millis(10))
+            .animate(updates)
+            .collect()
+            .await;
+        assert!(frames.len() > 2);
+        assert!(frames.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!((frames.last().unwrap() - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,69 @@
+//! Plumbing shared by [`Supervisor`](crate::Supervisor) and
+//! [`Scheduler`](crate::Scheduler), which run tasks from a factory and forward their
+//! updates as events, and by the observers of [`ProgressExt`](crate::ProgressExt).
+
+#[cfg(feature = "std")]
+use async_broadcast::{InactiveReceiver, Sender, broadcast};
+#[cfg(feature = "std")]
+use futures_core::Stream;
+use futures_util::{FutureExt, StreamExt, pin_mut, select};
+
+use crate::{Progress, ProgressUpdate};
+
+/// The events of a runner, buffering the 32 most recent ones for each observer.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct EventChannel<E> {
+    sender: Sender<E>,
+    receiver: InactiveReceiver<E>,
+}
+
+#[cfg(feature = "std")]
+impl<E: Clone> EventChannel<E> {
+    pub fn new() -> Self {
+        let (mut sender, receiver) = broadcast(32);
+        // Observers that fall behind lose the oldest events rather than stalling the task.
+        sender.set_overflow(true);
+        Self {
+            sender,
+            receiver: receiver.deactivate(),
+        }
+    }
+
+    pub fn subscribe(&self) -> impl Stream<Item = E> + Unpin + Send + 'static
+    where
+        E: Send + Sync + 'static,
+    {
+        self.receiver.activate_cloned()
+    }
+
+    pub fn emit(&self, event: E) {
+        let _ = self.sender.try_broadcast(event);
+    }
+}
+
+/// Runs `task` to completion, passing each of its updates to `forward`.
+pub async fn forward_updates<P: Progress>(
+    task: P,
+    mut forward: impl FnMut(ProgressUpdate),
+) -> P::Output {
+    let updates = task.progress().fuse();
+    let task = task.fuse();
+    pin_mut!(updates, task);
+
+    let output = loop {
+        select! {
+            output = task => break output,
+            update = updates.next() => {
+                if let Some(update) = update {
+                    forward(update);
+                }
+            }
+        }
+    };
+    // Forward updates that were still buffered when the task finished.
+    while let Some(Some(update)) = updates.next().now_or_never() {
+        forward(update);
+    }
+    output
+}
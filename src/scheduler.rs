@@ -0,0 +1,239 @@
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use futures_timer::Delay;
+
+use crate::{
+    DurationStore, Progress, ProgressUpdate,
+    runner::{EventChannel, forward_updates},
+};
+
+/// An event emitted by a [`Scheduler`] while it runs its task.
+///
+/// The event stream interleaves run boundaries with the progress updates reported by
+/// the run in progress.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ScheduleEvent {
+    /// A new run has started. Runs are numbered from 1.
+    Started(u64),
+    /// A progress update reported by the current run.
+    Progress(ProgressUpdate),
+    /// The run with the given number finished after the given duration.
+    Finished(u64, Duration),
+}
+
+/// Runs a progress-tracked task after a delay and, optionally, at a fixed interval.
+///
+/// The scheduler owns a factory that creates a fresh task for every run, and numbers the
+/// runs. The interval is measured from the start of one run to the start of the next;
+/// a run that takes longer than the interval is followed by the next one immediately.
+/// With [`record_durations`](Self::record_durations), the duration of every run is
+/// recorded in a [`DurationStore`], where [`estimate_with`](crate::estimate_with) finds
+/// it. Timers come from `futures-timer`, so the scheduler works on any runtime.
+///
+/// # Examples
+///
+/// ```
+/// use std::{sync::Arc, time::Duration};
+/// use progressor::{MemoryDurationStore, ScheduleEvent, Scheduler, progress};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let store = Arc::new(MemoryDurationStore::new(20));
+/// let scheduler = Scheduler::new(|| {
+///     progress(100, |mut updater| async move {
+///         for i in 1..=100 {
+///             updater.update(i);
+///         }
+///     })
+/// })
+/// .delay(Duration::from_secs(60))
+/// .every(Duration::from_secs(3600))
+/// .record_durations(store.clone(), "hourly-sync");
+///
+/// let mut events = scheduler.events();
+/// tokio::spawn(async move {
+///     while let Some(event) = events.next().await {
+///         if let ScheduleEvent::Finished(run, duration) = event {
+///             println!("run {run} took {duration:?}");
+///         }
+///     }
+/// });
+///
+/// scheduler.run().await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct Scheduler<F> {
+    factory: F,
+    delay: Duration,
+    interval: Option<Duration>,
+    max_runs: Option<u64>,
+    store: Option<(Box<dyn DurationStore + Send>, String)>,
+    events: EventChannel<ScheduleEvent>,
+}
+
+impl<F> core::fmt::Debug for Scheduler<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("delay", &self.delay)
+            .field("interval", &self.interval)
+            .field("max_runs", &self.max_runs)
+            .field("name", &self.store.as_ref().map(|(_, name)| name))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> Scheduler<F> {
+    /// Creates a scheduler that runs the task produced by `factory` once, immediately.
+    #[must_use]
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            delay: Duration::ZERO,
+            interval: None,
+            max_runs: None,
+            store: None,
+            events: EventChannel::new(),
+        }
+    }
+
+    /// Waits for `delay` before the first run.
+    #[must_use]
+    pub const fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Repeats the task every `interval`, without limit unless [`max_runs`](Self::max_runs) is set.
+    #[must_use]
+    pub const fn every(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Limits the number of runs of a recurring task.
+    #[must_use]
+    pub const fn max_runs(mut self, max_runs: u64) -> Self {
+        self.max_runs = Some(max_runs);
+        self
+    }
+
+    /// Records the duration of every run under `name` in `store`.
+    #[must_use]
+    pub fn record_durations(
+        mut self,
+        store: impl DurationStore + Send + 'static,
+        name: impl Into<String>,
+    ) -> Self {
+        self.store = Some((Box::new(store), name.into()));
+        self
+    }
+
+    /// Returns a stream of runs and progress updates across all runs.
+    ///
+    /// The stream ends once the scheduler has finished running. It buffers the 32 most
+    /// recent events; an observer that falls further behind skips the oldest ones.
+    pub fn events(&self) -> impl Stream<Item = ScheduleEvent> + Unpin + Send + 'static {
+        self.events.subscribe()
+    }
+
+    /// Runs the task on schedule, returning the number of runs once the last one finished.
+    ///
+    /// A recurring task without a run limit runs until the returned future is dropped.
+    pub async fn run<P>(self) -> u64
+    where
+        F: FnMut() -> P + Send,
+        P: Progress + Send,
+    {
+        let Self {
+            mut factory,
+            delay,
+            interval,
+            max_runs,
+            store,
+            events,
+        } = self;
+        let max_runs = if interval.is_some() {
+            max_runs
+        } else {
+            Some(1)
+        };
+
+        Delay::new(delay).await;
+        let mut run = 0;
+        loop {
+            run += 1;
+            events.emit(ScheduleEvent::Started(run));
+            let started = Instant::now();
+            forward_updates(factory(), |update| {
+                events.emit(ScheduleEvent::Progress(update));
+            })
+            .await;
+
+            let duration = started.elapsed();
+            if let Some((store, name)) = &store {
+                store.record(name, duration);
+            }
+            events.emit(ScheduleEvent::Finished(run, duration));
+
+            if max_runs.is_some_and(|max| run >= max) {
+                return run;
+            }
+            if let Some(interval) = interval {
+                Delay::new(interval.saturating_sub(duration)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::{MemoryDurationStore, progress};
+
+    #[tokio::test]
+    async fn test_recurring_runs() {
+        let store = std::sync::Arc::new(MemoryDurationStore::new(10));
+        let mut runs = 0;
+        let scheduler = Scheduler::new(move || {
+            runs += 1;
+            let run = runs;
+            progress(10, move |mut updater| async move {
+                updater.update(run);
+            })
+        })
+        .delay(Duration::from_millis(5))
+        .every(Duration::from_millis(5))
+        .max_runs(3)
+        .record_durations(store.clone(), "job");
+        let events = scheduler.events();
+
+        assert_eq!(scheduler.run().await, 3);
+
+        let events: Vec<_> = events.collect().await;
+        let started: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                ScheduleEvent::Started(run) => Some(*run),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(started, [1, 2, 3]);
+        assert!(events.iter().any(
+            |event| matches!(event, ScheduleEvent::Progress(update) if update.current() == 2)
+        ));
+        assert!(matches!(events.last(), Some(ScheduleEvent::Finished(3, _))));
+        assert_eq!(store.durations("job").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_runs_once_without_interval() {
+        let scheduler = Scheduler::new(|| progress(1, |_| async {})).max_runs(5);
+        assert_eq!(scheduler.run().await, 1);
+    }
+}
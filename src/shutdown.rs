@@ -0,0 +1,172 @@
+//! Graceful shutdown of progress-tracked tasks.
+//!
+//! Register tasks with a [`Coordinator`] as they are created. When the application shuts
+//! down, [`Coordinator::shutdown`] stops accepting new tasks, gives running ones a grace
+//! period to finish, cancels the rest and reports how far draining has come.
+
+use core::future::Future;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use futures_timer::Delay;
+use futures_util::{FutureExt, StreamExt, select, stream::FuturesUnordered};
+
+use crate::{CancelReason, ProgressController, ProgressFuture, progress, sync::lock};
+
+/// Tracks running tasks so they can be drained on shutdown.
+///
+/// The coordinator is cheap to clone; all clones share the same set of tasks.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{ProgressExt, progress, shutdown::Coordinator};
+///
+/// # async fn example() {
+/// let coordinator = Coordinator::new();
+///
+/// let task = progress(100, |updater| async move {
+///     let reason = updater.cancelled().await;
+///     println!("stopping: {reason:?}");
+/// });
+/// coordinator.register(&task);
+/// tokio::spawn(task);
+///
+/// coordinator
+///     .shutdown(Duration::from_secs(5))
+///     .observe(|update| println!("drained {}/{} tasks", update.current(), update.total()))
+///     .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Default)]
+pub struct Coordinator {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    tasks: Vec<ProgressController>,
+    shutting_down: bool,
+}
+
+impl Coordinator {
+    /// Creates a coordinator with no tasks.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a task to be drained on shutdown.
+    ///
+    /// Once shutdown has begun, no new work is accepted: the task is cancelled right away
+    /// with [`CancelReason::Shutdown`] and `false` is returned.
+    pub fn register<Fut: Future>(&self, task: &ProgressFuture<Fut>) -> bool {
+        let controller = task.controller();
+        let mut inner = self.lock();
+        if inner.shutting_down {
+            drop(inner);
+            controller.cancel(CancelReason::Shutdown);
+            return false;
+        }
+        inner.tasks.retain(|task| !task.is_finished());
+        inner.tasks.push(controller);
+        true
+    }
+
+    /// Returns `true` once [`shutdown`](Self::shutdown) has been called.
+    #[must_use]
+    pub fn is_shutting_down(&self) -> bool {
+        self.lock().shutting_down
+    }
+
+    /// Begins shutting down and drains the registered tasks.
+    ///
+    /// New registrations are refused from now on. Running tasks get `grace` to finish on
+    /// their own; after that, the remaining ones are cancelled with
+    /// [`CancelReason::Shutdown`]. The returned future reports the number of tasks that
+    /// have finished out of those still running when shutdown began, and resolves once
+    /// all of them have reached a terminal state.
+    #[must_use]
+    pub fn shutdown(&self, grace: Duration) -> ProgressFuture<impl Future<Output = ()> + Send> {
+        let tasks: Vec<_> = {
+            let mut inner = self.lock();
+            inner.shutting_down = true;
+            core::mem::take(&mut inner.tasks)
+        };
+        let tasks: Vec<_> = tasks
+            .into_iter()
+            .filter(|task| !task.is_finished())
+            .collect();
+        progress(tasks.len() as u64, move |mut updater| async move {
+            let mut pending: FuturesUnordered<_> =
+                tasks.iter().map(ProgressController::finished).collect();
+            let mut grace = Delay::new(grace).fuse();
+            let mut drained = 0;
+            loop {
+                select! {
+                    () = grace => {
+                        for task in &tasks {
+                            task.cancel(CancelReason::Shutdown);
+                        }
+                    }
+                    finished = pending.next() => {
+                        if finished.is_none() {
+                            break;
+                        }
+                        drained += 1;
+                        updater.update(drained);
+                    }
+                }
+            }
+            updater.complete();
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        lock(&self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Progress;
+
+    #[tokio::test]
+    async fn test_shutdown_drains_tasks() {
+        let coordinator = Coordinator::new();
+        let quick = progress(1, |mut updater| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            updater.complete();
+        });
+        let stubborn = progress(1, |updater| async move { updater.cancelled().await });
+        let stubborn_controller = stubborn.controller();
+        assert!(coordinator.register(&quick));
+        assert!(coordinator.register(&stubborn));
+        tokio::spawn(quick);
+        let stubborn = tokio::spawn(stubborn);
+
+        let drain = coordinator.shutdown(Duration::from_millis(50));
+        let updates = drain.progress();
+        drain.await;
+
+        assert_eq!(stubborn.await.unwrap(), CancelReason::Shutdown);
+        assert_eq!(
+            stubborn_controller.cancel_reason(),
+            Some(CancelReason::Shutdown)
+        );
+        let counts: Vec<_> = updates.map(|update| update.current()).collect().await;
+        assert_eq!(counts, [1, 2, 2]);
+
+        let late = progress(1, |_| async {});
+        assert!(!coordinator.register(&late));
+        assert_eq!(
+            late.controller().cancel_reason(),
+            Some(CancelReason::Shutdown)
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use async_broadcast::InactiveReceiver;
+use futures_core::Stream;
+use futures_sink::Sink;
+use pin_project_lite::pin_project;
+
+use crate::{ProgressUpdate, ProgressUpdater, updater::detached};
+
+pin_project! {
+    /// A [`Sink`] that reports how many items were sent into it.
+    ///
+    /// Created by [`ProgressSinkExt::inspect_progress`]. Every item accepted by the
+    /// wrapped sink advances the current value by one, against the expected total.
+    /// Closing the sink completes the progress; dropping it without closing reports
+    /// the operation as cancelled.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[derive(Debug)]
+    pub struct ProgressSink<S> {
+        #[pin]
+        sink: S,
+        updater: ProgressUpdater,
+        receiver: InactiveReceiver<ProgressUpdate>,
+    }
+}
+
+impl<S> ProgressSink<S> {
+    /// Wraps `sink`, expecting `total` items to be sent.
+    #[must_use]
+    pub fn new(sink: S, total: u64) -> Self {
+        let (updater, receiver) = detached(total);
+        Self {
+            sink,
+            updater,
+            receiver,
+        }
+    }
+
+    /// Returns a stream of the updates reported as items are sent.
+    pub fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.receiver.activate_cloned()
+    }
+
+    /// Returns the number of items sent so far.
+    #[must_use]
+    pub fn sent(&self) -> u64 {
+        self.updater.current()
+    }
+
+    /// Returns a reference to the wrapped sink.
+    #[must_use]
+    pub const fn get_ref(&self) -> &S {
+        &self.sink
+    }
+}
+
+impl<S, Item> Sink<Item> for ProgressSink<S>
+where
+    S: Sink<Item>,
+{
+    type Error = S::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().sink.poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        let this = self.project();
+        this.sink.start_send(item)?;
+        let sent = this.updater.current().saturating_add(1);
+        this.updater.update(sent);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.project();
+        ready!(this.sink.poll_close(cx))?;
+        this.updater.complete();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Extension trait for instrumenting any [`Sink`] with progress reporting.
+///
+/// Together with a progress-tracked producer, this lets both ends of a channel-based
+/// pipeline report how far they are.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::ProgressSinkExt;
+/// use futures_util::{SinkExt, StreamExt};
+///
+/// # async fn example() {
+/// let mut sink = Vec::new().inspect_progress(3);
+/// let mut updates = sink.progress();
+///
+/// for row in ["a", "b", "c"] {
+///     sink.send(row).await.unwrap();
+/// }
+/// sink.close().await.unwrap();
+///
+/// while let Some(update) = updates.next().await {
+///     println!("{}/{} rows written", update.current(), update.total());
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub trait ProgressSinkExt<Item>: Sink<Item> {
+    /// Wraps this sink to report the number of items sent against `total`.
+    fn inspect_progress(self, total: u64) -> ProgressSink<Self>
+    where
+        Self: Sized,
+    {
+        ProgressSink::new(self, total)
+    }
+}
+
+impl<S: Sink<Item>, Item> ProgressSinkExt<Item> for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+
+    #[tokio::test]
+    async fn test_sink_progress() {
+        let mut sink = Vec::new().inspect_progress(2);
+        let updates = sink.progress();
+        sink.send(1).await.unwrap();
+        sink.send(2).await.unwrap();
+        sink.close().await.unwrap();
+        assert_eq!(sink.sent(), 2);
+        assert_eq!(sink.get_ref(), &[1, 2]);
+        drop(sink);
+
+        let updates: Vec<_> = updates.collect().await;
+        let counts: Vec<_> = updates.iter().map(ProgressUpdate::current).collect();
+        assert_eq!(counts, [1, 2, 2]);
+        assert!(updates[2].is_completed());
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::sync::lock;
+
+/// Remembers how long named tasks took, for [`estimate_with`](crate::estimate_with).
+///
+/// Implement this to persist durations across process runs, for example in a file or a
+/// database. [`MemoryDurationStore`] keeps them in memory.
+pub trait DurationStore {
+    /// Returns the durations recorded for `name`.
+    fn durations(&self, name: &str) -> Vec<Duration>;
+
+    /// Records that a run of `name` took `duration`.
+    fn record(&self, name: &str, duration: Duration);
+}
+
+impl<S: DurationStore + ?Sized> DurationStore for &S {
+    fn durations(&self, name: &str) -> Vec<Duration> {
+        (**self).durations(name)
+    }
+
+    fn record(&self, name: &str, duration: Duration) {
+        (**self).record(name, duration);
+    }
+}
+
+impl<S: DurationStore + ?Sized> DurationStore for Arc<S> {
+    fn durations(&self, name: &str) -> Vec<Duration> {
+        (**self).durations(name)
+    }
+
+    fn record(&self, name: &str, duration: Duration) {
+        (**self).record(name, duration);
+    }
+}
+
+/// A [`DurationStore`] that keeps the most recent durations of each task in memory.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Default)]
+pub struct MemoryDurationStore {
+    capacity: usize,
+    durations: Mutex<HashMap<String, VecDeque<Duration>>>,
+}
+
+impl MemoryDurationStore {
+    /// Creates a store that retains the last `capacity` durations per task name.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            durations: Mutex::default(),
+        }
+    }
+}
+
+impl DurationStore for MemoryDurationStore {
+    fn durations(&self, name: &str) -> Vec<Duration> {
+        lock(&self.durations)
+            .get(name)
+            .map(|durations| durations.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, name: &str, duration: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut store = lock(&self.durations);
+        let durations = store.entry(name.to_owned()).or_default();
+        if durations.len() == self.capacity {
+            durations.pop_front();
+        }
+        durations.push_back(duration);
+        drop(store);
+    }
+}
@@ -0,0 +1,305 @@
+use core::future::ready;
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+#[cfg(feature = "governor")]
+use crate::RateLimit;
+#[cfg(feature = "std")]
+use crate::{EstimatedUpdate, Granularity, ProgressValidator, TotalEstimator, eta, infer};
+use crate::{ProgressUpdate, diff::signed_delta};
+
+/// Extension trait providing adapters for streams of progress updates.
+///
+/// This is implemented for every [`Stream`] of [`ProgressUpdate`]s, such as the one
+/// returned by [`Progress::progress`](crate::Progress::progress).
+pub trait ProgressStreamExt: Stream<Item = ProgressUpdate> {
+    /// Pairs every update with how far the current value moved since the previous one.
+    ///
+    /// The first update is compared against zero. This is useful for consumers that
+    /// compute rates or bill incrementally, such as bytes transferred since the last tick.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// use progressor::{Progress, ProgressStreamExt, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(1024, |mut updater| async move {
+    ///     for transferred in [256, 768, 1024] {
+    ///         updater.update(transferred);
+    ///     }
+    /// });
+    ///
+    /// let mut deltas = task.progress().deltas();
+    /// tokio::spawn(task);
+    /// while let Some((delta, update)) = deltas.next().await {
+    ///     println!("+{delta} bytes ({}/{})", update.current(), update.total());
+    /// }
+    /// # }
+    /// # }
+    /// ```
+    fn deltas(self) -> impl Stream<Item = (i64, ProgressUpdate)>
+    where
+        Self: Sized,
+    {
+        self.scan(0, |previous, update| {
+            let delta = signed_delta(*previous, update.current());
+            *previous = update.current();
+            ready(Some((delta, update)))
+        })
+    }
+
+    /// Yields the whole percentage done, only when it changes.
+    ///
+    /// At most 101 items are yielded however often the task reports, which suits
+    /// observers that notify people, such as webhooks, pagers or chat messages. Updates
+    /// with an unknown total count as 0%, and a current value beyond the total as 100%.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// use progressor::{Progress, ProgressStreamExt, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(1000, |mut updater| async move {
+    ///     for i in 1..=1000 {
+    ///         updater.update(i);
+    ///     }
+    /// });
+    ///
+    /// let mut percents = task.progress().percent_changes();
+    /// tokio::spawn(task);
+    /// while let Some(percent) = percents.next().await {
+    ///     if percent % 10 == 0 {
+    ///         println!("{percent}% done");
+    ///     }
+    /// }
+    /// # }
+    /// # }
+    /// ```
+    fn percent_changes(self) -> impl Stream<Item = u8>
+    where
+        Self: Sized,
+    {
+        let mut last = None;
+        self.filter_map(move |update| {
+            let percent = percent(&update);
+            let changed = last != Some(percent);
+            last = Some(percent);
+            ready(changed.then_some(percent))
+        })
+    }
+
+    /// Fills in a provisional total for updates whose total is unknown.
+    ///
+    /// Working updates with a total of zero get the total guessed by `estimator`, such as
+    /// a [`DecelerationEstimator`](crate::DecelerationEstimator), and are flagged with
+    /// [`is_total_estimated`](ProgressUpdate::is_total_estimated) so interfaces can
+    /// render the total as approximate. Updates with a known total pass through.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{DecelerationEstimator, Progress, ProgressStreamExt, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(0, |mut updater| async move {
+    ///     for scanned in 1..=500 {
+    ///         updater.update(scanned);
+    ///     }
+    ///     updater.complete();
+    /// });
+    ///
+    /// let mut updates = task.progress().infer_total(DecelerationEstimator::default());
+    /// tokio::spawn(task);
+    /// while let Some(update) = updates.next().await {
+    ///     let approx = if update.is_total_estimated() { "~" } else { "" };
+    ///     println!("{} of {approx}{}", update.current(), update.total());
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn infer_total(self, estimator: impl TotalEstimator) -> impl Stream<Item = ProgressUpdate>
+    where
+        Self: Sized,
+    {
+        infer::infer_total(self, estimator)
+    }
+
+    /// Attaches the rate and estimated remaining time to every update.
+    ///
+    /// Rates are measured from the first update this stream yields. Time spent
+    /// [`Paused`](crate::State::Paused) is tracked separately: the rate and the
+    /// [`eta`](EstimatedUpdate::eta) only count
+    /// [`active_elapsed`](EstimatedUpdate::active_elapsed) time, while
+    /// [`wall_elapsed`](EstimatedUpdate::wall_elapsed) includes pauses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{Progress, ProgressStreamExt, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// });
+    ///
+    /// let mut updates = task.progress().estimated();
+    /// tokio::spawn(task);
+    /// while let Some(estimate) = updates.next().await {
+    ///     if let Some(eta) = estimate.eta() {
+    ///         println!("{}% done, {eta:?} left", estimate.update().current());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn estimated(self) -> impl Stream<Item = EstimatedUpdate>
+    where
+        Self: Sized,
+    {
+        eta::estimated(self)
+    }
+
+    /// Keeps only the updates an observer at `granularity` wants to hear about.
+    ///
+    /// This lets observers of the same task see its progress at different rates, such
+    /// as a fine-grained local view next to a coarse summary.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{Granularity, Progress, ProgressStreamExt, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// });
+    ///
+    /// let mut quarters = task.progress().with_granularity(Granularity::new().with_min_step(0.25));
+    /// tokio::spawn(task);
+    /// while let Some(update) = quarters.next().await {
+    ///     println!("{}%", update.completed_fraction() * 100.0);
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn with_granularity(self, granularity: Granularity) -> impl Stream<Item = ProgressUpdate>
+    where
+        Self: Sized,
+    {
+        granularity.filter(self)
+    }
+
+    /// Keeps the updates that fit within `limit`, see [`RateLimit`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use governor::Quota;
+    /// use progressor::{Progress, ProgressStreamExt, RateLimit, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// });
+    ///
+    /// let limit = RateLimit::new(Quota::per_second(NonZeroU32::new(4).unwrap()));
+    /// let mut updates = task.progress().rate_limited(limit);
+    /// tokio::spawn(task);
+    /// while let Some(update) = updates.next().await {
+    ///     println!("{}/{}", update.current(), update.total());
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "governor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "governor")))]
+    fn rate_limited(self, limit: RateLimit) -> impl Stream<Item = ProgressUpdate>
+    where
+        Self: Sized,
+    {
+        limit.filter(self)
+    }
+
+    /// Checks every update for violated invariants, see [`ProgressValidator`].
+    ///
+    /// By default a violation panics in debug builds and is logged to standard error in
+    /// release builds. Use [`ProgressValidator::with_handler`] to handle them otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use progressor::{Progress, ProgressStreamExt, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(10, |mut updater| async move {
+    ///     updater.update(10);
+    ///     updater.complete();
+    /// });
+    ///
+    /// let updates = task.progress().validate();
+    /// task.await;
+    /// assert_eq!(updates.count().await, 2);
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn validate(self) -> ProgressValidator<Self>
+    where
+        Self: Sized,
+    {
+        ProgressValidator::new(self)
+    }
+}
+
+impl<S: Stream<Item = ProgressUpdate>> ProgressStreamExt for S {}
+
+/// Returns the whole percentage of `update` done, rounded down and capped at 100.
+pub fn percent(update: &ProgressUpdate) -> u8 {
+    if update.total() == 0 {
+        return 0;
+    }
+    let percent = u128::from(update.current()) * 100 / u128::from(update.total());
+    u8::try_from(percent.min(100)).unwrap_or(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::State;
+
+    #[tokio::test]
+    async fn test_percent_changes() {
+        let updates = [0, 1, 9, 10, 11, 25, 999, 1000, 1200]
+            .map(|current| ProgressUpdate::new(1000, current, State::Working, None));
+        let percents: Vec<_> = stream::iter(updates).percent_changes().collect().await;
+        assert_eq!(percents, [0, 1, 2, 99, 100]);
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::{Duration, Instant};
+
+use crate::{ProgressUpdate, eta::PauseClock};
+
+/// Rates are only sampled over intervals at least this long, so two updates sent in
+/// quick succession do not produce an absurd peak.
+const RATE_WINDOW: Duration = Duration::from_millis(100);
+
+/// Statistics about a finished progress-tracked operation.
+///
+/// Returned by [`ProgressExt::observe_summary`](crate::ProgressExt::observe_summary),
+/// so command-line tools can print a completion report without their own bookkeeping.
+/// Rates are in progress units per second.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProgressSummary {
+    duration: Duration,
+    paused_duration: Duration,
+    pauses: u32,
+    updates: u64,
+    progressed: u64,
+    peak_rate: f64,
+}
+
+impl ProgressSummary {
+    /// Returns the wall-clock time from the start of observation to completion.
+    #[must_use]
+    pub const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the total time spent in the [`Paused`](crate::State::Paused) state.
+    #[must_use]
+    pub const fn paused_duration(&self) -> Duration {
+        self.paused_duration
+    }
+
+    /// Returns how many times the operation was paused.
+    #[must_use]
+    pub const fn pauses(&self) -> u32 {
+        self.pauses
+    }
+
+    /// Returns the number of updates received.
+    #[must_use]
+    pub const fn updates(&self) -> u64 {
+        self.updates
+    }
+
+    /// Returns the average rate while not paused.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_rate(&self) -> f64 {
+        let active = self.duration.saturating_sub(self.paused_duration);
+        if active.is_zero() {
+            0.0
+        } else {
+            self.progressed as f64 / active.as_secs_f64()
+        }
+    }
+
+    /// Returns the highest rate seen over any interval of at least 100 milliseconds.
+    #[must_use]
+    pub const fn peak_rate(&self) -> f64 {
+        self.peak_rate
+    }
+}
+
+/// Accumulates a [`ProgressSummary`] from observed updates.
+#[derive(Debug)]
+pub struct SummaryRecorder {
+    started: Instant,
+    clock: PauseClock,
+    sample: Option<(Instant, u64)>,
+    summary: ProgressSummary,
+}
+
+impl SummaryRecorder {
+    pub fn new() -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            clock: PauseClock::new(started),
+            sample: None,
+            summary: ProgressSummary::default(),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn record(&mut self, update: &ProgressUpdate) {
+        let now = Instant::now();
+        self.summary.updates += 1;
+        self.summary.progressed = update.current();
+
+        if self.clock.observe(update, now) {
+            self.summary.pauses += 1;
+        }
+
+        let (sampled_at, sampled) = *self.sample.get_or_insert((self.started, 0));
+        let elapsed = now - sampled_at;
+        if elapsed >= RATE_WINDOW {
+            if !update.is_paused() {
+                let rate = update.current().saturating_sub(sampled) as f64 / elapsed.as_secs_f64();
+                self.summary.peak_rate = self.summary.peak_rate.max(rate);
+            }
+            self.sample = Some((now, update.current()));
+        }
+    }
+
+    pub fn finish(mut self) -> ProgressSummary {
+        let now = Instant::now();
+        self.summary.paused_duration = self.clock.paused(now);
+        self.summary.duration = self.clock.wall(now);
+        self.summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ProgressExt, progress};
+
+    #[tokio::test]
+    async fn test_observe_summary() {
+        let (result, summary) = progress(10, |mut updater| async move {
+            updater.update(3);
+            updater.pause();
+            updater.update(6);
+            updater.update(10);
+            updater.complete();
+            "done"
+        })
+        .observe_summary(|_| {})
+        .await;
+
+        assert_eq!(result, "done");
+        assert_eq!(summary.updates(), 5);
+        assert_eq!(summary.pauses(), 1);
+        assert!(summary.paused_duration() <= summary.duration());
+        assert!(summary.average_rate() >= 0.0);
+    }
+}
@@ -0,0 +1,298 @@
+use core::future::{Future, Ready, ready};
+
+use futures_core::Stream;
+
+use crate::{
+    CancelReason, Progress, ProgressUpdate, State,
+    runner::{EventChannel, forward_updates},
+};
+
+/// An event emitted by a [`Supervisor`] while it runs its task.
+///
+/// The event stream interleaves attempt boundaries with the progress updates
+/// reported by the attempt that is currently running.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+// Progress updates make up almost all events, so boxing them would not save anything.
+#[allow(clippy::large_enum_variant)]
+pub enum SupervisorEvent {
+    /// A new attempt has started. Attempts are numbered from 1.
+    Attempt(u32),
+    /// A progress update reported by the current attempt.
+    Progress(ProgressUpdate),
+    /// The attempt with the given number finished with an error, or reported itself
+    /// cancelled or failed.
+    Failed(u32),
+}
+
+/// Runs a progress-tracked task and restarts it when it fails.
+///
+/// The supervisor owns a factory that creates a fresh task for every attempt.
+/// A task signals failure by resolving to `Err` or by reporting itself
+/// [`Failed`](crate::State::Failed). A task reported
+/// [`Cancelled`](crate::State::Cancelled) with a reason, such as a timeout, is restarted
+/// too, unless the reason is [`Shutdown`](CancelReason::Shutdown). Between attempts the supervisor
+/// awaits a user-provided backoff future, which keeps it independent of any
+/// particular async runtime.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{Supervisor, SupervisorEvent, progress};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let mut runs = 0;
+/// let supervisor = Supervisor::new(move || {
+///     runs += 1;
+///     let run = runs;
+///     progress(10, move |mut updater| async move {
+///         updater.update(5);
+///         if run < 3 { Err("connection reset") } else { Ok("synced") }
+///     })
+/// })
+/// .max_attempts(5)
+/// .backoff(|attempt| tokio::time::sleep(tokio::time::Duration::from_millis(10 << attempt)));
+///
+/// let mut events = supervisor.events();
+/// tokio::spawn(async move {
+///     while let Some(event) = events.next().await {
+///         if let SupervisorEvent::Failed(attempt) = event {
+///             println!("attempt {attempt} failed, retrying");
+///         }
+///     }
+/// });
+///
+/// assert_eq!(supervisor.run().await, Ok("synced"));
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct Supervisor<F, B = fn(u32) -> Ready<()>> {
+    factory: F,
+    backoff: B,
+    max_attempts: Option<u32>,
+    events: EventChannel<SupervisorEvent>,
+}
+
+impl<F> Supervisor<F> {
+    /// Creates a supervisor that restarts tasks produced by `factory`.
+    ///
+    /// By default the task is retried immediately and without limit.
+    #[must_use]
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            backoff: |_| ready(()),
+            max_attempts: None,
+            events: EventChannel::new(),
+        }
+    }
+}
+
+impl<F, B> Supervisor<F, B> {
+    /// Limits the total number of attempts, including the first one.
+    ///
+    /// Once the limit is reached, the result of the last attempt is returned.
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Sets the backoff awaited after a failed attempt.
+    ///
+    /// The closure receives the number of the attempt that just failed and
+    /// returns a future, typically a timer from the runtime in use.
+    pub fn backoff<B2, Fut>(self, backoff: B2) -> Supervisor<F, B2>
+    where
+        B2: FnMut(u32) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        Supervisor {
+            factory: self.factory,
+            backoff,
+            max_attempts: self.max_attempts,
+            events: self.events,
+        }
+    }
+
+    /// Returns a stream of attempts and progress updates across all attempts.
+    ///
+    /// The stream ends once the supervisor has finished running. It buffers the 32 most
+    /// recent events; an observer that falls further behind skips the oldest ones.
+    pub fn events(&self) -> impl Stream<Item = SupervisorEvent> + Unpin + Send + 'static {
+        self.events.subscribe()
+    }
+
+    /// Runs the task until it succeeds or the attempt limit is reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt if every allowed attempt failed with one.
+    pub async fn run<P, T, E, Fut>(self) -> Result<T, E>
+    where
+        F: FnMut() -> P + Send,
+        P: Progress<Output = Result<T, E>> + Send,
+        B: FnMut(u32) -> Fut + Send,
+        Fut: Future<Output = ()> + Send,
+    {
+        let Self {
+            mut factory,
+            mut backoff,
+            max_attempts,
+            events,
+        } = self;
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            events.emit(SupervisorEvent::Attempt(attempt));
+
+            let task = factory();
+            let controller = task.controller();
+            let mut last = None;
+            let result = forward_updates(task, |update: ProgressUpdate| {
+                last = Some((update.state(), update.cancel_reason()));
+                events.emit(SupervisorEvent::Progress(update));
+            })
+            .await;
+
+            // The progress stream drops updates once its buffer is full, so how the
+            // attempt ended is read from its controller where there is one.
+            let ending = controller.map_or(last, |controller| {
+                controller
+                    .final_state()
+                    .map(|state| (state, controller.cancel_reason()))
+            });
+            let reported_failure = ending.is_some_and(|(state, reason)| match state {
+                State::Failed => true,
+                State::Cancelled => reason.is_some_and(|reason| reason != CancelReason::Shutdown),
+                _ => false,
+            });
+            if result.is_ok() && !reported_failure {
+                return result;
+            }
+            events.emit(SupervisorEvent::Failed(attempt));
+            if max_attempts.is_some_and(|max| attempt >= max) {
+                return result;
+            }
+            backoff(attempt).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::progress;
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let mut runs = 0;
+        let supervisor = Supervisor::new(move || {
+            runs += 1;
+            let run = runs;
+            progress(10, move |mut updater| async move {
+                updater.update(run);
+                if run < 3 { Err(run) } else { Ok(run) }
+            })
+        });
+        let events = supervisor.events();
+
+        assert_eq!(supervisor.run().await, Ok(3));
+
+        let events: Vec<_> = events.collect().await;
+        let attempts = events
+            .iter()
+            .filter(|event| matches!(event, SupervisorEvent::Attempt(_)))
+            .count();
+        assert_eq!(attempts, 3);
+        assert!(events.contains(&SupervisorEvent::Failed(2)));
+        assert!(!events.contains(&SupervisorEvent::Failed(3)));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let supervisor =
+            Supervisor::new(|| progress(1, |_| async { Err::<(), _>("boom") })).max_attempts(2);
+        let events = supervisor.events();
+
+        assert_eq!(supervisor.run().await, Err("boom"));
+
+        let events: Vec<_> = events.collect().await;
+        assert_eq!(events.last(), Some(&SupervisorEvent::Failed(2)));
+    }
+
+    #[tokio::test]
+    async fn test_restarts_reported_failures() {
+        let mut runs = 0;
+        let supervisor = Supervisor::new(move || {
+            runs += 1;
+            let run = runs;
+            progress(10, move |mut updater| async move {
+                match run {
+                    1 => updater.fail_with_message("disk full"),
+                    2 => updater.cancel_with_reason(CancelReason::Timeout),
+                    _ => updater.complete(),
+                }
+                Ok::<_, ()>(run)
+            })
+        });
+        let events = supervisor.events();
+
+        assert_eq!(supervisor.run().await, Ok(3));
+
+        let events: Vec<_> = events.collect().await;
+        assert!(events.contains(&SupervisorEvent::Failed(1)));
+        assert!(events.contains(&SupervisorEvent::Failed(2)));
+        assert!(!events.contains(&SupervisorEvent::Failed(3)));
+    }
+
+    #[tokio::test]
+    async fn test_restarts_failures_past_a_full_buffer() {
+        let mut runs = 0;
+        let supervisor = Supervisor::new(move || {
+            runs += 1;
+            let run = runs;
+            progress(100, move |mut updater| async move {
+                // More updates than the task's stream buffers, all sent before it is polled.
+                for current in 1..=40 {
+                    updater.update(current);
+                }
+                match run {
+                    1 => updater.fail_with_message("disk full"),
+                    2 => updater.cancel_with_reason(CancelReason::Timeout),
+                    _ => updater.complete(),
+                }
+                Ok::<_, ()>(run)
+            })
+        });
+
+        assert_eq!(supervisor.run().await, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn test_slow_observer_sees_latest_events() {
+        let supervisor = Supervisor::new(|| {
+            progress(100, |mut updater| async move {
+                for i in 1..=100 {
+                    updater.update(i);
+                    tokio::task::yield_now().await;
+                }
+                updater.complete();
+                Ok::<_, ()>(())
+            })
+        });
+        let events = supervisor.events();
+
+        assert_eq!(supervisor.run().await, Ok(()));
+
+        let events: Vec<_> = events.collect().await;
+        assert_eq!(events.len(), 32, "{events:?}");
+        let last = events.last().unwrap();
+        assert!(matches!(last, SupervisorEvent::Progress(update) if update.is_completed()));
+    }
+}
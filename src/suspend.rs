@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+/// Measures how long the system was suspended, such as a laptop going to sleep.
+///
+/// On Linux and macOS, [`Instant`] already stops while the system is suspended, so there
+/// is nothing to subtract. On Windows, [`Instant`] keeps running; with the
+/// `suspend-aware` feature the suspended time is the difference to the interrupt time,
+/// which excludes sleep and hibernation.
+#[derive(Debug, Clone, Copy)]
+pub struct SuspendTracker {
+    started: Instant,
+    #[cfg(all(windows, feature = "suspend-aware"))]
+    unbiased_started: u64,
+}
+
+impl SuspendTracker {
+    // Reading the interrupt time on Windows cannot be done in a const context.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            #[cfg(all(windows, feature = "suspend-aware"))]
+            unbiased_started: unbiased_interrupt_time(),
+        }
+    }
+
+    /// Returns how long the system was suspended between the start and `now`.
+    #[cfg(all(windows, feature = "suspend-aware"))]
+    pub fn suspended(&self, now: Instant) -> Duration {
+        // The interrupt time counts in units of 100 nanoseconds.
+        let awake = unbiased_interrupt_time().saturating_sub(self.unbiased_started);
+        let awake = Duration::from_nanos(awake.saturating_mul(100));
+        now.saturating_duration_since(self.started)
+            .saturating_sub(awake)
+    }
+
+    /// Returns how long the system was suspended between the start and `now`.
+    #[cfg(not(all(windows, feature = "suspend-aware")))]
+    pub const fn suspended(&self, now: Instant) -> Duration {
+        let _ = (self.started, now);
+        Duration::ZERO
+    }
+}
+
+#[cfg(all(windows, feature = "suspend-aware"))]
+fn unbiased_interrupt_time() -> u64 {
+    use windows_sys::Win32::System::WindowsProgramming::QueryUnbiasedInterruptTime;
+
+    let mut time = 0;
+    // SAFETY: `time` is a valid, writable u64 for the duration of the call.
+    unsafe { QueryUnbiasedInterruptTime(&mut time) };
+    time
+}
@@ -0,0 +1,187 @@
+//! Deterministic driving of progress-tracked tasks in tests.
+//!
+//! Tests of pause and cancellation races flake when they depend on how a multi-threaded
+//! runtime happens to schedule the task and its observer. The [`Driver`] runs both on the
+//! calling thread in a fixed order: poll the task once, then hand every update it
+//! reported to the observer, and repeat. Feedback the observer gives through a
+//! [`ProgressController`](crate::ProgressController) is therefore always seen by the
+//! task at its next poll.
+//!
+//! The driver is not an async runtime: tasks that rely on runtime services such as
+//! timers or I/O need a runtime. Everything else, including tasks that only yield or
+//! wait on channels fed by other threads, works.
+
+use core::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    sync::Arc,
+    task::Wake,
+    thread::{self, Thread},
+};
+
+use futures_core::Stream;
+use futures_util::{FutureExt, StreamExt};
+
+use crate::{Progress, ProgressUpdate};
+
+/// Wakes the driving thread when the task can make progress.
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Steps a progress-tracked task and its updates in a reproducible order.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{CancelReason, progress, test_util::Driver};
+///
+/// let task = progress(10, |mut updater| async move {
+///     for i in 1..=10 {
+///         if updater.cancel_requested().is_some() {
+///             return i;
+///         }
+///         updater.update(i);
+///         tokio::task::yield_now().await;
+///     }
+///     10
+/// });
+/// let controller = task.controller();
+/// let mut driver = Driver::new(task);
+///
+/// // The first poll reports 1, then yields.
+/// assert_eq!(driver.step().len(), 1);
+/// controller.cancel(CancelReason::UserRequest);
+/// driver.step();
+/// assert_eq!(driver.into_output(), Some(2));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub struct Driver<P: Progress> {
+    task: Pin<Box<P>>,
+    updates: Pin<Box<dyn Stream<Item = ProgressUpdate> + Send>>,
+    output: Option<P::Output>,
+    waker: Waker,
+}
+
+impl<P: Progress> core::fmt::Debug for Driver<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Driver")
+            .field("finished", &self.is_finished())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: Progress> Driver<P> {
+    /// Prepares to drive `task` on the current thread.
+    pub fn new(task: P) -> Self {
+        let updates = Box::pin(task.progress());
+        Self {
+            task: Box::pin(task),
+            updates,
+            output: None,
+            waker: Waker::from(Arc::new(Unpark(thread::current()))),
+        }
+    }
+
+    /// Polls the task once and returns the updates it reported, in order.
+    ///
+    /// Once the task has finished, this only returns updates that are still buffered.
+    pub fn step(&mut self) -> Vec<ProgressUpdate> {
+        if self.output.is_none() {
+            let mut cx = Context::from_waker(&self.waker);
+            if let Poll::Ready(output) = self.task.as_mut().poll(&mut cx) {
+                self.output = Some(output);
+            }
+        }
+        let mut updates = Vec::new();
+        while let Some(Some(update)) = self.updates.next().now_or_never() {
+            updates.push(update);
+        }
+        updates
+    }
+
+    /// Returns `true` once the task has resolved.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// Runs the task to completion, handing every update to `observer` after the poll
+    /// that reported it.
+    ///
+    /// While the task is waiting, the thread is parked until the task is woken.
+    pub fn run(mut self, mut observer: impl FnMut(ProgressUpdate)) -> P::Output {
+        loop {
+            for update in self.step() {
+                observer(update);
+            }
+            if let Some(output) = self.output.take() {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// Returns the output of the task if it has finished.
+    #[must_use]
+    pub fn into_output(self) -> Option<P::Output> {
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{State, progress};
+
+    #[test]
+    fn test_pause_then_resume_is_reproducible() {
+        for _ in 0..20 {
+            let task = progress(3, |mut updater| async move {
+                updater.update(1);
+                updater.pause();
+                tokio::task::yield_now().await;
+                updater.update(2);
+                updater.update(3);
+                updater.complete();
+            });
+            let mut states = Vec::new();
+            Driver::new(task).run(|update| states.push(update.state()));
+            assert_eq!(
+                states,
+                [
+                    State::Working,
+                    State::Paused,
+                    State::Working,
+                    State::Working,
+                    State::Completed
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_steps_are_separated_by_yields() {
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            tokio::task::yield_now().await;
+            updater.update(2);
+            updater.complete();
+        });
+        let mut driver = Driver::new(task);
+        assert_eq!(driver.step().len(), 1);
+        assert!(!driver.is_finished());
+        assert_eq!(driver.step().len(), 2);
+        assert!(driver.is_finished());
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use crate::ProgressUpdater;
+
+/// A progress updater for work whose progress is a position in time.
+///
+/// Media transcoders and similar tools report how far into the input they are, such as
+/// `time=00:01:23.45`, rather than a count of finished items. This updater maps that
+/// position onto the total duration of the input. Both are reported in milliseconds, so
+/// [`completed_fraction`](crate::ProgressUpdate::completed_fraction) works as usual.
+///
+/// Every update also carries the processing [`speed`](crate::ProgressUpdate::speed) relative
+/// to real time: `2.0` means one minute of input was processed in thirty seconds.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{ProgressExt, TimebasedUpdater, progress};
+///
+/// # async fn example() {
+/// progress(0, |updater| async move {
+///     let mut transcode = TimebasedUpdater::new(updater, Duration::from_secs(90));
+///     for second in 0..=90 {
+///         transcode.update_position(Duration::from_secs(second));
+///     }
+///     transcode.complete();
+/// })
+/// .observe(|update| {
+///     if let Some(speed) = update.speed() {
+///         println!("{:.0}% at {speed:.1}x realtime", update.completed_fraction() * 100.0);
+///     }
+/// })
+/// .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct TimebasedUpdater {
+    updater: ProgressUpdater,
+    duration: Duration,
+    started: Instant,
+}
+
+impl TimebasedUpdater {
+    /// Wraps an updater for input of the given total `duration`.
+    #[must_use]
+    pub fn new(updater: ProgressUpdater, duration: Duration) -> Self {
+        Self {
+            updater,
+            duration,
+            started: Instant::now(),
+        }
+    }
+
+    /// Reports the position reached in the input.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn update_position(&mut self, position: Duration) {
+        let elapsed = self.started.elapsed();
+        let speed = if elapsed.is_zero() {
+            None
+        } else {
+            Some(position.as_secs_f64() / elapsed.as_secs_f64())
+        };
+        self.updater.report(
+            millis(self.duration),
+            millis(position),
+            |update| match speed {
+                Some(speed) => update.with_speed(speed),
+                None => update,
+            },
+        );
+    }
+
+    /// Returns the total duration of the input.
+    #[must_use]
+    pub const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Marks the operation as completed.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn complete(&mut self) {
+        self.updater.complete();
+    }
+
+    /// Returns the wrapped updater.
+    #[must_use]
+    pub fn into_inner(self) -> ProgressUpdater {
+        self.updater
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
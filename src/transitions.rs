@@ -0,0 +1,185 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Instant,
+};
+
+use crate::{State, sync::lock};
+
+/// A change of a task's [`State`], recorded by a [`TransitionLog`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    from: State,
+    to: State,
+    at: Instant,
+}
+
+impl Transition {
+    /// Returns the state before the change.
+    #[must_use]
+    pub const fn from(&self) -> State {
+        self.from
+    }
+
+    /// Returns the state after the change.
+    #[must_use]
+    pub const fn to(&self) -> State {
+        self.to
+    }
+
+    /// Returns when the update carrying the new state was sent.
+    #[must_use]
+    pub const fn at(&self) -> Instant {
+        self.at
+    }
+}
+
+#[derive(Debug)]
+struct Log {
+    capacity: AtomicUsize,
+    entries: Mutex<(State, VecDeque<Transition>)>,
+}
+
+/// A bounded audit log of the state changes of a task.
+///
+/// Logging is off by default; enable it with
+/// [`ProgressFuture::with_transition_log`](crate::ProgressFuture::with_transition_log).
+/// The log is shared with the task, so a handle obtained from
+/// [`Progress::transitions`](crate::Progress::transitions) before awaiting the task can
+/// be read after it finished, for example to find out why it paused or when it was
+/// cancelled. Once full, the oldest entries are discarded.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{Progress, State, progress};
+///
+/// # async fn example() {
+/// let task = progress(10, |mut updater| async move {
+///     updater.pause();
+///     updater.update(5);
+///     updater.complete();
+/// })
+/// .with_transition_log(16);
+///
+/// let log = task.transitions();
+/// task.await;
+///
+/// for transition in log.entries() {
+///     println!("{:?} -> {:?} at {:?}", transition.from(), transition.to(), transition.at());
+/// }
+/// assert_eq!(log.entries().last().map(|t| t.to()), Some(State::Completed));
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct TransitionLog {
+    log: Arc<Log>,
+}
+
+impl Default for TransitionLog {
+    fn default() -> Self {
+        Self {
+            log: Arc::new(Log {
+                capacity: AtomicUsize::new(0),
+                entries: Mutex::new((State::Working, VecDeque::new())),
+            }),
+        }
+    }
+}
+
+impl TransitionLog {
+    /// Returns the recorded transitions, oldest first.
+    #[must_use]
+    pub fn entries(&self) -> Vec<Transition> {
+        self.lock().1.iter().copied().collect()
+    }
+
+    /// Returns the maximum number of retained transitions; zero means logging is off.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.log.capacity.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        self.log.capacity.store(capacity, Ordering::Relaxed);
+        let mut entries = self.lock();
+        let excess = entries.1.len().saturating_sub(capacity);
+        entries.1.drain(..excess);
+    }
+
+    /// Records `state` if it differs from the state of the previous update.
+    pub(crate) fn record(&self, state: State) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+        let mut guard = self.lock();
+        let (last, entries) = &mut *guard;
+        if *last == state {
+            return;
+        }
+        if entries.len() == capacity {
+            entries.pop_front();
+        }
+        entries.push_back(Transition {
+            from: *last,
+            to: state,
+            at: Instant::now(),
+        });
+        *last = state;
+        drop(guard);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, (State, VecDeque<Transition>)> {
+        lock(&self.log.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Progress, progress};
+
+    #[tokio::test]
+    async fn test_transition_log() {
+        let task = progress(10, |mut updater| async move {
+            updater.update(1);
+            updater.pause();
+            updater.pause_with_message("still paused");
+            updater.update(2);
+            updater.pause();
+            updater.update(3);
+        })
+        .with_transition_log(3);
+        let log = task.transitions();
+        task.await;
+
+        let states: Vec<_> = log.entries().iter().map(|t| (t.from(), t.to())).collect();
+        assert_eq!(
+            states,
+            [
+                (State::Working, State::Paused),
+                (State::Paused, State::Working),
+                (State::Working, State::Cancelled),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transition_log_disabled_by_default() {
+        let task = progress(10, |mut updater| async move {
+            updater.pause();
+            updater.complete();
+        });
+        let log = task.transitions();
+        task.await;
+
+        assert_eq!(log.capacity(), 0);
+        assert!(log.entries().is_empty());
+    }
+}
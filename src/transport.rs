@@ -0,0 +1,115 @@
+use crate::ProgressUpdate;
+
+/// A channel that delivers a task's progress updates to observers.
+///
+/// Every task reports through its own `async-broadcast` channel, read with
+/// [`Progress::progress`](crate::Progress::progress). Applications with other fan-out or
+/// loss-tolerance needs attach the channel they already use with
+/// [`ProgressFuture::with_transport`](crate::ProgressFuture::with_transport), and hand
+/// its receivers to their observers.
+///
+/// A transport is an extra delivery path, not a replacement backend: the task still
+/// allocates its `async-broadcast` channel and sends every update through it, so the
+/// crate keeps depending on `async-broadcast` whichever transports are used.
+///
+/// This is implemented for the senders of `async-broadcast`, and of `tokio::sync::watch`,
+/// `tokio::sync::broadcast` and `flume` with the `tokio` and `flume` features.
+pub trait Transport: Send + Sync + 'static {
+    /// Delivers `update` without blocking.
+    ///
+    /// Returns `false` once the channel is closed, which detaches the transport.
+    fn send(&self, update: &ProgressUpdate) -> bool;
+}
+
+impl Transport for async_broadcast::Sender<ProgressUpdate> {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        let _ = self.try_broadcast(update.clone());
+        !self.is_closed()
+    }
+}
+
+/// Keeps only the latest update; observers that fall behind skip to it.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+impl Transport for tokio::sync::watch::Sender<ProgressUpdate> {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        self.send_replace(update.clone());
+        !self.is_closed()
+    }
+}
+
+/// Observers that fall behind see a lag error and lose the oldest updates.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+impl Transport for tokio::sync::broadcast::Sender<ProgressUpdate> {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        // Sending fails without receivers, but receivers may subscribe later.
+        let _ = Self::send(self, update.clone());
+        true
+    }
+}
+
+/// Updates are dropped while a bounded channel is full.
+#[cfg(feature = "flume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "flume")))]
+impl Transport for flume::Sender<ProgressUpdate> {
+    fn send(&self, update: &ProgressUpdate) -> bool {
+        !matches!(
+            self.try_send(update.clone()),
+            Err(flume::TrySendError::Disconnected(_))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use crate::progress;
+
+    #[tokio::test]
+    async fn test_async_broadcast_transport() {
+        let (sender, receiver) = async_broadcast::broadcast(8);
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            updater.complete();
+        })
+        .with_transport(sender);
+        task.await;
+
+        let currents: Vec<_> = receiver.map(|update| update.current()).collect().await;
+        assert_eq!(currents, [1, 1]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_watch_transport_keeps_latest() {
+        let (sender, receiver) = tokio::sync::watch::channel(crate::ProgressUpdate::new(
+            2,
+            0,
+            crate::State::Working,
+            None,
+        ));
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            updater.update(2);
+            updater.complete();
+        })
+        .with_transport(sender);
+        task.await;
+
+        assert!(receiver.borrow().is_completed());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_watch_transport_detaches_without_receivers() {
+        use crate::{ProgressUpdate, State, Transport};
+
+        let update = ProgressUpdate::new(2, 1, State::Working, None);
+        let (sender, receiver) = tokio::sync::watch::channel(update.clone());
+        assert!(Transport::send(&sender, &update));
+        drop(receiver);
+        assert!(!Transport::send(&sender, &update));
+    }
+}
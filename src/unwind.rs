@@ -0,0 +1,370 @@
+use core::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{
+    panic::{self, AssertUnwindSafe, catch_unwind, resume_unwind},
+    sync::Once,
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{Progress, ProgressController, ProgressUpdate, TransitionLog};
+
+/// What a task wrapped by [`ProgressExt::catch_panic`](crate::ProgressExt::catch_panic)
+/// does when it panics.
+///
+/// Whatever the policy, observers of the task see it as
+/// [`Failed`](crate::State::Failed).
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PanicPolicy {
+    /// The panic propagates to whoever awaits the task, as if it were not wrapped.
+    #[default]
+    Propagate,
+    /// The task resolves to a [`TaskPanic`] error with the message and location of the
+    /// panic. Tasks that resolve to `Result<T, E>` with `E: From<TaskPanic>` can turn it
+    /// into their own error with [`TaskPanic::flatten`].
+    ConvertToFailedOutput,
+    /// Like [`ConvertToFailedOutput`](Self::ConvertToFailedOutput), and the error also
+    /// keeps the panic payload, to be inspected with [`TaskPanic::payload`] or raised
+    /// again with [`TaskPanic::resume`].
+    Quarantine,
+}
+
+impl PanicPolicy {
+    /// Installs a panic hook recording where tasks panic, see [`TaskPanic::location`].
+    ///
+    /// Without it, the errors of panicking tasks carry no location. The hook wraps the
+    /// hook in place when it is installed, so panics are still reported as before, and
+    /// only records locations while a task with a catching policy is being polled.
+    /// Installing it more than once has no effect.
+    ///
+    /// Panic hooks are process-wide: call this after the application has set its own
+    /// hook. A hook set afterwards with [`std::panic::set_hook`] replaces this one, and
+    /// locations are no longer recorded unless that hook calls the one it replaced,
+    /// taken with [`std::panic::take_hook`].
+    pub fn install_location_hook() {
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if CATCHING.get() {
+                    LOCATION.set(info.location().map(PanicLocation::new));
+                }
+                previous(info);
+            }));
+        });
+    }
+}
+
+/// Where a task panicked, see [`TaskPanic::location`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PanicLocation {
+    file: String,
+    line: u32,
+    column: u32,
+}
+
+impl PanicLocation {
+    fn new(location: &panic::Location<'_>) -> Self {
+        Self {
+            file: location.file().to_owned(),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+
+    /// Returns the source file that panicked.
+    #[must_use]
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line that panicked.
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the column that panicked.
+    #[must_use]
+    pub const fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl fmt::Display for PanicLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// The error a task resolves to when it panicked, see [`PanicPolicy`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct TaskPanic {
+    message: Option<String>,
+    location: Option<PanicLocation>,
+    payload: Option<Box<dyn Any + Send>>,
+}
+
+impl TaskPanic {
+    fn new(payload: Box<dyn Any + Send>, location: Option<PanicLocation>, keep: bool) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned());
+        Self {
+            message,
+            location,
+            payload: keep.then_some(payload),
+        }
+    }
+
+    /// Returns the message the task panicked with, if it was a string.
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns where the task panicked.
+    ///
+    /// This is `None` unless [`PanicPolicy::install_location_hook`] installed the hook
+    /// recording it, or if the panic was raised again with
+    /// [`resume_unwind`](std::panic::resume_unwind), which does not run the panic hook.
+    #[must_use]
+    pub const fn location(&self) -> Option<&PanicLocation> {
+        self.location.as_ref()
+    }
+
+    /// Returns the payload the task panicked with, kept under
+    /// [`PanicPolicy::Quarantine`].
+    #[must_use]
+    pub fn payload(&self) -> Option<&(dyn Any + Send)> {
+        self.payload.as_deref()
+    }
+
+    /// Raises the panic again, with its original payload if it was kept.
+    pub fn resume(self) -> ! {
+        match self.payload {
+            Some(payload) => resume_unwind(payload),
+            None => resume_unwind(Box::new(self.message.unwrap_or_default())),
+        }
+    }
+
+    /// Turns the panic of a task resolving to `Result<T, E>` into the task's own error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the task, or the panic converted into it.
+    pub fn flatten<T, E: From<Self>>(output: Result<Result<T, E>, Self>) -> Result<T, E> {
+        output.unwrap_or_else(|panic| Err(panic.into()))
+    }
+}
+
+impl fmt::Debug for TaskPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskPanic")
+            .field("message", &self.message)
+            .field("location", &self.location)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for TaskPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "task panicked: {message}")?,
+            None => f.write_str("task panicked")?,
+        }
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TaskPanic {}
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static LOCATION: RefCell<Option<PanicLocation>> = const { RefCell::new(None) };
+}
+
+/// Calls `f`, catching a panic together with where it was raised, if the location hook
+/// is installed.
+fn catch_located<T>(
+    f: impl FnOnce() -> T,
+) -> Result<T, (Box<dyn Any + Send>, Option<PanicLocation>)> {
+    // A location left over from a panic caught elsewhere must not be reported for this
+    // one, such as a resumed panic that skips the hook.
+    LOCATION.take();
+    let catching = CATCHING.replace(true);
+    let result = catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(catching);
+    result.map_err(|payload| (payload, LOCATION.take()))
+}
+
+pin_project! {
+    /// A task whose panics are handled by a [`PanicPolicy`], created by
+    /// [`ProgressExt::catch_panic`](crate::ProgressExt::catch_panic).
+    ///
+    /// It implements [`Progress`] with the updates of the task. A task that panics
+    /// reports itself as [`Failed`](crate::State::Failed) to its observers.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub struct CatchPanic<P> {
+        #[pin]
+        task: P,
+        policy: PanicPolicy,
+    }
+}
+
+impl<P> fmt::Debug for CatchPanic<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> CatchPanic<P> {
+    pub(crate) const fn new(task: P, policy: PanicPolicy) -> Self {
+        Self { task, policy }
+    }
+}
+
+impl<P: Future> Future for CatchPanic<P> {
+    type Output = Result<P::Output, TaskPanic>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if *this.policy == PanicPolicy::Propagate {
+            return this.task.poll(cx).map(Ok);
+        }
+        // The task is not polled again after it panicked, so its state cannot be observed
+        // half-updated.
+        match catch_located(|| this.task.poll(cx)) {
+            Ok(poll) => poll.map(Ok),
+            Err((payload, location)) => {
+                let keep = *this.policy == PanicPolicy::Quarantine;
+                Poll::Ready(Err(TaskPanic::new(payload, location, keep)))
+            }
+        }
+    }
+}
+
+impl<P: Progress> Progress for CatchPanic<P> {
+    fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.task.progress()
+    }
+
+    fn transitions(&self) -> TransitionLog {
+        self.task.transitions()
+    }
+
+    fn controller(&self) -> Option<ProgressController> {
+        self.task.controller()
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&self) -> crate::Diagnostics {
+        self.task.diagnostics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use futures_util::{FutureExt, StreamExt};
+
+    use super::catch_located;
+    use crate::{PanicPolicy, Progress, ProgressExt, TaskPanic, progress};
+
+    #[tokio::test]
+    async fn test_quarantine() {
+        PanicPolicy::install_location_hook();
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            panic!("corrupt input");
+        })
+        .catch_panic(PanicPolicy::Quarantine);
+        let updates = task.progress();
+
+        let error = task.await.unwrap_err();
+        assert_eq!(error.message(), Some("corrupt input"));
+        assert_eq!(error.location().unwrap().file(), file!());
+        assert_eq!(
+            error.payload().unwrap().downcast_ref::<&str>(),
+            Some(&"corrupt input")
+        );
+        let updates: Vec<_> = updates.collect().await;
+        assert!(updates.last().unwrap().is_failed());
+    }
+
+    #[test]
+    fn test_location_is_not_carried_over() {
+        PanicPolicy::install_location_hook();
+        // The task handles its own panic, leaving a location behind.
+        let handled = catch_located(|| std::panic::catch_unwind(|| panic!("handled")).is_err());
+        assert!(matches!(handled, Ok(true)));
+
+        // Resumed panics skip the hook, so there is no location to report.
+        let Err((_, location)) =
+            catch_located::<()>(|| std::panic::resume_unwind(Box::new("resumed")))
+        else {
+            panic!("expected a caught panic");
+        };
+        assert!(location.is_none());
+    }
+
+    #[derive(Debug)]
+    enum JobError {
+        Panicked(TaskPanic),
+    }
+
+    impl From<TaskPanic> for JobError {
+        fn from(panic: TaskPanic) -> Self {
+            Self::Panicked(panic)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_convert_to_failed_output() {
+        PanicPolicy::install_location_hook();
+        let task = progress(1, |_| async move {
+            let items = 3;
+            assert!(items < 3, "{items} items");
+            Ok::<_, JobError>(())
+        })
+        .catch_panic(PanicPolicy::ConvertToFailedOutput);
+
+        let Err(JobError::Panicked(panic)) = TaskPanic::flatten(task.await) else {
+            panic!("expected a converted panic");
+        };
+        assert!(panic.payload().is_none());
+        let location = panic.location().unwrap();
+        assert!(
+            panic
+                .to_string()
+                .starts_with(&format!("task panicked: 3 items at {}:", location.file()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_propagate() {
+        let task = progress(1, |_| async move {
+            panic!("unrecoverable");
+        })
+        .catch_panic(PanicPolicy::Propagate);
+
+        let panic = AssertUnwindSafe(task).catch_unwind().await.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"unrecoverable"));
+    }
+}
@@ -1,129 +1,2044 @@
 use core::{
+    cell::Cell,
     future::Future,
+    ops::Deref,
     pin::Pin,
-    task::{Context, Poll},
+    sync::atomic::{AtomicBool, AtomicU8, AtomicU64, AtomicUsize, Ordering},
+    task::{Context, Poll, Waker},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Condvar, Mutex, PoisonError, TryLockError},
+    thread::ThreadId,
+    time::{Duration, Instant},
 };
 
-use async_broadcast::{Receiver, Sender, broadcast};
+use async_broadcast::{InactiveReceiver, Receiver, Sender, broadcast};
 use futures_core::Stream;
+use futures_timer::Delay;
 use pin_project_lite::pin_project;
 
-use crate::{Progress, ProgressUpdate, State};
+use crate::{
+    CancelReason, CompactProgress, Dimension, KeyedMessage, MessagePolicy, Outcomes, Pacing,
+    PacingProvider, Priority, Progress, ProgressEvent, ProgressUpdate, Redactor, State,
+    TransitionLog, Transport, compact, eta::PauseClock, sync::lock,
+};
+
+/// State shared between a task's updater and its controllers.
+#[derive(Debug)]
+struct Shared {
+    pacing: AtomicU8,
+    pacing_provider: Mutex<Option<Provider>>,
+    priority: AtomicU8,
+    cancel_reason: AtomicU8,
+    cancel_wakers: Mutex<Vec<Waker>>,
+    pause_requested: AtomicBool,
+    resume_wakers: Mutex<Vec<Waker>>,
+    sequence: AtomicU64,
+    /// The current value last reported by any clone of the updater.
+    position: AtomicU64,
+    /// The total last set by any clone of the updater.
+    total: AtomicU64,
+    /// The optional parts of updates the task has used, see [`Parts`]; the locks of
+    /// the others are skipped.
+    parts: AtomicU8,
+    extra_state: Mutex<Option<&'static str>>,
+    secondary: Mutex<Option<Dimension>>,
+    /// The outcomes recorded by any clone; recording one also moves the position while
+    /// holding this lock.
+    outcomes: Mutex<Option<Outcomes>>,
+    /// The number of live updaters, including clones and children.
+    updaters: AtomicUsize,
+    transitions: TransitionLog,
+    /// The items in flight, rebuilt when one starts or finishes and shared by the updates.
+    active_items: Mutex<Option<Arc<[String]>>>,
+    /// The terminal state of the task once it finished, encoded with `State::to_u8`.
+    final_state: AtomicU8,
+    finished_wakers: Mutex<Vec<Waker>>,
+    prompts: Mutex<Prompts>,
+    prompt_wakers: Mutex<Vec<Waker>>,
+    subscribers: Mutex<Subscribers>,
+    arc_updates: Mutex<Option<InactiveReceiver<Arc<ProgressUpdate>>>>,
+    message_budget: AtomicUsize,
+    time_budget: Mutex<Option<TimeBudget>>,
+    time_budget_wakers: Mutex<Vec<Waker>>,
+    /// The latest update and when it was sent, kept once [`Parts::LATEST`] is set.
+    latest: Mutex<Option<(ProgressUpdate, Instant)>>,
+    latest_changed: Condvar,
+    message_policy: Mutex<MessagePolicy>,
+    redactor: Mutex<Option<Redactor>>,
+    unit_cost: Mutex<Option<f64>>,
+    ticker: Mutex<Option<Ticker>>,
+    #[cfg(feature = "diagnostics")]
+    diagnostics: crate::Diagnostics,
+}
+
+/// Bits of [`Shared::parts`], set once a task first uses the part.
+struct Parts;
+
+impl Parts {
+    const OUTCOMES: u8 = 1;
+    const EXTRA_STATE: u8 = 1 << 1;
+    const SECONDARY: u8 = 1 << 2;
+    const UNIT_COST: u8 = 1 << 3;
+    const ACTIVE_ITEMS: u8 = 1 << 4;
+    /// A [`Redactor`] or [`MessagePolicy`] rewrites the updates.
+    const FILTERS: u8 = 1 << 5;
+    const TIME_BUDGET: u8 = 1 << 6;
+    /// The latest update is read, by [`ProgressUpdater::auto_tick`] or a [`ProgressHandle`].
+    const LATEST: u8 = 1 << 7;
+}
+
+/// The helper thread repeating updates for [`ProgressUpdater::auto_tick`].
+#[derive(Debug)]
+struct Ticker {
+    stopped: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl Ticker {
+    /// Stops the thread and waits until it has let go of its sender.
+    fn stop(self) {
+        self.stopped.store(true, Ordering::Release);
+        self.thread.thread().unpark();
+        let _ = self.thread.join();
+    }
+}
+
+/// Requests a task is waiting on observers to answer, see [`ProgressEvent`].
+#[derive(Debug, Default)]
+struct Prompts {
+    next_id: u64,
+    open: HashMap<u64, Prompt>,
+}
+
+/// An open request and its answer, once an observer gave one.
+#[derive(Debug)]
+enum Prompt {
+    Confirmation,
+    Input,
+    Answered(Reply),
+}
+
+/// An observer's answer to a request.
+#[derive(Debug)]
+enum Reply {
+    Approved(bool),
+    Value(String),
+}
+
+/// What happens to a task that used up its active time budget, see
+/// [`ProgressFuture::with_time_budget`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BudgetAction {
+    /// Stop polling the task until [`ProgressController::extend_budget`] grants more time.
+    Suspend,
+    /// Ask the task to stop with [`CancelReason::Timeout`].
+    Cancel,
+}
+
+/// A limit on the time a task may spend working, see [`ProgressFuture::with_time_budget`].
+#[derive(Debug)]
+struct TimeBudget {
+    limit: Duration,
+    action: BudgetAction,
+    /// Measures active time from the first poll on.
+    clock: Option<PauseClock>,
+    total: u64,
+    current: u64,
+    exhausted: bool,
+}
+
+impl TimeBudget {
+    fn observe(&mut self, update: &ProgressUpdate, now: Instant) {
+        self.total = update.total();
+        self.current = update.current();
+        if let Some(clock) = &mut self.clock {
+            clock.observe(update, now);
+        }
+    }
+
+    /// Returns the active time left, starting the clock if needed.
+    fn remaining(&mut self, now: Instant) -> Duration {
+        let clock = self.clock.get_or_insert_with(|| PauseClock::new(now));
+        self.limit.saturating_sub(clock.active(now))
+    }
+}
+
+/// What a task with a time budget should do next.
+enum BudgetCheck {
+    /// Keep running; check again after the given time.
+    Run(Duration),
+    /// Keep running; the budget no longer applies.
+    Unlimited,
+    /// Report that the budget is used up, then stop polling the task.
+    Suspend(ProgressUpdate),
+    /// Stop polling the task.
+    Suspended,
+    /// Report that the task runs again, then resume polling it.
+    Resume(ProgressUpdate),
+}
+
+/// The system conditions a task paces itself by, see [`PacingProvider`].
+struct Provider(Box<dyn PacingProvider>);
+
+impl core::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Provider")
+    }
+}
+
+/// Receives every update as it is sent; returns `false` once it is no longer interested.
+type Forward = Box<dyn Fn(&ProgressUpdate) -> bool + Send + Sync>;
+
+/// An observer called within the update, see [`progress_inline`].
+type Inline = Arc<dyn Fn(&ProgressUpdate) + Send + Sync>;
+
+/// Observers that are handed updates directly instead of reading the task's channel.
+#[derive(Default)]
+struct Subscribers {
+    /// Forwards into channels of their own, such as filtered subscribers and buses.
+    list: Vec<Forward>,
+    /// Observers that run within the update and may report progress themselves, see
+    /// [`progress_inline`]. Replaced rather than changed, so updates call them without
+    /// holding the lock.
+    inline: Arc<[Inline]>,
+    closed: bool,
+}
+
+impl core::fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Subscribers")
+            .field("len", &self.list.len())
+            .field("inline", &self.inline.len())
+            .field("closed", &self.closed)
+            .finish()
+    }
+}
+
+impl Shared {
+    fn add_forward(&self, forward: Forward) {
+        let mut subscribers = lock(&self.subscribers);
+        if !subscribers.closed {
+            subscribers.list.push(forward);
+        }
+    }
+
+    fn add_inline_observer(&self, observer: Inline) {
+        let mut subscribers = lock(&self.subscribers);
+        if !subscribers.closed {
+            let observers = subscribers.inline.iter().cloned();
+            subscribers.inline = observers.chain([observer]).collect();
+        }
+    }
+
+    /// Returns a receiver of the channel delivering updates behind an `Arc`, creating
+    /// the channel for the first subscriber.
+    fn arc_updates(&self) -> Receiver<Arc<ProgressUpdate>> {
+        let mut slot = lock(&self.arc_updates);
+        if let Some(receiver) = &*slot {
+            return receiver.activate_cloned();
+        }
+        let (mut sender, receiver) = broadcast(32);
+        sender.set_overflow(true);
+        #[cfg(feature = "diagnostics")]
+        let diagnostics = self.diagnostics.clone();
+        self.add_forward(Box::new(move |update| {
+            // Allocate once per update, shared by all receivers, and only if anyone listens.
+            if sender.receiver_count() > 0 {
+                #[cfg(feature = "diagnostics")]
+                diagnostics.send_to_subscriber(&sender, Arc::new(update.clone()), update);
+                #[cfg(not(feature = "diagnostics"))]
+                let _ = sender.try_broadcast(Arc::new(update.clone()));
+            }
+            true
+        }));
+        let inactive = receiver.deactivate();
+        let receiver = inactive.activate_cloned();
+        *slot = Some(inactive);
+        drop(slot);
+        receiver
+    }
+
+    fn broadcast(&self, sender: Option<&Sender<ProgressUpdate>>, mut update: ProgressUpdate) {
+        if self.uses(Parts::FILTERS) {
+            if let Some(redactor) = &*lock(&self.redactor) {
+                redactor.apply_to(&mut update);
+            }
+            lock(&self.message_policy).apply_to(&mut update);
+        }
+        if let Some(sender) = sender {
+            // Every buffered update may carry its share of the budget.
+            let budget = self.message_budget.load(Ordering::Relaxed);
+            update.truncate_message(budget / sender.capacity());
+        }
+        self.transitions.record(update.state());
+        if self.uses(Parts::TIME_BUDGET)
+            && let Some(budget) = &mut *lock(&self.time_budget)
+        {
+            budget.observe(&update, Instant::now());
+        }
+        if self.uses(Parts::LATEST) && !update.is_heartbeat() {
+            *lock(&self.latest) = Some((update.clone(), Instant::now()));
+            self.latest_changed.notify_all();
+        }
+        if update.is_terminal() {
+            self.set_finished(update.state());
+        }
+        let mut subscribers = lock(&self.subscribers);
+        subscribers.list.retain(|forward| forward(&update));
+        let inline = subscribers.inline.clone();
+        drop(subscribers);
+        if let Some(_nested) = Nested::enter() {
+            for observer in &*inline {
+                observer(&update);
+            }
+        }
+        if let Some(sender) = sender {
+            #[cfg(feature = "diagnostics")]
+            self.diagnostics.send(sender, update);
+            #[cfg(not(feature = "diagnostics"))]
+            let _ = sender.try_broadcast(update);
+        }
+    }
+
+    fn check_time_budget(&self, now: Instant) -> BudgetCheck {
+        if !self.uses(Parts::TIME_BUDGET) {
+            return BudgetCheck::Unlimited;
+        }
+        let mut slot = lock(&self.time_budget);
+        let Some(budget) = &mut *slot else {
+            return BudgetCheck::Unlimited;
+        };
+        let remaining = budget.remaining(now);
+        let update = |state| {
+            let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+            ProgressUpdate::new(budget.total, budget.current, state, None)
+                .with_priority(Priority::from_u8(self.priority.load(Ordering::Relaxed)))
+                .with_sequence(sequence)
+        };
+        match (budget.action, budget.exhausted, remaining.is_zero()) {
+            (_, false, false) => BudgetCheck::Run(remaining),
+            (BudgetAction::Cancel, ..) => {
+                *slot = None;
+                drop(slot);
+                self.set_cancel_reason(CancelReason::Timeout);
+                BudgetCheck::Unlimited
+            }
+            (BudgetAction::Suspend, false, true) => {
+                budget.exhausted = true;
+                let event = ProgressEvent::BudgetExhausted {
+                    budget: budget.limit,
+                };
+                BudgetCheck::Suspend(update(State::Paused).with_event(event))
+            }
+            (BudgetAction::Suspend, true, true) => BudgetCheck::Suspended,
+            (BudgetAction::Suspend, true, false) => {
+                budget.exhausted = false;
+                BudgetCheck::Resume(update(State::Working))
+            }
+        }
+    }
+
+    /// Marks `part`, one of [`Parts`], as used; call after storing its value.
+    fn enable(&self, part: u8) {
+        self.parts.fetch_or(part, Ordering::Release);
+    }
+
+    fn uses(&self, part: u8) -> bool {
+        self.parts.load(Ordering::Acquire) & part != 0
+    }
+
+    fn cancel_reason(&self) -> Option<CancelReason> {
+        CancelReason::from_u8(self.cancel_reason.load(Ordering::Acquire))
+    }
+
+    fn set_cancel_reason(&self, reason: CancelReason) {
+        self.cancel_reason.store(reason.to_u8(), Ordering::Release);
+        wake_all(&self.cancel_wakers);
+    }
+
+    fn is_pause_requested(&self) -> bool {
+        self.pause_requested.load(Ordering::Acquire)
+    }
+
+    fn set_pause_requested(&self, paused: bool) {
+        self.pause_requested.store(paused, Ordering::Release);
+        if !paused {
+            wake_all(&self.resume_wakers);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.final_state().is_some()
+    }
+
+    fn final_state(&self) -> Option<State> {
+        State::from_u8(self.final_state.load(Ordering::Acquire))
+    }
+
+    fn open_prompt(&self, prompt: Prompt) -> u64 {
+        let mut prompts = lock(&self.prompts);
+        let id = prompts.next_id;
+        prompts.next_id += 1;
+        prompts.open.insert(id, prompt);
+        id
+    }
+
+    /// Records the answer to request `id`, unless it is closed, was already answered,
+    /// or asked for a different kind of answer.
+    fn answer(&self, id: u64, reply: Reply) {
+        let answered = match (lock(&self.prompts).open.get_mut(&id), reply) {
+            (Some(prompt @ Prompt::Confirmation), reply @ Reply::Approved(_))
+            | (Some(prompt @ Prompt::Input), reply @ Reply::Value(_)) => {
+                *prompt = Prompt::Answered(reply);
+                true
+            }
+            _ => false,
+        };
+        if answered {
+            wake_all(&self.prompt_wakers);
+        }
+    }
+
+    fn close_prompt(&self, id: u64) {
+        lock(&self.prompts).open.remove(&id);
+    }
+
+    fn take_answer(&self, id: u64) -> Option<Reply> {
+        let mut prompts = lock(&self.prompts);
+        match prompts.open.remove(&id)? {
+            Prompt::Answered(reply) => Some(reply),
+            unanswered => {
+                prompts.open.insert(id, unanswered);
+                None
+            }
+        }
+    }
+
+    /// Records the terminal `state`; only the first one counts.
+    fn set_finished(&self, state: State) {
+        if self
+            .final_state
+            .compare_exchange(0, state.to_u8(), Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            wake_all(&self.finished_wakers);
+        }
+    }
+}
+
+/// How deeply observers called synchronously may report progress from within an update.
+static MAX_OBSERVER_DEPTH: AtomicUsize = AtomicUsize::new(8);
+
+std::thread_local! {
+    /// How many updates are being handed to synchronous observers on this thread.
+    static OBSERVER_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Sets how deeply observers called synchronously may nest, eight by default.
+///
+/// Observers passed to [`progress_inline`] are called from within the update that
+/// triggered them. If such an observer starts a tracked task that reports back into it,
+/// for instance through a global observer, every update would trigger another. Updates
+/// sent while this many are already being observed on the same thread are not handed to
+/// inline observers, which stops the feedback. Streams, transports and buses still
+/// receive them.
+pub fn set_max_observer_depth(depth: usize) {
+    MAX_OBSERVER_DEPTH.store(depth, Ordering::Relaxed);
+}
+
+/// Marks an update being handed to synchronous observers on this thread.
+struct Nested;
+
+impl Nested {
+    /// Enters one level of observation, or returns `None` if the limit is reached.
+    fn enter() -> Option<Self> {
+        OBSERVER_DEPTH.with(|depth| {
+            (depth.get() < MAX_OBSERVER_DEPTH.load(Ordering::Relaxed)).then(|| {
+                depth.set(depth.get() + 1);
+                Self
+            })
+        })
+    }
+}
+
+impl Drop for Nested {
+    fn drop(&mut self) {
+        OBSERVER_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// An observer passed to [`progress_inline`], with the thread it runs on.
+struct InlineObserver<F> {
+    observer: Mutex<F>,
+    caller: Mutex<Option<ThreadId>>,
+}
+
+impl<F: FnMut(&ProgressUpdate)> InlineObserver<F> {
+    fn call(&self, update: &ProgressUpdate) {
+        let current = std::thread::current().id();
+        let mut observer = match self.observer.try_lock() {
+            Ok(observer) => observer,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            // The observer reported this update itself; it is not handed back to it.
+            Err(TryLockError::WouldBlock) if *lock(&self.caller) == Some(current) => return,
+            Err(TryLockError::WouldBlock) => lock(&self.observer),
+        };
+        *lock(&self.caller) = Some(current);
+        observer(update);
+        // Cleared before the next caller can take the observer.
+        *lock(&self.caller) = None;
+        drop(observer);
+    }
+}
+
+/// Adds `waker` to `wakers` unless an equivalent waker is already registered.
+fn register(wakers: &Mutex<Vec<Waker>>, waker: &Waker) {
+    let mut wakers = lock(wakers);
+    if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+        wakers.push(waker.clone());
+    }
+}
+
+/// Wakes and removes every waker in `wakers`.
+fn wake_all(wakers: &Mutex<Vec<Waker>>) {
+    let wakers = core::mem::take(&mut *lock(wakers));
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// Resolves once cancellation of a task has been requested.
+struct Cancelled {
+    shared: Arc<Shared>,
+}
+
+impl Future for Cancelled {
+    type Output = CancelReason;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(reason) = self.shared.cancel_reason() {
+            return Poll::Ready(reason);
+        }
+        register(&self.shared.cancel_wakers, cx.waker());
+        // Check again in case cancellation was requested while registering.
+        self.shared
+            .cancel_reason()
+            .map_or(Poll::Pending, Poll::Ready)
+    }
+}
+
+/// Resolves to `true` once a pause is lifted, or `false` once cancellation has been
+/// requested.
+struct Resumed {
+    shared: Arc<Shared>,
+}
+
+impl Future for Resumed {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(resumed) = self.check() {
+            return Poll::Ready(resumed);
+        }
+        register(&self.shared.resume_wakers, cx.waker());
+        register(&self.shared.cancel_wakers, cx.waker());
+        // Check again in case the task was resumed while registering.
+        self.check()
+    }
+}
+
+impl Resumed {
+    fn check(&self) -> Poll<bool> {
+        if self.shared.cancel_reason().is_some() {
+            Poll::Ready(false)
+        } else if self.shared.is_pause_requested() {
+            Poll::Pending
+        } else {
+            Poll::Ready(true)
+        }
+    }
+}
+
+/// Resolves to the answer to a request, or `None` once cancellation has been requested.
+struct Answer {
+    shared: Arc<Shared>,
+    id: u64,
+}
+
+impl Future for Answer {
+    type Output = Option<Reply>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(answer) = self.check() {
+            return Poll::Ready(answer);
+        }
+        register(&self.shared.prompt_wakers, cx.waker());
+        register(&self.shared.cancel_wakers, cx.waker());
+        // Check again in case an answer arrived while registering.
+        self.check()
+    }
+}
+
+impl Answer {
+    fn check(&self) -> Poll<Option<Reply>> {
+        match self.shared.take_answer(self.id) {
+            Some(answer) => Poll::Ready(Some(answer)),
+            None if self.shared.cancel_reason().is_some() => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for Answer {
+    fn drop(&mut self) {
+        self.shared.close_prompt(self.id);
+    }
+}
+
+/// Resolves once a task has reported a terminal state.
+struct Finished {
+    shared: Arc<Shared>,
+}
+
+impl Future for Finished {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.shared.is_finished() {
+            return Poll::Ready(());
+        }
+        register(&self.shared.finished_wakers, cx.waker());
+        // Check again in case the task finished while registering.
+        if self.shared.is_finished() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Default for Shared {
+    fn default() -> Self {
+        Self {
+            pacing: AtomicU8::new(Pacing::Normal as u8),
+            pacing_provider: Mutex::new(None),
+            priority: AtomicU8::new(Priority::Normal as u8),
+            cancel_reason: AtomicU8::new(0),
+            cancel_wakers: Mutex::new(Vec::new()),
+            pause_requested: AtomicBool::new(false),
+            resume_wakers: Mutex::new(Vec::new()),
+            sequence: AtomicU64::new(0),
+            position: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            parts: AtomicU8::new(0),
+            extra_state: Mutex::new(None),
+            secondary: Mutex::new(None),
+            outcomes: Mutex::new(None),
+            updaters: AtomicUsize::new(0),
+            transitions: TransitionLog::default(),
+            active_items: Mutex::new(None),
+            final_state: AtomicU8::new(0),
+            finished_wakers: Mutex::new(Vec::new()),
+            prompts: Mutex::default(),
+            prompt_wakers: Mutex::new(Vec::new()),
+            subscribers: Mutex::default(),
+            arc_updates: Mutex::new(None),
+            message_budget: AtomicUsize::new(usize::MAX),
+            time_budget: Mutex::new(None),
+            time_budget_wakers: Mutex::new(Vec::new()),
+            latest: Mutex::new(None),
+            latest_changed: Condvar::new(),
+            message_policy: Mutex::default(),
+            redactor: Mutex::new(None),
+            unit_cost: Mutex::new(None),
+            ticker: Mutex::new(None),
+            #[cfg(feature = "diagnostics")]
+            diagnostics: crate::Diagnostics::default(),
+        }
+    }
+}
+
+/// Where the progress of a child updater lands in its task's, see
+/// [`ProgressUpdater::child`].
+#[derive(Debug)]
+struct Scale {
+    /// The task's total when the child was created.
+    total: u64,
+    /// The task's current value when the child started.
+    offset: f64,
+    /// The part of the task's total the child covers.
+    range: f64,
+    /// The current value last reported by any clone of the child.
+    position: AtomicU64,
+    /// The child's own total, shared by its clones.
+    child_total: AtomicU64,
+    /// Whether a clone of the child completed it.
+    completed: AtomicBool,
+}
+
+impl Scale {
+    /// Returns the task's current value once the child did `fraction` of its work.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    const fn task_current(&self, fraction: f64) -> u64 {
+        self.range.mul_add(fraction, self.offset).round() as u64
+    }
+}
 
 /// A handle for updating progress during execution of a future.
 ///
 /// This struct allows you to report progress updates that will be broadcast
 /// to listeners via the progress stream. It maintains internal state and
 /// automatically handles cancellation when dropped.
+///
+/// Clones report the same task and share its current value, total, outcome counts,
+/// extra state and secondary counter. Only the last one to be dropped reports the task as cancelled,
+/// unless a clone completed it before.
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct ProgressUpdater {
-    total: u64,
-    current: u64,
-    completed: bool,
-    sender: Sender<ProgressUpdate>,
+    /// `None` for tasks observed inline, see [`progress_inline`].
+    sender: Option<Sender<ProgressUpdate>>,
+    shared: Arc<Shared>,
+    /// `None` unless this is a child updater, see [`ProgressUpdater::child`].
+    scale: Option<Arc<Scale>>,
+}
+
+impl Clone for ProgressUpdater {
+    fn clone(&self) -> Self {
+        self.shared.updaters.fetch_add(1, Ordering::Relaxed);
+        Self {
+            sender: self.sender.clone(),
+            shared: self.shared.clone(),
+            scale: self.scale.clone(),
+        }
+    }
 }
 
 impl ProgressUpdater {
-    const fn new(total: u64, sender: Sender<ProgressUpdate>) -> Self {
+    fn new(total: u64, sender: Option<Sender<ProgressUpdate>>, shared: Arc<Shared>) -> Self {
+        shared.updaters.fetch_add(1, Ordering::Relaxed);
+        shared.total.store(total, Ordering::Relaxed);
         Self {
-            total,
-            current: 0,
-            completed: false,
             sender,
+            shared,
+            scale: None,
+        }
+    }
+
+    /// Updates the progress with the given current value and message.
+    ///
+    /// This will broadcast the update to all progress stream listeners.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn update_with_message(&mut self, current: u64, message: impl Into<String>) {
+        self.set_current(current);
+        let update = self.snapshot(State::Working, Some(message.into()));
+        self.broadcast(update);
+    }
+
+    /// Updates the progress with the given current value and a localizable message.
+    ///
+    /// Instead of display text, the message is identified by `key` and named `args`.
+    /// Observers resolve it with their own [`Localizer`](crate::Localizer).
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn update_keyed(
+        &mut self,
+        current: u64,
+        key: &'static str,
+        args: impl IntoIterator<Item = (&'static str, String)>,
+    ) {
+        self.set_current(current);
+        let update = self
+            .snapshot(State::Working, None)
+            .with_keyed_message(KeyedMessage::new(key, args));
+        self.broadcast(update);
+    }
+
+    /// Updates the progress with the given current value.
+    ///
+    /// This will broadcast the update to all progress stream listeners.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn update(&mut self, current: u64) {
+        self.set_current(current);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Advances the current value by one and broadcasts the update.
+    ///
+    /// See [`advance`](Self::advance).
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn increment(&mut self) {
+        self.advance(1);
+    }
+
+    /// Advances the current value by `delta` and broadcasts the update.
+    ///
+    /// The counter is shared by all clones of this updater and advanced atomically, so
+    /// workers processing items in any order can each hold a clone and report what they
+    /// did without computing absolute positions. It continues from the value last
+    /// reported by any clone, including through [`update`](Self::update).
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn advance(&mut self, delta: u64) {
+        self.add(delta);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Pauses the progress operation.
+    ///
+    /// This method sets the progress state to paused and broadcasts the update to all listeners.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn pause(&self) {
+        let update = self.snapshot(State::Paused, None);
+        self.broadcast(update);
+    }
+
+    /// Waits while an observer has paused the task.
+    ///
+    /// [`pause`](Self::pause) only tells observers that the task is paused; this lets
+    /// observers pause the task itself. Call it between units of work: while a
+    /// [`ProgressController::pause`] is in effect, it reports a [`Paused`](State::Paused)
+    /// update and waits until [`ProgressController::resume`], then reports
+    /// [`Working`](State::Working) again. Otherwise it returns right away. It also
+    /// returns once the task is asked to cancel, which the task should then check with
+    /// [`cancel_requested`](Self::cancel_requested).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{Progress, State, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let sync = progress(100, |mut updater| async move {
+    ///     for file in 1..=100 {
+    ///         updater.checkpoint().await;
+    ///         if updater.cancel_requested().is_some() {
+    ///             return;
+    ///         }
+    ///         updater.update(file);
+    ///     }
+    ///     updater.complete();
+    /// });
+    /// let controller = sync.controller();
+    /// let mut updates = sync.progress();
+    /// tokio::spawn(sync);
+    ///
+    /// // The user clicked the pause button.
+    /// controller.pause();
+    /// while let Some(update) = updates.next().await {
+    ///     if update.state() == State::Paused {
+    ///         println!("Paused at {} files", update.current());
+    ///         controller.resume();
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn checkpoint(&self) {
+        if !self.shared.is_pause_requested() {
+            return;
+        }
+        let resumed = Resumed {
+            shared: self.shared.clone(),
+        };
+        let update = self.snapshot(State::Paused, None);
+        self.broadcast(update);
+        if resumed.await {
+            let update = self.snapshot(State::Working, None);
+            self.broadcast(update);
+        }
+    }
+
+    /// Marks the progress operation as completed.
+    ///
+    /// This broadcasts a completion update with the current value shared by all clones.
+    /// Once any clone reported the final update of the task, this has no effect.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn complete(&mut self) {
+        if self.shared.is_finished() {
+            return;
+        }
+        if let Some(scale) = &self.scale {
+            // The child's share is done; the task itself goes on.
+            if !scale.completed.swap(true, Ordering::AcqRel) {
+                scale.position.fetch_max(self.total(), Ordering::Relaxed);
+                let update = self.snapshot(State::Working, None);
+                self.broadcast(update);
+            }
+            return;
+        }
+        lock(&self.shared.active_items).take();
+        let update = self.snapshot(State::Completed, None);
+        self.broadcast(update);
+    }
+
+    /// Marks the progress operation as failed, with a message describing the error.
+    ///
+    /// Like [`complete`](Self::complete), this reports the final update of the task, and
+    /// has no effect once any clone reported it.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn fail_with_message(&mut self, message: impl Into<String>) {
+        if !self.shared.is_finished() {
+            lock(&self.shared.active_items).take();
+            let update = self.snapshot(State::Failed, Some(message.into()));
+            self.broadcast(update);
+        }
+    }
+
+    /// Asks observers to approve an action and waits for their answer.
+    ///
+    /// This reports a [`Paused`](State::Paused) update carrying a
+    /// [`ProgressEvent::NeedsConfirmation`] with `prompt`, which an observer answers with
+    /// [`ProgressController::approve`] or [`ProgressController::deny`]. Once answered,
+    /// the task reports [`Working`](State::Working) again. Returns `true` if the action
+    /// was approved, and `false` if it was denied or the task was asked to cancel while
+    /// waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{Progress, ProgressEvent, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let install = progress(10, |mut updater| async move {
+    ///     if !updater.await_confirmation("Overwrite existing files?").await {
+    ///         return false;
+    ///     }
+    ///     updater.update(10);
+    ///     true
+    /// });
+    /// let controller = install.controller();
+    /// let mut updates = install.progress();
+    /// tokio::spawn(async move {
+    ///     while let Some(update) = updates.next().await {
+    ///         if let Some(ProgressEvent::NeedsConfirmation { id, prompt }) = update.event() {
+    ///             println!("{prompt} [y/n] y");
+    ///             controller.approve(*id);
+    ///         }
+    ///     }
+    /// });
+    /// assert!(install.await);
+    /// # }
+    /// ```
+    pub async fn await_confirmation(&self, prompt: impl Into<String>) -> bool {
+        let prompt = prompt.into();
+        let reply = self
+            .ask(Prompt::Confirmation, |id| {
+                ProgressEvent::NeedsConfirmation { id, prompt }
+            })
+            .await;
+        matches!(reply, Some(Reply::Approved(true)))
+    }
+
+    /// Asks observers for a value and waits for their response.
+    ///
+    /// This reports a [`Paused`](State::Paused) update carrying a
+    /// [`ProgressEvent::NeedsInput`] with `prompt`, which an observer answers with
+    /// [`ProgressController::respond`]. Once answered, the task reports
+    /// [`Working`](State::Working) again. Returns `None` if the task was asked to cancel
+    /// while waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{Progress, ProgressEvent, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let setup = progress(2, |mut updater| async move {
+    ///     let name = updater.request_input("Name of the new database?").await?;
+    ///     updater.update(2);
+    ///     Some(name)
+    /// });
+    /// let controller = setup.controller();
+    /// let mut updates = setup.progress();
+    /// tokio::spawn(async move {
+    ///     while let Some(update) = updates.next().await {
+    ///         if let Some(ProgressEvent::NeedsInput { id, prompt }) = update.event() {
+    ///             println!("{prompt} inventory");
+    ///             controller.respond(*id, "inventory");
+    ///         }
+    ///     }
+    /// });
+    /// assert_eq!(setup.await.as_deref(), Some("inventory"));
+    /// # }
+    /// ```
+    pub async fn request_input(&self, prompt: impl Into<String>) -> Option<String> {
+        let prompt = prompt.into();
+        match self
+            .ask(Prompt::Input, |id| ProgressEvent::NeedsInput { id, prompt })
+            .await?
+        {
+            Reply::Value(value) => Some(value),
+            Reply::Approved(_) => None,
+        }
+    }
+
+    /// Reports the request built by `event` and waits for its answer.
+    async fn ask(&self, prompt: Prompt, event: impl FnOnce(u64) -> ProgressEvent) -> Option<Reply> {
+        let id = self.shared.open_prompt(prompt);
+        let answer = Answer {
+            shared: self.shared.clone(),
+            id,
+        };
+        let update = self.snapshot(State::Paused, None).with_event(event(id));
+        self.broadcast(update);
+        let reply = answer.await;
+        if reply.is_some() {
+            let update = self.snapshot(State::Working, None);
+            self.broadcast(update);
+        }
+        reply
+    }
+
+    /// Pauses the progress operation with a descriptive message.
+    ///
+    /// This method sets the progress state to paused and broadcasts the update to all listeners.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn pause_with_message(&self, message: impl Into<String>) {
+        let update = self.snapshot(State::Paused, Some(message.into()));
+        self.broadcast(update);
+    }
+
+    /// Updates the total expected value for the progress operation.
+    ///
+    /// This method changes the total value and broadcasts an update with the current progress.
+    /// The total is shared by all clones of this updater, so their following updates
+    /// carry it too.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn set_total(&mut self, total: u64) {
+        self.total_counter().store(total, Ordering::Relaxed);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Repeats the latest update every `interval` until the task finishes.
+    ///
+    /// Observers that track when they last heard from a task, such as remote dashboards,
+    /// cannot tell a task that is busy between updates from one that stopped. With a
+    /// tick, they keep hearing from it. The updates are repeated from a helper thread,
+    /// so they keep coming even while the task blocks its executor with CPU-bound work.
+    /// Each repetition is a [heartbeat](ProgressUpdate::is_heartbeat) carrying the time
+    /// since the task last reported, and gets a new [`sequence`](ProgressUpdate::sequence)
+    /// number.
+    ///
+    /// Every call spawns an OS thread that lives until the task finishes or its last
+    /// updater is dropped. Calling it again replaces the previous thread, so a task
+    /// ticks at most once per interval.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the helper thread cannot be spawned.
+    pub fn auto_tick(&self, interval: Duration) {
+        self.shared.enable(Parts::LATEST);
+        lock(&self.shared.latest)
+            .get_or_insert_with(|| (self.snapshot(State::Working, None), Instant::now()));
+        let previous = lock(&self.shared.ticker).take();
+        if let Some(previous) = previous {
+            previous.stop();
+        }
+
+        let shared = self.shared.clone();
+        let sender = self.sender.clone();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = stopped.clone();
+        let thread = std::thread::Builder::new()
+            .name("progressor-tick".into())
+            .spawn(move || {
+                loop {
+                    let deadline = Instant::now() + interval;
+                    let mut now = Instant::now();
+                    while now < deadline && !stop.load(Ordering::Acquire) {
+                        std::thread::park_timeout(deadline - now);
+                        now = Instant::now();
+                    }
+                    if stop.load(Ordering::Acquire) || shared.is_finished() {
+                        break;
+                    }
+                    let latest = lock(&shared.latest).clone();
+                    if let Some((update, sent)) = latest {
+                        let sequence = shared.sequence.fetch_add(1, Ordering::Relaxed);
+                        let heartbeat = update
+                            .with_sequence(sequence)
+                            .with_last_progress_age(sent.elapsed());
+                        shared.broadcast(sender.as_ref(), heartbeat);
+                    }
+                }
+            })
+            .expect("failed to spawn the tick thread");
+        *lock(&self.shared.ticker) = Some(Ticker { stopped, thread });
+    }
+
+    /// Returns the pacing most recently requested by an observer.
+    ///
+    /// Observers set the hint through [`ProgressController::set_pacing`]. While they
+    /// request [`Pacing::Normal`], the hint comes from the task's [`PacingProvider`], if
+    /// any, so long background jobs can also back off on battery or under thermal
+    /// pressure. The hint is advisory: the task decides whether and how to slow down
+    /// or speed up.
+    #[must_use]
+    pub fn pacing_hint(&self) -> Pacing {
+        match Pacing::from_u8(self.shared.pacing.load(Ordering::Relaxed)) {
+            Pacing::Normal => lock(&self.shared.pacing_provider)
+                .as_ref()
+                .map_or(Pacing::Normal, |provider| provider.0.pacing()),
+            requested => requested,
+        }
+    }
+
+    /// Returns the priority of the task, as last set at creation or by a controller.
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        Priority::from_u8(self.shared.priority.load(Ordering::Relaxed))
+    }
+
+    /// Returns the current progress value, as last reported through any clone of this
+    /// updater.
+    #[must_use]
+    pub fn current(&self) -> u64 {
+        self.position().load(Ordering::Relaxed)
+    }
+
+    /// Returns the total expected value of the operation.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.total_counter().load(Ordering::Relaxed)
+    }
+
+    /// Returns an updater for a nested operation covering `weight` of this updater's
+    /// total, which counts its own progress from 0 to `child_total`.
+    ///
+    /// The child's updates are scaled into this updater's range and reported as the
+    /// task's progress. A function that reports through a [`ProgressUpdater`] can thus
+    /// run as one stage of a larger task without knowing about it. Children can have
+    /// children of their own.
+    ///
+    /// The child covers the `weight` following this updater's current value, which
+    /// moves past it right away without reporting anything. The next child therefore
+    /// starts where this one ends, whether stages run one after another or side by side,
+    /// and this updater continues from there, for instance with
+    /// [`advance`](Self::advance).
+    ///
+    /// Completing a child reports its whole share as done, without completing the task.
+    /// Dropping a child without completing it reports nothing: its parent decides how
+    /// the task ends. Failing a child, or panicking while holding one, fails the task,
+    /// and completing its parent afterwards has no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{ProgressExt, ProgressUpdater, progress};
+    ///
+    /// async fn download(mut updater: ProgressUpdater) {
+    ///     for chunk in 1..=64 {
+    ///         updater.update(chunk);
+    ///     }
+    ///     updater.complete();
+    /// }
+    ///
+    /// # async fn example() {
+    /// progress(100, |mut updater| async move {
+    ///     // Downloading is 80% of the work, unpacking the rest.
+    ///     download(updater.child(80, 64)).await;
+    ///     let mut unpack = updater.child(20, 3);
+    ///     for file in 1..=3 {
+    ///         unpack.update(file);
+    ///     }
+    ///     updater.complete();
+    /// })
+    /// .observe(|update| println!("{}/{}", update.current(), update.total()))
+    /// .await;
+    /// # }
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn child(&mut self, weight: u64, child_total: u64) -> Self {
+        let (total, current) = self.task_counts();
+        let own_total = self.total();
+        let range = match &self.scale {
+            None => weight as f64,
+            Some(_) if own_total == 0 => 0.0,
+            Some(scale) => scale.range * weight as f64 / own_total as f64,
+        };
+        self.position().fetch_add(weight, Ordering::Relaxed);
+        let mut child = self.clone();
+        child.scale = Some(Arc::new(Scale {
+            total,
+            offset: current as f64,
+            range,
+            position: AtomicU64::new(0),
+            child_total: AtomicU64::new(child_total),
+            completed: AtomicBool::new(false),
+        }));
+        child
+    }
+
+    /// Reports new counts and lets the caller attach extra data to the update.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub(crate) fn report(
+        &self,
+        total: u64,
+        current: u64,
+        decorate: impl FnOnce(ProgressUpdate) -> ProgressUpdate,
+    ) {
+        self.total_counter().store(total, Ordering::Relaxed);
+        self.set_current(current);
+        let update = decorate(self.snapshot(State::Working, None));
+        self.broadcast(update);
+    }
+
+    /// Sets a domain-specific state that refines the lifecycle state.
+    ///
+    /// Tasks use this to expose meaningful phases such as `"Verifying"` or `"Finalizing"`
+    /// that would otherwise be hidden behind [`State::Working`]. The extra state is carried
+    /// by every following update, from any clone of this updater, until it is changed or
+    /// cleared with `None`.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn set_extra_state(&mut self, extra_state: Option<&'static str>) {
+        self.set_extra_state_silently(extra_state);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Sets the extra state without broadcasting; the next update carries it.
+    pub(crate) fn set_extra_state_silently(&self, extra_state: Option<&'static str>) {
+        *lock(&self.shared.extra_state) = extra_state;
+        self.shared.enable(Parts::EXTRA_STATE);
+    }
+
+    /// Updates the second counter tracked alongside the main progress and broadcasts it.
+    ///
+    /// The counter is shared by all clones of this updater and carried by every following
+    /// update as [`ProgressUpdate::secondary`].
+    /// A file copy, for instance, reports bytes through [`update`](Self::update) and the
+    /// number of files copied through this method.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn update_secondary(&mut self, current: u64, total: u64) {
+        *lock(&self.shared.secondary) = Some(Dimension::new(current, total));
+        self.shared.enable(Parts::SECONDARY);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Records one processed item that succeeded.
+    ///
+    /// This advances the current value by one and broadcasts an update that carries
+    /// the per-outcome counts, see [`ProgressUpdate::outcomes`]. The counts are shared
+    /// by all clones of this updater, so parallel workers report the task's totals.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn record_ok(&mut self) {
+        self.record(|outcomes| outcomes.succeeded += 1);
+    }
+
+    /// Records one processed item that failed.
+    ///
+    /// This advances the current value by one and broadcasts an update that carries
+    /// the per-outcome counts, see [`ProgressUpdate::outcomes`].
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn record_err(&mut self) {
+        self.record(|outcomes| outcomes.failed += 1);
+    }
+
+    /// Records one processed item that was skipped.
+    ///
+    /// This advances the current value by one and broadcasts an update that carries
+    /// the per-outcome counts, see [`ProgressUpdate::outcomes`].
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn record_skipped(&mut self) {
+        self.record(|outcomes| outcomes.skipped += 1);
+    }
+
+    /// Adds `item` to the set of items being processed and broadcasts an update.
+    ///
+    /// The set is shared by all clones of this updater and carried by every update as
+    /// [`ProgressUpdate::active_items`], so concurrent workers can show what is in flight.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn item_started(&self, item: impl Into<String>) {
+        let item = item.into();
+        let mut items = lock(&self.shared.active_items);
+        let started = items.iter().flat_map(|items| items.iter().cloned());
+        *items = Some(started.chain([item]).collect());
+        drop(items);
+        self.shared.enable(Parts::ACTIVE_ITEMS);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Removes `item` from the set of items being processed and broadcasts an update.
+    ///
+    /// This does not advance the current value; report that with [`update`](Self::update)
+    /// or one of the `record_*` methods.
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    pub fn item_finished(&self, item: &str) {
+        self.item_finished_silently(item);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Removes `item` from the active items without broadcasting; the next update reflects it.
+    pub(crate) fn item_finished_silently(&self, item: &str) {
+        let mut items = lock(&self.shared.active_items);
+        let Some(active) = &*items else {
+            return;
+        };
+        if let Some(index) = active.iter().position(|active| active == item) {
+            let rest = active[..index].iter().chain(&active[index + 1..]);
+            *items = (active.len() > 1).then(|| rest.cloned().collect());
+        }
+    }
+
+    /// Returns a controller for the task this updater reports for.
+    pub(crate) fn controller(&self) -> ProgressController {
+        ProgressController {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Hands every following update to `forward` until it returns `false`.
+    pub(crate) fn forward(
+        &self,
+        forward: impl Fn(&ProgressUpdate) -> bool + Send + Sync + 'static,
+    ) {
+        self.shared.add_forward(Box::new(forward));
+    }
+
+    /// Returns the items being processed, in the order they started.
+    #[must_use]
+    pub fn active_items(&self) -> Vec<String> {
+        lock(&self.shared.active_items)
+            .as_deref()
+            .map_or_else(Vec::new, <[String]>::to_vec)
+    }
+
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    fn record(&self, count: impl FnOnce(&mut Outcomes)) {
+        let mut outcomes = lock(&self.shared.outcomes);
+        count(outcomes.get_or_insert_default());
+        self.add(1);
+        drop(outcomes);
+        self.shared.enable(Parts::OUTCOMES);
+        let update = self.snapshot(State::Working, None);
+        self.broadcast(update);
+    }
+
+    /// Returns the counter holding this updater's current value, shared by its clones.
+    fn position(&self) -> &AtomicU64 {
+        self.scale
+            .as_ref()
+            .map_or(&self.shared.position, |scale| &scale.position)
+    }
+
+    /// Returns the counter holding this updater's total, shared by its clones.
+    fn total_counter(&self) -> &AtomicU64 {
+        self.scale
+            .as_ref()
+            .map_or(&self.shared.total, |scale| &scale.child_total)
+    }
+
+    fn set_current(&self, current: u64) {
+        self.position().store(current, Ordering::Relaxed);
+    }
+
+    fn add(&self, delta: u64) {
+        self.position().fetch_add(delta, Ordering::Relaxed);
+    }
+
+    /// Returns the total and current value of the task, which differ from this
+    /// updater's own for a child.
+    #[allow(clippy::cast_precision_loss)]
+    fn task_counts(&self) -> (u64, u64) {
+        let current = self.current();
+        let own_total = self.total();
+        self.scale.as_ref().map_or((own_total, current), |scale| {
+            let fraction = if scale.completed.load(Ordering::Acquire) {
+                1.0
+            } else if own_total == 0 {
+                0.0
+            } else {
+                current.min(own_total) as f64 / own_total as f64
+            };
+            (scale.total, scale.task_current(fraction))
+        })
+    }
+
+    #[cfg_attr(all(feature = "debug-callsite", debug_assertions), track_caller)]
+    fn snapshot(&self, state: State, message: Option<String>) -> ProgressUpdate {
+        let sequence = self.shared.sequence.fetch_add(1, Ordering::Relaxed);
+        let shared = &self.shared;
+        let (total, current, outcomes) = if shared.uses(Parts::OUTCOMES) {
+            // Read together with the position, so the counts add up to the current value.
+            let recorded = lock(&shared.outcomes);
+            let (total, current) = self.task_counts();
+            (total, current, *recorded)
+        } else {
+            let (total, current) = self.task_counts();
+            (total, current, None)
+        };
+        let mut update = ProgressUpdate::new(total, current, state, message)
+            .with_priority(self.priority())
+            .with_sequence(sequence);
+        #[cfg(all(feature = "debug-callsite", debug_assertions))]
+        {
+            update.callsite.location = Some(core::panic::Location::caller());
+        }
+        if let Some(outcomes) = outcomes {
+            update = update.with_outcomes(outcomes);
+        }
+        if shared.uses(Parts::EXTRA_STATE) {
+            update.extra_state = *lock(&shared.extra_state);
+        }
+        if shared.uses(Parts::SECONDARY) {
+            update.secondary = *lock(&shared.secondary);
+        }
+        if shared.uses(Parts::UNIT_COST)
+            && let Some(cost) = *lock(&shared.unit_cost)
+        {
+            update = update.with_unit_cost(cost);
+        }
+        if shared.uses(Parts::ACTIVE_ITEMS) {
+            update.active_items.clone_from(&lock(&shared.active_items));
+        }
+        update
+    }
+
+    fn broadcast(&self, update: ProgressUpdate) {
+        self.shared.broadcast(self.sender.as_ref(), update);
+    }
+
+    /// Cancels the progress operation.
+    pub fn cancel(self) {
+        // Drop will handle cancellation automatically
+    }
+
+    /// Cancels the progress operation, recording why it stopped.
+    ///
+    /// The reason is carried by the [`Cancelled`](State::Cancelled) update and can be
+    /// queried with [`ProgressController::cancel_reason`].
+    pub fn cancel_with_reason(self, reason: CancelReason) {
+        self.shared.set_cancel_reason(reason);
+    }
+
+    /// Returns the reason if an observer has asked the task to stop.
+    ///
+    /// Cancellation is cooperative: the task should check this regularly and return
+    /// early once it is set.
+    #[must_use]
+    pub fn cancel_requested(&self) -> Option<CancelReason> {
+        self.shared.cancel_reason()
+    }
+
+    /// Returns a future that resolves once an observer has asked the task to stop.
+    ///
+    /// This is the awaitable counterpart of [`cancel_requested`](Self::cancel_requested),
+    /// useful for racing the actual work against a cancellation request.
+    pub fn cancelled(&self) -> impl Future<Output = CancelReason> + Send + 'static {
+        Cancelled {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Drop for ProgressUpdater {
+    fn drop(&mut self) {
+        let last = self.shared.updaters.fetch_sub(1, Ordering::AcqRel) == 1;
+        if last {
+            // Stop ticking before the final update, which must be the last one sent.
+            let ticker = lock(&self.shared.ticker).take();
+            if let Some(ticker) = ticker {
+                ticker.stop();
+            }
+        }
+        if !self.shared.is_finished() {
+            let update = if std::thread::panicking() {
+                Some(self.snapshot(State::Failed, Some("task panicked".to_owned())))
+            } else if last {
+                let update = self.snapshot(State::Cancelled, None);
+                Some(match self.shared.cancel_reason() {
+                    Some(reason) => update.with_cancel_reason(reason),
+                    None => update,
+                })
+            } else {
+                // Other clones are still at work and report how the task ends.
+                None
+            };
+            if let Some(update) = update {
+                self.broadcast(update);
+            }
+        }
+        // The last updater is going away: release the forwards, ending their streams.
+        if last {
+            let mut subscribers = lock(&self.shared.subscribers);
+            subscribers.closed = true;
+            subscribers.list.clear();
+            subscribers.inline = Arc::new([]);
+        }
+    }
+}
+
+/// A cloneable handle that lets observers send feedback to a running task.
+///
+/// Obtain one with [`ProgressFuture::controller`] before handing the future to an
+/// executor or to [`observe`](crate::ProgressExt::observe).
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct ProgressController {
+    shared: Arc<Shared>,
+}
+
+impl ProgressController {
+    /// Asks the task to change its pace.
+    ///
+    /// The task reads the hint with [`ProgressUpdater::pacing_hint`].
+    pub fn set_pacing(&self, pacing: Pacing) {
+        self.shared.pacing.store(pacing as u8, Ordering::Relaxed);
+    }
+
+    /// Returns the pacing currently requested from the task.
+    #[must_use]
+    pub fn pacing(&self) -> Pacing {
+        Pacing::from_u8(self.shared.pacing.load(Ordering::Relaxed))
+    }
+
+    /// Changes the priority of the task.
+    ///
+    /// The new priority is carried by every update the task reports from now on.
+    pub fn set_priority(&self, priority: Priority) {
+        self.shared
+            .priority
+            .store(priority as u8, Ordering::Relaxed);
+    }
+
+    /// Returns the current priority of the task.
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        Priority::from_u8(self.shared.priority.load(Ordering::Relaxed))
+    }
+
+    /// Asks the task to stop, recording why.
+    ///
+    /// The task sees the request through [`ProgressUpdater::cancel_requested`] and the
+    /// reason is carried by its final [`Cancelled`](State::Cancelled) update.
+    pub fn cancel(&self, reason: CancelReason) {
+        self.shared.set_cancel_reason(reason);
+    }
+
+    /// Approves the action the task asked about with the request `id`, see
+    /// [`ProgressEvent::NeedsConfirmation`].
+    ///
+    /// Only the first answer counts; answers to requests the task no longer waits on,
+    /// for example because another observer answered first, are ignored.
+    pub fn approve(&self, id: u64) {
+        self.shared.answer(id, Reply::Approved(true));
+    }
+
+    /// Denies the action the task asked about with the request `id`, see [`approve`](Self::approve).
+    pub fn deny(&self, id: u64) {
+        self.shared.answer(id, Reply::Approved(false));
+    }
+
+    /// Answers the request `id` of a [`ProgressEvent::NeedsInput`] with `value`.
+    ///
+    /// Like [`approve`](Self::approve), only the first answer counts.
+    pub fn respond(&self, id: u64, value: impl Into<String>) {
+        self.shared.answer(id, Reply::Value(value.into()));
+    }
+
+    /// Grants a task created with [`ProgressFuture::with_time_budget`] `extra` active
+    /// time, resuming it if it was suspended.
+    pub fn extend_budget(&self, extra: Duration) {
+        if let Some(budget) = &mut *lock(&self.shared.time_budget) {
+            budget.limit = budget.limit.saturating_add(extra);
+        }
+        wake_all(&self.shared.time_budget_wakers);
+    }
+
+    /// Returns why the task was cancelled, or was asked to cancel, if it was.
+    #[must_use]
+    pub fn cancel_reason(&self) -> Option<CancelReason> {
+        self.shared.cancel_reason()
+    }
+
+    /// Asks the task to pause at its next [`ProgressUpdater::checkpoint`].
+    ///
+    /// The task reports [`Paused`](State::Paused) once it reaches the checkpoint and waits
+    /// there until [`resume`](Self::resume). Tasks that never call `checkpoint` keep
+    /// running.
+    pub fn pause(&self) {
+        self.shared.set_pause_requested(true);
+    }
+
+    /// Lifts a [`pause`](Self::pause), letting the task continue past its checkpoint.
+    pub fn resume(&self) {
+        self.shared.set_pause_requested(false);
+    }
+
+    /// Returns `true` while a [`pause`](Self::pause) is in effect.
+    #[must_use]
+    pub fn is_pause_requested(&self) -> bool {
+        self.shared.is_pause_requested()
+    }
+
+    /// Returns `true` once the task has reported [`Completed`](State::Completed),
+    /// [`Cancelled`](State::Cancelled) or [`Failed`](State::Failed).
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.shared.is_finished()
+    }
+
+    /// Returns the terminal state the task reported, or `None` while it is running.
+    ///
+    /// Unlike the last update of a progress stream, which a full buffer may drop, this
+    /// is read from the state shared with the task and is always accurate.
+    #[must_use]
+    pub fn final_state(&self) -> Option<State> {
+        self.shared.final_state()
+    }
+
+    /// Returns a future that resolves once the task has reported a terminal state.
+    ///
+    /// Unlike a progress stream, this does not buffer updates, so it can be kept around
+    /// for as long as needed.
+    pub fn finished(&self) -> impl Future<Output = ()> + Send + 'static {
+        Finished {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// A cloneable view of a task for the components that observe and steer it.
+///
+/// Created by [`ProgressFuture::split`], so that the code running the task can own the
+/// future while others subscribe to its progress. It also dereferences to the task's
+/// [`ProgressController`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct ProgressHandle {
+    receiver: InactiveReceiver<ProgressUpdate>,
+    controller: ProgressController,
+    received: Option<u64>,
+}
+
+impl ProgressHandle {
+    /// Returns a stream of the task's progress updates, like [`Progress::progress`].
+    pub fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        self.receiver.activate_cloned()
+    }
+
+    /// Returns a handle to the log of state changes of the task.
+    #[must_use]
+    pub fn transitions(&self) -> TransitionLog {
+        self.controller.shared.transitions.clone()
+    }
+
+    /// Returns the latest update the task reported since the handle was split off.
+    ///
+    /// This does not need an executor, so plain threads such as a render thread can
+    /// poll it.
+    #[must_use]
+    pub fn latest(&self) -> Option<ProgressUpdate> {
+        lock(&self.controller.shared.latest)
+            .as_ref()
+            .map(|(update, _)| update.clone())
+    }
+
+    /// Blocks the current thread until the task reports something this handle has not
+    /// received yet, and returns the latest update.
+    ///
+    /// Updates reported in between are skipped, so a slow reader always sees the
+    /// current state. Returns `None` once the final update has been received. This
+    /// must not be called from async code, as it would block the executor.
+    pub fn blocking_recv_latest(&mut self) -> Option<ProgressUpdate> {
+        let shared = &self.controller.shared;
+        let mut latest = lock(&shared.latest);
+        loop {
+            if let Some((update, _)) = &*latest {
+                if self
+                    .received
+                    .is_none_or(|received| update.sequence() > received)
+                {
+                    self.received = Some(update.sequence());
+                    return Some(update.clone());
+                }
+                if update.is_terminal() {
+                    return None;
+                }
+            }
+            latest = shared
+                .latest_changed
+                .wait(latest)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Returns the controller for sending feedback to the task.
+    #[must_use]
+    pub const fn controller(&self) -> &ProgressController {
+        &self.controller
+    }
+}
+
+impl Deref for ProgressHandle {
+    type Target = ProgressController;
+
+    fn deref(&self) -> &ProgressController {
+        &self.controller
+    }
+}
+
+pin_project! {
+    /// A progress-tracked future created by [`progress`].
+    ///
+    /// It resolves to the output of the wrapped future and implements [`Progress`]
+    /// to expose the updates reported through its [`ProgressUpdater`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub struct ProgressFuture<Fut>
+    where
+        Fut: Future,
+    {
+        receiver: InactiveReceiver<ProgressUpdate>,
+        shared: Arc<Shared>,
+        timer: Option<Delay>,
+        #[pin]
+        fut: Fut,
+    }
+}
+
+impl<Fut> ProgressFuture<Fut>
+where
+    Fut: Future,
+{
+    /// Returns a controller for sending feedback to the task.
+    #[must_use]
+    pub fn controller(&self) -> ProgressController {
+        ProgressController {
+            shared: self.shared.clone(),
         }
     }
 
-    /// Updates the progress with the given current value and message.
+    /// Splits off a [`ProgressHandle`] for observing and steering the task elsewhere.
     ///
-    /// This will broadcast the update to all progress stream listeners.
-    pub fn update_with_message(&mut self, current: u64, message: impl Into<String>) {
-        self.current = current;
-        let update = ProgressUpdate::new(self.total, current, State::Working, Some(message.into()));
-        self.broadcast(update);
+    /// The future stays with the code that runs the task, while the handle can be
+    /// cloned and sent to the components that render progress or cancel the task.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{CancelReason, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let (task, handle) = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///     }
+    /// })
+    /// .split();
+    ///
+    /// let ui = handle.clone();
+    /// tokio::spawn(async move {
+    ///     let mut updates = ui.progress();
+    ///     while let Some(update) = updates.next().await {
+    ///         println!("{}/{}", update.current(), update.total());
+    ///     }
+    /// });
+    /// let shutdown = handle;
+    /// tokio::spawn(async move { shutdown.cancel(CancelReason::Shutdown) });
+    ///
+    /// task.await;
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn split(self) -> (Self, ProgressHandle) {
+        self.shared.enable(Parts::LATEST);
+        let handle = ProgressHandle {
+            receiver: self.receiver.clone(),
+            controller: self.controller(),
+            received: None,
+        };
+        (self, handle)
     }
 
-    /// Updates the progress with the given current value.
+    /// Returns a verifier comparing the updates this task sends from now on with the
+    /// updates a stream receives, see [`DeliveryVerifier`](crate::DeliveryVerifier).
+    #[must_use]
+    pub fn verifier(&self) -> crate::DeliveryVerifier {
+        let (verifier, forward) = crate::DeliveryVerifier::new();
+        self.shared.add_forward(Box::new(forward));
+        verifier
+    }
+
+    /// Returns a stream of only the updates for which `filter` returns `true`.
     ///
-    /// This will broadcast the update to all progress stream listeners.
-    pub fn update(&mut self, current: u64) {
-        self.current = current;
-        let update = ProgressUpdate::new(self.total, current, State::Working, None);
-        self.broadcast(update);
+    /// Filtering happens when the update is sent, and every filtered subscriber has its
+    /// own buffer. Low-bandwidth subscribers such as webhooks or remote mirrors can thin
+    /// out the updates they receive without throttling other observers. If such a
+    /// subscriber falls behind, its oldest buffered updates are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{State, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(1000, |mut updater| async move {
+    ///     for i in 1..=1000 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// });
+    /// let mut milestones =
+    ///     task.subscribe_filtered(|u| u.state() != State::Working || u.current() % 100 == 0);
+    /// tokio::spawn(task);
+    ///
+    /// while let Some(update) = milestones.next().await {
+    ///     println!("{}/{}", update.current(), update.total());
+    /// }
+    /// # }
+    /// ```
+    pub fn subscribe_filtered(
+        &self,
+        filter: impl Fn(&ProgressUpdate) -> bool + Send + Sync + 'static,
+    ) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
+        let (mut sender, receiver) = broadcast(32);
+        sender.set_overflow(true);
+        #[cfg(feature = "diagnostics")]
+        let diagnostics = self.shared.diagnostics.clone();
+        self.shared.add_forward(Box::new(move |update| {
+            if filter(update) {
+                #[cfg(feature = "diagnostics")]
+                diagnostics.send_to_subscriber(&sender, update.clone(), update);
+                #[cfg(not(feature = "diagnostics"))]
+                let _ = sender.try_broadcast(update.clone());
+            }
+            sender.receiver_count() > 0
+        }));
+        receiver
     }
 
-    /// Pauses the progress operation.
+    /// Returns a stream of the updates behind an [`Arc`].
     ///
-    /// This method sets the progress state to paused and broadcasts the update to all listeners.
-    pub fn pause(&self) {
-        let update = ProgressUpdate::new(self.total, self.current, State::Paused, None);
-        self.broadcast(update);
+    /// Every [`progress`](Progress::progress) stream receives its own clone of each
+    /// update, including its message and active items. All streams returned by this
+    /// method share a single clone per update instead, which matters on hot paths with
+    /// many subscribers. If a subscriber falls behind, its oldest buffered updates are
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::progress;
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update_with_message(i, format!("Processing item {i}"));
+    ///     }
+    ///     updater.complete();
+    /// });
+    /// let mut renderers: Vec<_> = (0..8).map(|_| task.progress_shared()).collect();
+    /// tokio::spawn(task);
+    ///
+    /// while let Some(update) = renderers[0].next().await {
+    ///     println!("{}", update.message().unwrap_or_default());
+    /// }
+    /// # }
+    /// ```
+    pub fn progress_shared(
+        &self,
+    ) -> impl Stream<Item = Arc<ProgressUpdate>> + Unpin + Send + 'static {
+        self.shared.arc_updates()
     }
 
-    /// Marks the progress operation as completed.
+    /// Returns a stream of compact updates for high-frequency counters.
     ///
-    /// This method sets the completed flag and broadcasts a completion update.
-    /// Subsequent calls to this method have no effect.
-    pub fn complete(&mut self) {
-        if !self.completed {
-            self.completed = true;
-            let update = ProgressUpdate::new(self.total, self.current, State::Completed, None);
-            self.broadcast(update);
-        }
+    /// An update that differs from the last full one only in its current value is sent
+    /// as a small [`CounterUpdate`](crate::CounterUpdate) instead of a full
+    /// [`ProgressUpdate`]; the receiver merges it with the metadata it already has, see
+    /// [`CompactProgress::expand`]. Tasks reporting millions of updates then copy a few
+    /// bytes per update instead of the whole struct.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::{CompactUpdate, progress};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() {
+    /// let task = progress(1_000_000, |mut updater| async move {
+    ///     for i in 1..=1_000_000 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// });
+    /// let mut updates = task.progress_compact();
+    /// tokio::spawn(task);
+    ///
+    /// while let Some(update) = updates.next().await {
+    ///     match update {
+    ///         CompactUpdate::Full(update) => println!("{update:?}"),
+    ///         CompactUpdate::Counter(counter) => println!("{}", counter.current()),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn progress_compact(&self) -> CompactProgress {
+        let (sender, receiver) = broadcast(32);
+        self.shared.add_forward(Box::new(compact::forward(sender)));
+        CompactProgress::new(receiver)
     }
 
-    /// Pauses the progress operation with a descriptive message.
+    /// Also delivers every update through `transport`.
     ///
-    /// This method sets the progress state to paused and broadcasts the update to all listeners.
-    pub fn pause_with_message(&self, message: impl Into<String>) {
-        let update = ProgressUpdate::new(
-            self.total,
-            self.current,
-            State::Paused,
-            Some(message.into()),
-        );
-        self.broadcast(update);
+    /// This adds a delivery path rather than replacing the task's channel: its own
+    /// [`progress`](Progress::progress) streams keep working. The transport is released
+    /// when it reports being closed or the task finishes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use progressor::progress;
+    ///
+    /// # async fn example() {
+    /// let (sender, mut receiver) = async_broadcast::broadcast(64);
+    /// let task = progress(100, |mut updater| async move {
+    ///     for i in 1..=100 {
+    ///         updater.update(i);
+    ///     }
+    ///     updater.complete();
+    /// })
+    /// .with_transport(sender);
+    /// tokio::spawn(task);
+    ///
+    /// while let Ok(update) = receiver.recv().await {
+    ///     println!("{}/{}", update.current(), update.total());
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_transport(self, transport: impl Transport) -> Self {
+        self.shared
+            .add_forward(Box::new(move |update| transport.send(update)));
+        self
     }
 
-    /// Updates the total expected value for the progress operation.
+    /// Applies `policy` to every message before it is broadcast.
+    #[must_use]
+    pub fn with_message_policy(self, policy: MessagePolicy) -> Self {
+        *lock(&self.shared.message_policy) = policy;
+        self.shared.enable(Parts::FILTERS);
+        self
+    }
+
+    /// Limits the time the task may spend working to `budget`.
     ///
-    /// This method changes the total value and broadcasts an update with the current progress.
-    pub fn set_total(&mut self, total: u64) {
-        self.total = total;
-        let update = ProgressUpdate::new(self.total, self.current, State::Working, None);
-        self.broadcast(update);
+    /// Time is counted from the first poll, except while the task reports itself
+    /// [`Paused`](State::Paused). Once the budget is used up, the task is suspended or
+    /// cancelled depending on `action`. A suspended task is no longer polled and
+    /// reports a [`Paused`](State::Paused) update carrying
+    /// [`ProgressEvent::BudgetExhausted`], until [`ProgressController::extend_budget`]
+    /// grants more time. A cancelled one sees [`CancelReason::Timeout`] through
+    /// [`ProgressUpdater::cancel_requested`]. This suits fair-share scheduling of
+    /// background maintenance tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use progressor::{BudgetAction, progress};
+    ///
+    /// # async fn example() {
+    /// let vacuum = progress(1_000, |mut updater| async move {
+    ///     for page in 1..=1_000 {
+    ///         if updater.cancel_requested().is_some() {
+    ///             return page;
+    ///         }
+    ///         tokio::time::sleep(Duration::from_millis(1)).await;
+    ///         updater.update(page);
+    ///     }
+    ///     1_000
+    /// })
+    /// .with_time_budget(Duration::from_millis(50), BudgetAction::Cancel);
+    /// let vacuumed = vacuum.await;
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_time_budget(self, budget: Duration, action: BudgetAction) -> Self {
+        *lock(&self.shared.time_budget) = Some(TimeBudget {
+            limit: budget,
+            action,
+            clock: None,
+            total: 0,
+            current: 0,
+            exhausted: false,
+        });
+        self.shared.enable(Parts::TIME_BUDGET);
+        self
     }
 
-    fn broadcast(&self, update: ProgressUpdate) {
-        let _ = self.sender.try_broadcast(update);
+    /// Consults `provider` for the task's [`pacing_hint`](ProgressUpdater::pacing_hint)
+    /// while observers do not request a specific pace.
+    #[must_use]
+    pub fn with_pacing_provider(self, provider: impl PacingProvider) -> Self {
+        *lock(&self.shared.pacing_provider) = Some(Provider(Box::new(provider)));
+        self
     }
-    /// Cancels the progress operation.
-    pub fn cancel(self) {
-        // Drop will handle cancellation automatically
+
+    /// Attaches `cost` per unit of work to every update.
+    ///
+    /// Observers read the accumulated and projected spend with
+    /// [`ProgressUpdate::cost`] and [`ProgressUpdate::projected_cost`], such as API
+    /// billing or cloud egress of a batch job next to its progress.
+    #[must_use]
+    pub fn with_unit_cost(self, cost: f64) -> Self {
+        *lock(&self.shared.unit_cost) = Some(cost);
+        self.shared.enable(Parts::UNIT_COST);
+        self
     }
-}
 
-impl Drop for ProgressUpdater {
-    fn drop(&mut self) {
-        if !self.completed {
-            let _ = self.sender.try_broadcast(ProgressUpdate::new(
-                self.total,
-                self.current,
-                State::Cancelled,
-                None,
-            ));
-        }
+    /// Masks secrets in every update before it is broadcast, see [`Redactor`].
+    #[must_use]
+    pub fn with_redactor(self, redactor: Redactor) -> Self {
+        *lock(&self.shared.redactor) = Some(redactor);
+        self.shared.enable(Parts::FILTERS);
+        self
     }
-}
 
-pin_project! {
-    struct ProgressFuture<Fut>
-    where
-        Fut: Future,
-    {
-        receiver: Receiver<ProgressUpdate>,
-        #[pin]
-        fut: Fut,
+    /// Caps the total size of messages retained in the task's channel at `bytes`.
+    ///
+    /// Updates wait in the channel until every observer has read them. A task that
+    /// attaches large messages to each item could otherwise retain a full buffer of
+    /// them. With a budget, each message is truncated to an equal share of it, at a
+    /// character boundary.
+    #[must_use]
+    pub fn with_message_budget(self, bytes: usize) -> Self {
+        self.shared.message_budget.store(bytes, Ordering::Relaxed);
+        self
+    }
+
+    /// Sets the initial priority of the task.
+    ///
+    /// The priority can be changed later with [`ProgressController::set_priority`].
+    #[must_use]
+    pub fn with_priority(self, priority: Priority) -> Self {
+        self.shared
+            .priority
+            .store(priority as u8, Ordering::Relaxed);
+        self
+    }
+
+    /// Enables the [`TransitionLog`], retaining at most `capacity` state changes.
+    ///
+    /// Read the log through [`Progress::transitions`].
+    #[must_use]
+    pub fn with_transition_log(self, capacity: usize) -> Self {
+        self.shared.transitions.set_capacity(capacity);
+        self
     }
 }
 
@@ -134,7 +2049,35 @@ where
     type Output = Fut::Output;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.project().fut.poll(cx)
+        let this = self.project();
+        loop {
+            match this.shared.check_time_budget(Instant::now()) {
+                BudgetCheck::Unlimited => break,
+                BudgetCheck::Run(remaining) => {
+                    let timer = this.timer.get_or_insert_with(|| Delay::new(remaining));
+                    if Pin::new(timer).poll(cx).is_pending() {
+                        break;
+                    }
+                    // Time is up, unless the task paused meanwhile: check again.
+                    *this.timer = None;
+                }
+                BudgetCheck::Suspend(update) | BudgetCheck::Resume(update) => {
+                    let sender = this.receiver.activate_cloned().new_sender();
+                    this.shared.broadcast(Some(&sender), update);
+                }
+                BudgetCheck::Suspended => {
+                    register(&this.shared.time_budget_wakers, cx.waker());
+                    // Check again in case more time was granted while registering.
+                    if matches!(
+                        this.shared.check_time_budget(Instant::now()),
+                        BudgetCheck::Suspended
+                    ) {
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+        this.fut.poll(cx)
     }
 }
 
@@ -143,7 +2086,20 @@ where
     Fut: Future,
 {
     fn progress(&self) -> impl Stream<Item = ProgressUpdate> + Unpin + Send + 'static {
-        self.receiver.clone()
+        self.receiver.activate_cloned()
+    }
+
+    fn transitions(&self) -> TransitionLog {
+        self.shared.transitions.clone()
+    }
+
+    fn controller(&self) -> Option<ProgressController> {
+        Some(Self::controller(self))
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&self) -> crate::Diagnostics {
+        self.shared.diagnostics.clone()
     }
 }
 
@@ -182,18 +2138,991 @@ where
 ///         State::Paused => println!("Task paused at {}%", (update.completed_fraction() * 100.0) as u32),
 ///         State::Completed => println!("Task completed!"),
 ///         State::Cancelled => println!("Task cancelled!"),
+///         State::Failed => println!("Task failed: {}", update.message().unwrap_or_default()),
 ///     }
 /// }
 /// # }
 /// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-pub fn progress<F, Fut>(total: u64, f: F) -> impl Progress<Output = Fut::Output>
+pub fn progress<F, Fut>(total: u64, f: F) -> ProgressFuture<Fut>
 where
     F: FnOnce(ProgressUpdater) -> Fut,
     Fut: Future,
 {
     let (sender, receiver) = broadcast(32);
-    let updater = ProgressUpdater::new(total, sender);
+    let shared = Arc::new(Shared::default());
+    let updater = ProgressUpdater::new(total, Some(sender), shared.clone());
     let fut = f(updater);
-    ProgressFuture { receiver, fut }
+    ProgressFuture {
+        receiver: receiver.deactivate(),
+        shared,
+        timer: None,
+        fut,
+    }
+}
+
+/// Creates a progress-tracked future for an operation whose total is unknown.
+///
+/// This is [`progress`] with a total of zero: updates count what was done so far, such
+/// as the bytes of a download without a known length, and are
+/// [indeterminate](ProgressUpdate::is_indeterminate), so observers render a spinner
+/// instead of a bar. Should the total become known, report it with
+/// [`ProgressUpdater::set_total`].
+///
+/// # Examples
+///
+/// ```
+/// use progressor::{Progress, progress_indeterminate};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let download = progress_indeterminate(|mut updater| async move {
+///     let mut received = 0;
+///     for chunk in [512, 2048, 1024] {
+///         received += chunk;
+///         updater.update(received);
+///     }
+///     updater.complete();
+/// });
+///
+/// let mut updates = download.progress();
+/// tokio::spawn(download);
+/// while let Some(update) = updates.next().await {
+///     match update.known_fraction() {
+///         Some(fraction) => println!("{:.0}%", fraction * 100.0),
+///         None => println!("{} bytes so far", update.current()),
+///     }
+/// }
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn progress_indeterminate<F, Fut>(f: F) -> ProgressFuture<Fut>
+where
+    F: FnOnce(ProgressUpdater) -> Fut,
+    Fut: Future,
+{
+    progress(0, f)
+}
+
+/// Creates a task whose updates are handed straight to `observer`.
+///
+/// Unlike [`progress`], no channel is involved: `observer` is called synchronously from
+/// every [`ProgressUpdater`] method that reports an update, on the task's thread. This
+/// suits single-consumer cases such as command-line tools and embedded targets, where
+/// buffering and fan-out are pure overhead. The returned future is the one created by
+/// `f`, so it has no progress stream or controller.
+///
+/// `observer` may report progress for the same task, such as through a clone of its
+/// updater; those updates reach streams and other observers, but not `observer` itself.
+///
+/// # Examples
+///
+/// ```
+/// use progressor::progress_inline;
+///
+/// # async fn example() {
+/// let mut last = 0;
+/// let checksum = progress_inline(
+///     100,
+///     move |update| {
+///         let percent = (update.completed_fraction() * 100.0) as u32;
+///         if percent != last {
+///             last = percent;
+///             eprint!("\r{percent}%");
+///         }
+///     },
+///     |mut updater| async move {
+///         let mut checksum = 0_u64;
+///         for i in 1..=100 {
+///             checksum = checksum.wrapping_mul(31).wrapping_add(i);
+///             updater.update(i);
+///         }
+///         checksum
+///     },
+/// )
+/// .await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn progress_inline<F, Fut>(
+    total: u64,
+    observer: impl FnMut(&ProgressUpdate) + Send + 'static,
+    f: F,
+) -> Fut
+where
+    F: FnOnce(ProgressUpdater) -> Fut,
+    Fut: Future,
+{
+    let shared = Arc::new(Shared::default());
+    let observer = InlineObserver {
+        observer: Mutex::new(observer),
+        caller: Mutex::new(None),
+    };
+    shared.add_inline_observer(Arc::new(move |update| observer.call(update)));
+    f(ProgressUpdater::new(total, None, shared))
+}
+
+/// Creates an updater that is not tied to a future, with the receiving end of its updates.
+///
+/// Adapters such as [`ProgressSink`](crate::ProgressSink) report progress for types
+/// that are not futures themselves.
+pub fn detached(total: u64) -> (ProgressUpdater, InactiveReceiver<ProgressUpdate>) {
+    let (sender, receiver) = broadcast(32);
+    let updater = ProgressUpdater::new(total, Some(sender), Arc::new(Shared::default()));
+    (updater, receiver.deactivate())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{FutureExt, StreamExt};
+
+    #[tokio::test]
+    async fn test_updates_beyond_channel_capacity() {
+        let task = progress(100, |mut updater| async move {
+            for i in 1..=100 {
+                updater.update(i);
+                tokio::task::yield_now().await;
+            }
+        });
+        let mut updates = task.progress();
+        let counter = tokio::spawn(async move {
+            let mut last = 0;
+            while let Some(update) = updates.next().await {
+                last = last.max(update.current());
+            }
+            last
+        });
+        task.await;
+        assert_eq!(counter.await.unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_pacing_hint() {
+        let task = progress(1, |updater| async move { updater.pacing_hint() });
+        let controller = task.controller();
+        assert_eq!(controller.pacing(), Pacing::Normal);
+
+        controller.set_pacing(Pacing::Slow);
+        assert_eq!(task.await, Pacing::Slow);
+
+        let hint = |updater: ProgressUpdater| async move { updater.pacing_hint() };
+        let task = progress(1, hint).with_pacing_provider(|| Pacing::Slow);
+        assert_eq!(task.await, Pacing::Slow);
+
+        let task = progress(1, hint).with_pacing_provider(|| Pacing::Slow);
+        let controller = task.controller();
+        assert_eq!(controller.pacing(), Pacing::Normal);
+        controller.set_pacing(Pacing::Fast);
+        assert_eq!(task.await, Pacing::Fast);
+    }
+
+    #[tokio::test]
+    async fn test_priority_in_updates() {
+        let task = progress(10, |mut updater| async move {
+            updater.update(1);
+            tokio::task::yield_now().await;
+            updater.update(2);
+        })
+        .with_priority(Priority::Low);
+        let controller = task.controller();
+        let mut updates = task.progress();
+
+        let task = tokio::spawn(task);
+        assert_eq!(updates.next().await.unwrap().priority(), Priority::Low);
+        controller.set_priority(Priority::High);
+        task.await.unwrap();
+        assert_eq!(updates.next().await.unwrap().priority(), Priority::High);
+    }
+
+    #[tokio::test]
+    async fn test_record_outcomes() {
+        let task = progress(3, |mut updater| async move {
+            updater.record_ok();
+            updater.record_err();
+            updater.record_skipped();
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let last = updates.collect::<Vec<_>>().await.pop().unwrap();
+        assert_eq!(last.current(), 3);
+        assert_eq!(last.outcomes(), Some(Outcomes::new(1, 1, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_record_outcomes_across_clones() {
+        let task = progress(20, |mut updater| async move {
+            let mut workers = Vec::new();
+            for _ in 0..4 {
+                let mut worker = updater.clone();
+                workers.push(tokio::spawn(async move {
+                    for item in 0..5 {
+                        match item % 5 {
+                            0 => worker.record_err(),
+                            1 => worker.record_skipped(),
+                            _ => worker.record_ok(),
+                        }
+                    }
+                }));
+            }
+            for worker in workers {
+                worker.await.unwrap();
+            }
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates: Vec<_> = updates.collect().await;
+        for update in &updates {
+            let outcomes = update.outcomes().unwrap();
+            assert_eq!(
+                outcomes.succeeded() + outcomes.failed() + outcomes.skipped(),
+                update.current()
+            );
+        }
+        assert_eq!(
+            updates.last().unwrap().outcomes(),
+            Some(Outcomes::new(12, 4, 4))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reason() {
+        let task = progress(10, |updater| async move {
+            while updater.cancel_requested().is_none() {
+                tokio::task::yield_now().await;
+            }
+        });
+        let controller = task.controller();
+        let updates = task.progress();
+
+        controller.cancel(CancelReason::Shutdown);
+        task.await;
+
+        let last = updates.collect::<Vec<_>>().await.pop().unwrap();
+        assert!(last.is_cancelled());
+        assert_eq!(last.cancel_reason(), Some(CancelReason::Shutdown));
+        assert_eq!(controller.cancel_reason(), Some(CancelReason::Shutdown));
+    }
+
+    #[tokio::test]
+    async fn test_completed_within() {
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            updater.complete();
+        });
+        let in_time = tokio::spawn(task.completed_within(Duration::from_secs(5)));
+        let too_late = tokio::spawn(task.completed_within(Duration::from_millis(1)));
+        task.await;
+        assert!(in_time.await.unwrap());
+        assert!(!too_late.await.unwrap());
+
+        let task = progress(1, |_| async {});
+        let cancelled = task.completed_within(Duration::from_secs(5));
+        task.await;
+        assert!(!cancelled.await);
+    }
+
+    #[tokio::test]
+    async fn test_advance_across_clones() {
+        let task = progress(10, |mut updater| async move {
+            updater.update(2);
+            let mut workers = Vec::new();
+            for _ in 0..4 {
+                let mut worker = updater.clone();
+                workers.push(tokio::spawn(async move {
+                    worker.increment();
+                    worker.advance(1);
+                }));
+            }
+            for worker in workers {
+                worker.await.unwrap();
+            }
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates: Vec<_> = updates
+            .map(|update| (update.current(), update.state()))
+            .collect()
+            .await;
+        // Workers that finish without completing leave the outcome to the others.
+        let mut expected: Vec<_> = (2..=10).map(|current| (current, State::Working)).collect();
+        expected.push((10, State::Completed));
+        assert_eq!(updates, expected);
+    }
+
+    #[tokio::test]
+    async fn test_total_and_state_across_clones() {
+        let task = progress(10, |mut updater| async move {
+            let mut worker = updater.clone();
+            worker.set_total(20);
+            worker.set_extra_state(Some("Indexing"));
+            worker.update_secondary(1, 2);
+            updater.update(5);
+            assert_eq!(updater.total(), 20);
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let last = updates.collect::<Vec<_>>().await.pop().unwrap();
+        assert_eq!((last.current(), last.total()), (5, 20));
+        assert_eq!(last.extra_state(), Some("Indexing"));
+        assert_eq!(last.secondary(), Some(Dimension::new(1, 2)));
+    }
+
+    #[tokio::test]
+    async fn test_indeterminate() {
+        let task = progress_indeterminate(|mut updater| async move {
+            updater.update(512);
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates: Vec<_> = updates.collect().await;
+        assert!(updates[0].is_indeterminate());
+        assert_eq!(updates[0].known_fraction(), None);
+        assert_eq!(updates[0].current(), 512);
+        assert!(!updates[1].is_indeterminate());
+        assert_eq!(updates[1].known_fraction(), Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_fail_with_message() {
+        let task = progress(10, |mut updater| async move {
+            updater.update(4);
+            updater.fail_with_message("disk full");
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates: Vec<_> = updates.collect().await;
+        assert_eq!(updates.len(), 2);
+        assert!(updates[1].is_failed());
+        assert_eq!(updates[1].current(), 4);
+        assert_eq!(updates[1].message(), Some("disk full"));
+    }
+
+    #[tokio::test]
+    async fn test_active_items_across_clones() {
+        let task = progress(2, |mut updater| async move {
+            let worker = updater.clone();
+            updater.item_started("a");
+            worker.item_started("b");
+            updater.item_finished("a");
+            updater.update(1);
+            worker.item_finished("b");
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let items: Vec<_> = updates
+            .map(|update| update.active_items().join(","))
+            .collect()
+            .await;
+        assert_eq!(items, ["a", "a,b", "b", "b", "", ""]);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_filtered() {
+        let task = progress(100, |mut updater| async move {
+            for i in 1..=100 {
+                updater.update(i);
+            }
+            updater.complete();
+        });
+        let even_tens =
+            task.subscribe_filtered(|update| !update.is_working() || update.current() % 20 == 0);
+        let first = task.subscribe_filtered(|update| update.current() == 1);
+        drop(first);
+        task.await;
+
+        let currents: Vec<_> = even_tens.map(|update| update.current()).collect().await;
+        assert_eq!(currents, [20, 40, 60, 80, 100, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_secondary_dimension() {
+        let task = progress(300, |mut updater| async move {
+            updater.update_secondary(0, 2);
+            updater.update(100);
+            updater.update(200);
+            updater.update_secondary(1, 2);
+            updater.update(300);
+            updater.update_secondary(2, 2);
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates: Vec<_> = updates.collect().await;
+        let files: Vec<_> = updates
+            .iter()
+            .map(|update| update.secondary().map(|files| files.current()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(2)
+            ]
+        );
+        let last = updates.last().unwrap();
+        assert_eq!(last.current(), 300);
+        assert_eq!(last.secondary(), Some(Dimension::new(2, 2)));
+    }
+
+    #[tokio::test]
+    async fn test_progress_through_smart_pointers() {
+        async fn total_of(task: impl Progress) -> u64 {
+            let mut updates = task.progress();
+            task.await;
+            updates.next().await.map_or(0, |update| update.total())
+        }
+
+        let boxed = Box::pin(progress(3, |mut updater| async move { updater.update(1) }));
+        assert_eq!(total_of(boxed).await, 3);
+
+        let mut pinned = Box::pin(progress(5, |mut updater| async move { updater.update(1) }));
+        assert_eq!(total_of(&mut pinned).await, 5);
+        assert!(pinned.transitions().entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_progress_shared() {
+        let task = progress(2, |mut updater| async move {
+            updater.update_with_message(1, "first");
+            updater.complete();
+        });
+        let first = task.progress_shared();
+        let second = task.progress_shared();
+        task.await;
+
+        let first: Vec<_> = first.collect().await;
+        let second: Vec<_> = second.collect().await;
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].message(), Some("first"));
+        assert!(first.iter().zip(&second).all(|(a, b)| Arc::ptr_eq(a, b)));
+    }
+
+    #[tokio::test]
+    async fn test_progress_inline() {
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let observed = states.clone();
+        let result = progress_inline(
+            2,
+            move |update| lock(&observed).push((update.current(), update.state())),
+            |mut updater| async move {
+                updater.update(1);
+                updater.pause();
+                updater.complete();
+                "done"
+            },
+        )
+        .await;
+
+        assert_eq!(result, "done");
+        assert_eq!(
+            *lock(&states),
+            [
+                (1, State::Working),
+                (1, State::Paused),
+                (1, State::Completed)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_observer_reports_into_its_own_task() {
+        let slot = Arc::new(Mutex::new(None::<ProgressUpdater>));
+        let currents = Arc::new(Mutex::new(Vec::new()));
+        let (reporter, observed) = (slot.clone(), currents.clone());
+        let task = progress_inline(
+            2,
+            move |update| {
+                lock(&observed).push(update.current());
+                if let Some(updater) = &*lock(&reporter) {
+                    updater.item_started("echo");
+                }
+            },
+            |mut updater| {
+                *lock(&slot) = Some(updater.clone());
+                async move {
+                    updater.update(1);
+                    updater.update(2);
+                    updater.complete();
+                }
+            },
+        );
+
+        assert_eq!(task.now_or_never(), Some(()));
+        assert_eq!(*lock(&currents), [1, 2, 2]);
+        lock(&slot).take();
+    }
+
+    #[test]
+    fn test_observer_feedback_is_bounded() {
+        fn start(calls: &Arc<AtomicUsize>) {
+            let observer = calls.clone();
+            let task = progress_inline(
+                1,
+                move |_| {
+                    observer.fetch_add(1, Ordering::Relaxed);
+                    start(&observer);
+                },
+                |mut updater| async move {
+                    updater.update(1);
+                    updater.complete();
+                },
+            );
+            assert_eq!(task.now_or_never(), Some(()));
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        start(&calls);
+        // Each task reports twice; observers run for eight levels of nesting.
+        assert_eq!(
+            calls.load(Ordering::Relaxed),
+            2 + 4 + 8 + 16 + 32 + 64 + 128 + 256
+        );
+    }
+
+    #[test]
+    fn test_streams_receive_nested_updates() {
+        let _levels: Vec<_> = core::iter::from_fn(Nested::enter).collect();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let inline = progress_inline(
+            1,
+            move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            },
+            |mut updater| async move { updater.update(1) },
+        );
+        assert_eq!(inline.now_or_never(), Some(()));
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+
+        let task = progress(1, |mut updater| async move { updater.update(1) });
+        let filtered = task.subscribe_filtered(|_| true);
+        let compact = task.progress_compact();
+        assert_eq!(task.now_or_never(), Some(()));
+        assert_eq!(
+            filtered.collect::<Vec<_>>().now_or_never().unwrap().len(),
+            2
+        );
+        assert_eq!(compact.collect::<Vec<_>>().now_or_never().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_await_confirmation() {
+        let task = progress(1, |updater| async move {
+            let first = updater.await_confirmation("Overwrite a?").await;
+            let second = updater.await_confirmation("Overwrite b?").await;
+            (first, second)
+        });
+        let controller = task.controller();
+        let mut updates = task.progress();
+        let answers = tokio::spawn(async move {
+            let mut prompts = Vec::new();
+            while let Some(update) = updates.next().await {
+                if let Some(ProgressEvent::NeedsConfirmation { id, prompt }) = update.event() {
+                    assert!(update.is_paused());
+                    prompts.push(prompt.clone());
+                    if prompts.len() == 2 {
+                        controller.deny(*id);
+                    }
+                    // Ignored for the second request, which was already denied.
+                    controller.approve(*id);
+                }
+            }
+            prompts
+        });
+        assert_eq!(task.await, (true, false));
+        assert_eq!(answers.await.unwrap(), ["Overwrite a?", "Overwrite b?"]);
+    }
+
+    #[tokio::test]
+    async fn test_request_input() {
+        let task = progress(1, |updater| async move {
+            let overwrite = updater.await_confirmation("Overwrite?").await;
+            let name = updater.request_input("Name?").await;
+            (overwrite, name)
+        });
+        let controller = task.controller();
+        let mut updates = task.progress();
+        tokio::spawn(async move {
+            while let Some(update) = updates.next().await {
+                match update.event() {
+                    Some(ProgressEvent::NeedsConfirmation { id, .. }) => {
+                        // Answers of the wrong kind are ignored.
+                        controller.respond(*id, "yes");
+                        controller.approve(*id);
+                    }
+                    Some(ProgressEvent::NeedsInput { id, .. }) => {
+                        controller.approve(*id);
+                        controller.respond(*id, "backup");
+                    }
+                    _ => {}
+                }
+            }
+        });
+        assert_eq!(task.await, (true, Some("backup".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_ends_on_cancel() {
+        let task = progress(1, |updater| async move {
+            updater.await_confirmation("Continue?").await
+        });
+        task.controller().cancel(CancelReason::UserRequest);
+        assert!(!task.await);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint() {
+        let task = progress(2, |mut updater| async move {
+            for i in 1..=2 {
+                updater.checkpoint().await;
+                updater.update(i);
+            }
+            updater.complete();
+        });
+        let controller = task.controller();
+        let mut updates = task.progress();
+        controller.pause();
+        let observer = tokio::spawn(async move {
+            let mut states = Vec::new();
+            while let Some(update) = updates.next().await {
+                states.push((update.current(), update.state()));
+                if update.is_paused() {
+                    controller.resume();
+                    assert!(!controller.is_pause_requested());
+                }
+            }
+            states
+        });
+        task.await;
+        assert_eq!(
+            observer.await.unwrap(),
+            [
+                (0, State::Paused),
+                (0, State::Working),
+                (1, State::Working),
+                (2, State::Working),
+                (2, State::Completed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_child() {
+        let task = progress(100, |mut updater| async move {
+            let mut download = updater.child(80, 4);
+            for chunk in 1..=4 {
+                download.update(chunk);
+            }
+            download.complete();
+            let mut unpack = updater.child(20, 2);
+            let mut inner = unpack.child(1, 4);
+            inner.update(2);
+            // Dropping a child reports nothing.
+            drop(inner);
+            unpack.update(2);
+            drop(unpack);
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+        let updates: Vec<_> = updates
+            .map(|update| (update.current(), update.total(), update.state()))
+            .collect()
+            .await;
+        assert_eq!(
+            updates,
+            [
+                (20, 100, State::Working),
+                (40, 100, State::Working),
+                (60, 100, State::Working),
+                (80, 100, State::Working),
+                (80, 100, State::Working),
+                (85, 100, State::Working),
+                (100, 100, State::Working),
+                (100, 100, State::Completed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_advance_after_child() {
+        let task = progress(50, |mut updater| async move {
+            let mut child = updater.child(25, 5);
+            for i in 1..=5 {
+                child.update(i);
+            }
+            drop(child);
+            updater.advance(1);
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+        let updates: Vec<_> = updates
+            .map(|update| (update.current(), update.state()))
+            .collect()
+            .await;
+        assert_eq!(
+            updates,
+            [
+                (5, State::Working),
+                (10, State::Working),
+                (15, State::Working),
+                (20, State::Working),
+                (25, State::Working),
+                (26, State::Working),
+                (26, State::Completed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_child() {
+        let task = progress(100, |mut updater| async move {
+            let mut child = updater.child(50, 2);
+            child.update(1);
+            child.fail_with_message("disk full");
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+        let updates: Vec<_> = updates
+            .map(|update| (update.current(), update.state()))
+            .collect()
+            .await;
+        assert_eq!(updates, [(25, State::Working), (25, State::Failed)]);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_ends_on_cancel() {
+        let task = progress(1, |updater| async move {
+            updater.checkpoint().await;
+            updater.cancel_requested()
+        });
+        let controller = task.controller();
+        controller.pause();
+        controller.cancel(CancelReason::Shutdown);
+        assert_eq!(task.await, Some(CancelReason::Shutdown));
+    }
+
+    #[tokio::test]
+    async fn test_time_budget_suspends() {
+        let task = progress(3, |mut updater| async move {
+            for i in 1..=3 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                updater.update(i);
+            }
+        })
+        .with_time_budget(Duration::from_millis(30), BudgetAction::Suspend);
+        let controller = task.controller();
+        let mut updates = task.progress();
+        let observer = tokio::spawn(async move {
+            let mut budgets = Vec::new();
+            while let Some(update) = updates.next().await {
+                if let Some(ProgressEvent::BudgetExhausted { budget }) = update.event() {
+                    assert!(update.is_paused());
+                    budgets.push(*budget);
+                    controller.extend_budget(Duration::from_secs(1));
+                }
+            }
+            budgets
+        });
+        task.await;
+        assert_eq!(observer.await.unwrap(), [Duration::from_millis(30)]);
+    }
+
+    #[tokio::test]
+    async fn test_time_budget_cancels() {
+        let task = progress(0, |updater| async move {
+            updater.cancelled().await;
+            updater.cancel_requested()
+        })
+        .with_time_budget(Duration::from_millis(10), BudgetAction::Cancel);
+        assert_eq!(task.await, Some(CancelReason::Timeout));
+    }
+
+    #[tokio::test]
+    async fn test_auto_tick() {
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            updater.auto_tick(Duration::from_millis(5));
+            // Busy without reporting, blocking the executor thread.
+            std::thread::sleep(Duration::from_millis(50));
+            updater.update(2);
+        });
+        let updates = task.progress();
+        task.await;
+        let updates: Vec<_> = updates.collect().await;
+        let heartbeats: Vec<_> = updates
+            .iter()
+            .filter(|update| update.is_heartbeat())
+            .collect();
+        assert!(heartbeats.len() > 2, "{updates:?}");
+        assert!(heartbeats.iter().all(|update| update.current() == 1));
+        let ages: Vec<_> = heartbeats
+            .iter()
+            .filter_map(|update| update.last_progress_age())
+            .collect();
+        assert!(ages.windows(2).all(|w| w[0] < w[1]), "{ages:?}");
+        assert!(
+            updates
+                .windows(2)
+                .all(|w| w[0].sequence() < w[1].sequence())
+        );
+        assert_eq!(updates.last().unwrap().current(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_auto_tick_ends_with_the_task() {
+        let task = progress(2, |mut updater| async move {
+            updater.update(1);
+            updater.auto_tick(Duration::from_hours(1));
+            updater.auto_tick(Duration::from_hours(1));
+        });
+        let updates = task.progress();
+        task.await;
+
+        let updates = tokio::time::timeout(Duration::from_secs(5), updates.collect::<Vec<_>>())
+            .await
+            .expect("the stream ends once the updater is dropped");
+        assert!(!updates.iter().any(ProgressUpdate::is_heartbeat));
+        assert!(updates.last().unwrap().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_split() {
+        let (task, handle) = progress(2, |mut updater| async move {
+            updater.update(1);
+            updater.cancel_requested()
+        })
+        .split();
+        let updates = handle.clone().progress();
+        handle.cancel(CancelReason::UserRequest);
+        assert_eq!(task.await, Some(CancelReason::UserRequest));
+
+        assert!(handle.is_finished());
+        let currents: Vec<_> = updates.map(|update| update.current()).collect().await;
+        assert_eq!(currents, [1, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_recv_latest() {
+        let (task, mut handle) = progress(3, |mut updater| async move {
+            for i in 1..=3 {
+                updater.update(i);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            updater.complete();
+        })
+        .split();
+        assert_eq!(handle.latest(), None);
+        let reader = std::thread::spawn(move || {
+            let mut currents = Vec::new();
+            while let Some(update) = handle.blocking_recv_latest() {
+                currents.push(update.current());
+            }
+            (currents, handle.latest())
+        });
+        task.await;
+
+        let (currents, latest) = reader.join().unwrap();
+        assert!(currents.windows(2).all(|w| w[0] <= w[1]), "{currents:?}");
+        assert_eq!(currents.last(), Some(&3));
+        assert!(latest.unwrap().is_completed());
+    }
+
+    #[cfg(all(feature = "debug-callsite", debug_assertions))]
+    #[tokio::test]
+    async fn test_callsite() {
+        let task = progress(2, |mut updater| async move {
+            let update_line = line!() + 1;
+            updater.update(1);
+            let counting = crate::CountingUpdater::new(updater, "checked");
+            let start_line = line!() + 1;
+            counting.start("a");
+            (update_line, start_line)
+        });
+        let updates = task.progress();
+        let (update_line, start_line) = task.await;
+
+        let lines: Vec<_> = updates
+            .map(|update| update.callsite().unwrap().line())
+            .collect()
+            .await;
+        assert_eq!(lines[0], update_line);
+        assert_eq!(lines[1], start_line);
+
+        let mut moved = ProgressUpdate::new(2, 1, State::Working, None);
+        moved.callsite.location = Some(core::panic::Location::caller());
+        assert_eq!(moved, ProgressUpdate::new(2, 1, State::Working, None));
+    }
+
+    #[tokio::test]
+    async fn test_unit_cost() {
+        let task = progress(10, |mut updater| async move {
+            updater.update(4);
+        })
+        .with_unit_cost(0.5);
+        let updates = task.progress();
+        task.await;
+        let update = updates.collect::<Vec<_>>().await.remove(0);
+        assert_eq!(update.cost(), Some(2.0));
+        assert_eq!(update.projected_cost(), Some(5.0));
+    }
+
+    #[tokio::test]
+    async fn test_message_budget() {
+        let task = progress(2, |mut updater| async move {
+            updater.update_with_message(1, "é".repeat(100));
+            updater.update_with_message(2, "short");
+        })
+        .with_message_budget(32 * 9);
+        let updates = task.progress();
+        task.await;
+
+        let messages: Vec<_> = updates
+            .filter_map(|update| async move { update.message().map(str::to_owned) })
+            .collect()
+            .await;
+        assert_eq!(messages, ["éééé", "short"]);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_numbers_across_clones() {
+        let task = progress(10, |mut updater| async move {
+            let mut worker = updater.clone();
+            updater.update(1);
+            worker.update(2);
+            updater.update(3);
+            worker.complete();
+            updater.complete();
+        });
+        let updates = task.progress();
+        task.await;
+
+        let sequences: Vec<_> = updates.map(|update| update.sequence()).collect().await;
+        assert_eq!(sequences, [0, 1, 2, 3]);
+    }
 }
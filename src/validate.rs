@@ -0,0 +1,206 @@
+use core::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::ProgressUpdate;
+
+/// An invariant of a progress stream that an update violated.
+///
+/// Reported by [`ProgressValidator`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// The current value exceeds a known total that is not flagged as
+    /// [estimated](ProgressUpdate::is_total_estimated).
+    CurrentExceedsTotal {
+        /// The current value of the update.
+        current: u64,
+        /// The total of the update.
+        total: u64,
+    },
+    /// An update arrived after a completed, cancelled or failed one.
+    UpdateAfterTerminal,
+    /// The sequence number is lower than that of the previous update.
+    SequenceWentBackwards {
+        /// The sequence number of the previous update.
+        previous: u64,
+        /// The sequence number of this update.
+        sequence: u64,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CurrentExceedsTotal { current, total } => {
+                write!(f, "current value {current} exceeds total {total}")
+            }
+            Self::UpdateAfterTerminal => f.write_str("update after a terminal state"),
+            Self::SequenceWentBackwards { previous, sequence } => {
+                write!(
+                    f,
+                    "sequence number went backwards from {previous} to {sequence}"
+                )
+            }
+        }
+    }
+}
+
+/// The default violation handler: panics in debug builds and logs to standard error
+/// otherwise.
+fn report(violation: Violation, update: &ProgressUpdate) {
+    #[cfg(debug_assertions)]
+    panic!("progress invariant violated: {violation} in {update:?}");
+    #[cfg(not(debug_assertions))]
+    eprintln!("progress invariant violated: {violation} in {update:?}");
+}
+
+pin_project! {
+    /// A stream adapter that checks progress updates for emission bugs.
+    ///
+    /// Created by [`ProgressStreamExt::validate`](crate::ProgressStreamExt::validate).
+    /// Updates pass through unchanged; every [`Violation`] is handed to the handler,
+    /// which by default panics in debug builds and logs to standard error in release
+    /// builds. Library authors can wrap the progress of their tasks in integration
+    /// tests to catch updates that observers would mis-render.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[derive(Debug)]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct ProgressValidator<S, F = fn(Violation, &ProgressUpdate)> {
+        #[pin]
+        stream: S,
+        handler: F,
+        previous: Option<(u64, bool)>,
+    }
+}
+
+impl<S> ProgressValidator<S> {
+    /// Wraps `stream` with the default handler.
+    pub fn new(stream: S) -> Self {
+        Self::with_handler(stream, report)
+    }
+}
+
+impl<S, F> ProgressValidator<S, F>
+where
+    F: FnMut(Violation, &ProgressUpdate),
+{
+    /// Wraps `stream`, handing every violation to `handler`.
+    pub const fn with_handler(stream: S, handler: F) -> Self {
+        Self {
+            stream,
+            handler,
+            previous: None,
+        }
+    }
+}
+
+impl<S, F> Stream for ProgressValidator<S, F>
+where
+    S: Stream<Item = ProgressUpdate>,
+    F: FnMut(Violation, &ProgressUpdate),
+{
+    type Item = ProgressUpdate;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let Some(update) = ready!(this.stream.poll_next(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        let (current, total) = (update.current(), update.total());
+        if total != 0 && current > total && !update.is_total_estimated() {
+            (this.handler)(Violation::CurrentExceedsTotal { current, total }, &update);
+        }
+        if let Some((previous, terminal)) = *this.previous {
+            if terminal {
+                (this.handler)(Violation::UpdateAfterTerminal, &update);
+            }
+            if update.sequence() < previous {
+                let sequence = update.sequence();
+                (this.handler)(
+                    Violation::SequenceWentBackwards { previous, sequence },
+                    &update,
+                );
+            }
+        }
+        let terminal = update.is_terminal();
+        *this.previous = Some((update.sequence(), terminal));
+
+        Poll::Ready(Some(update))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{StreamExt, stream};
+
+    use super::*;
+    use crate::{Progress, ProgressStreamExt, State, progress};
+
+    #[tokio::test]
+    async fn test_well_behaved_task_passes() {
+        let task = progress(3, |mut updater| async move {
+            updater.update(1);
+            updater.pause();
+            updater.update(3);
+            updater.complete();
+        });
+        let updates = task.progress().validate();
+        task.await;
+        assert_eq!(updates.count().await, 4);
+    }
+
+    #[tokio::test]
+    async fn test_violations_are_reported() {
+        let updates = [
+            ProgressUpdate::new(10, 12, State::Working, None).with_sequence(4),
+            ProgressUpdate::new(10, 10, State::Completed, None).with_sequence(3),
+            ProgressUpdate::new(10, 10, State::Working, None).with_sequence(5),
+        ];
+        let mut violations = Vec::new();
+        ProgressValidator::with_handler(stream::iter(updates), |violation, _: &ProgressUpdate| {
+            violations.push(violation);
+        })
+        .for_each(|_| async {})
+        .await;
+
+        assert_eq!(
+            violations,
+            [
+                Violation::CurrentExceedsTotal {
+                    current: 12,
+                    total: 10
+                },
+                Violation::SequenceWentBackwards {
+                    previous: 4,
+                    sequence: 3
+                },
+                Violation::UpdateAfterTerminal,
+            ]
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    #[should_panic(expected = "update after a terminal state")]
+    async fn test_default_handler_panics_in_debug() {
+        let updates = [
+            ProgressUpdate::new(1, 1, State::Cancelled, None),
+            ProgressUpdate::new(1, 1, State::Working, None),
+        ];
+        stream::iter(updates)
+            .validate()
+            .for_each(|_| async {})
+            .await;
+    }
+}
@@ -0,0 +1,226 @@
+use core::fmt;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::{ProgressUpdate, sync::lock};
+
+#[derive(Debug, Default)]
+struct Record {
+    sent: Vec<u64>,
+    received: Vec<u64>,
+}
+
+/// Compares the updates a task sent with the updates one of its streams received.
+///
+/// Created by [`ProgressFuture::verifier`](crate::ProgressFuture::verifier), which
+/// records the [`sequence`](ProgressUpdate::sequence) of every update the task reports
+/// from then on. Wrap the stream under test with [`verify`](Self::verify), and once it
+/// ended, [`report`](Self::report) lists the updates it missed and the ones it received
+/// out of order. This shows what a channel capacity or a throttling setting costs an
+/// observer before it is shipped.
+///
+/// Verify one stream per verifier; create another verifier for another stream.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use progressor::{Granularity, Progress, ProgressStreamExt, progress};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() {
+/// let task = progress(1000, |mut updater| async move {
+///     for i in 1..=1000 {
+///         updater.update(i);
+///     }
+///     updater.complete();
+/// });
+/// let verifier = task.verifier();
+/// let updates = verifier.verify(
+///     task.progress()
+///         .with_granularity(Granularity::new().with_min_interval(Duration::from_millis(50))),
+/// );
+/// tokio::spawn(task);
+///
+/// updates.for_each(|update| async move { println!("{}", update.current()) }).await;
+/// let report = verifier.report();
+/// println!("{report}");
+/// assert!(report.reordered().is_empty());
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct DeliveryVerifier {
+    record: Arc<Mutex<Record>>,
+}
+
+impl DeliveryVerifier {
+    /// Creates a verifier and the function recording sent updates, which stops once the
+    /// verifier is dropped.
+    pub(crate) fn new() -> (
+        Self,
+        impl Fn(&ProgressUpdate) -> bool + Send + Sync + 'static,
+    ) {
+        let record = Arc::new(Mutex::new(Record::default()));
+        let weak = Arc::downgrade(&record);
+        let forward = move |update: &ProgressUpdate| {
+            weak.upgrade().is_some_and(|record| {
+                lock(&record).sent.push(update.sequence());
+                true
+            })
+        };
+        (Self { record }, forward)
+    }
+
+    /// Passes the updates of `stream` through, recording each one received.
+    pub fn verify<S>(&self, stream: S) -> impl Stream<Item = ProgressUpdate> + use<S>
+    where
+        S: Stream<Item = ProgressUpdate>,
+    {
+        let record = self.record.clone();
+        stream.inspect(move |update| lock(&record).received.push(update.sequence()))
+    }
+
+    /// Compares the updates sent so far with the updates received so far.
+    ///
+    /// Updates still on their way count as dropped, so call this once the verified
+    /// stream ended.
+    #[must_use]
+    pub fn report(&self) -> DeliveryReport {
+        let record = lock(&self.record);
+        let received: HashSet<u64> = record.received.iter().copied().collect();
+        let dropped = record
+            .sent
+            .iter()
+            .copied()
+            .filter(|sequence| !received.contains(sequence))
+            .collect();
+        let mut reordered = Vec::new();
+        let mut highest = None;
+        for &sequence in &record.received {
+            if highest.is_some_and(|highest| sequence < highest) {
+                reordered.push(sequence);
+            } else {
+                highest = Some(sequence);
+            }
+        }
+        DeliveryReport {
+            sent: record.sent.len(),
+            received: record.received.len(),
+            dropped,
+            reordered,
+        }
+    }
+}
+
+/// How the updates a stream received diverge from the updates its task sent, see
+/// [`DeliveryVerifier`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryReport {
+    sent: usize,
+    received: usize,
+    dropped: Vec<u64>,
+    reordered: Vec<u64>,
+}
+
+impl DeliveryReport {
+    /// Returns the number of updates the task sent.
+    #[must_use]
+    pub const fn sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Returns the number of updates the stream received.
+    #[must_use]
+    pub const fn received(&self) -> usize {
+        self.received
+    }
+
+    /// Returns the sequence numbers of the sent updates the stream never received.
+    #[must_use]
+    pub fn dropped(&self) -> &[u64] {
+        &self.dropped
+    }
+
+    /// Returns the sequence numbers of the updates received after a later one.
+    #[must_use]
+    pub fn reordered(&self) -> &[u64] {
+        &self.reordered
+    }
+
+    /// Returns whether the stream received every update, in order.
+    #[must_use]
+    pub const fn is_exact(&self) -> bool {
+        self.dropped.is_empty() && self.reordered.is_empty()
+    }
+}
+
+impl fmt::Display for DeliveryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "received {} of {} updates, {} dropped, {} out of order",
+            self.received,
+            self.sent,
+            self.dropped.len(),
+            self.reordered.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::{Progress, State, progress};
+
+    #[tokio::test]
+    async fn test_exact_delivery() {
+        let task = progress(10, |mut updater| async move {
+            for i in 1..=10 {
+                updater.update(i);
+            }
+            updater.complete();
+        });
+        let verifier = task.verifier();
+        let updates = verifier.verify(task.progress());
+        task.await;
+
+        assert_eq!(updates.count().await, 11);
+        let report = verifier.report();
+        assert!(report.is_exact(), "{report}");
+        assert_eq!(report.sent(), 11);
+    }
+
+    #[tokio::test]
+    async fn test_drops_and_reordering() {
+        let task = progress(40, |mut updater| async move {
+            for i in 1..=40 {
+                updater.update(i);
+            }
+            updater.complete();
+        });
+        let verifier = task.verifier();
+        let updates = verifier.verify(task.progress());
+        task.await;
+        updates.for_each(|_| async {}).await;
+
+        let report = verifier.report();
+        assert_eq!((report.sent(), report.received()), (41, 32));
+        assert_eq!(report.dropped(), (32..=40).collect::<Vec<_>>());
+
+        let verifier = progress(1, |_| async {}).verifier();
+        let update =
+            |sequence| ProgressUpdate::new(1, 0, State::Working, None).with_sequence(sequence);
+        let updates = verifier.verify(stream::iter([update(1), update(0), update(2)]));
+        updates.for_each(|_| async {}).await;
+        assert_eq!(verifier.report().reordered(), [0]);
+    }
+}